@@ -0,0 +1,32 @@
+"""Advent of Code 2023 solutions implemented in Rust."""
+
+def solve(day: int, part: int, input: str) -> str:
+    """Solve given part of the day with `input`.
+
+    Raises `ValueError` for unknown days or parts and `RuntimeError` if the solution fails.
+    """
+
+def days() -> list[int]:
+    """Return the list of implemented days."""
+
+def answers(day: int) -> tuple[str, str] | None:
+    """Return the known answers for the day's puzzle input if the day is solved."""
+
+class Almanac:
+    """Day 5 almanac which maps seeds to their locations."""
+
+    def __init__(self, input: str) -> None: ...
+    @property
+    def seeds(self) -> list[int]: ...
+    def location(self, seed: int) -> int: ...
+    def locations(self, seeds: list[int]) -> list[int]: ...
+
+class Workflows:
+    """Day 19 workflows which accept or reject parts.
+
+    `input` is either the workflows or the whole puzzle input.
+    """
+
+    def __init__(self, input: str) -> None: ...
+    def accepts(self, x: int, m: int, a: int, s: int) -> bool: ...
+    def workflow_names(self) -> list[str]: ...
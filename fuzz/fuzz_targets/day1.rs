@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Runs every implementation of both parts
+fuzz_target!(|input: &str| {
+    let day = aoc2023::days::get(1).unwrap();
+    for implementation in day.parts.into_iter().flatten() {
+        let _ = (implementation.solver)(input);
+    }
+});
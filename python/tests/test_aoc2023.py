@@ -0,0 +1,129 @@
+import textwrap
+
+import pytest
+
+import aoc2023
+
+DAY5_EXAMPLE = textwrap.dedent(
+    """\
+    seeds: 79 14 55 13
+
+    seed-to-soil map:
+    50 98 2
+    52 50 48
+
+    soil-to-fertilizer map:
+    0 15 37
+    37 52 2
+    39 0 15
+
+    fertilizer-to-water map:
+    49 53 8
+    0 11 42
+    42 0 7
+    57 7 4
+
+    water-to-light map:
+    88 18 7
+    18 25 70
+
+    light-to-temperature map:
+    45 77 23
+    81 45 19
+    68 64 13
+
+    temperature-to-humidity map:
+    0 69 1
+    1 0 69
+
+    humidity-to-location map:
+    60 56 37
+    56 93 4
+    """
+)
+
+DAY19_EXAMPLE = textwrap.dedent(
+    """\
+    px{a<2006:qkq,m>2090:A,rfg}
+    pv{a>1716:R,A}
+    lnx{m>1548:A,A}
+    rfg{s<537:gd,x>2440:R,A}
+    qs{s>3448:A,lnx}
+    qkq{x<1416:A,crn}
+    crn{x>2662:A,R}
+    in{s<1351:px,qqz}
+    qqz{s>2770:qs,m<1801:hdj,R}
+    gd{a>3333:R,R}
+    hdj{m>838:A,pv}
+
+    {x=787,m=2655,a=1222,s=2876}
+    {x=1679,m=44,a=2067,s=496}
+    {x=2036,m=264,a=79,s=2244}
+    {x=2461,m=1339,a=466,s=291}
+    {x=2127,m=1623,a=2188,s=1013}
+    """
+)
+
+
+def test_days():
+    days = aoc2023.days()
+    assert 1 in days
+    assert days == sorted(days)
+
+
+def test_solve():
+    assert aoc2023.solve(9, 1, "0 3 6 9 12 15") == "18"
+    assert aoc2023.solve(5, 1, DAY5_EXAMPLE) == "35"
+    assert aoc2023.solve(5, 2, DAY5_EXAMPLE) == "46"
+    assert aoc2023.solve(19, 2, DAY19_EXAMPLE) == "167409079868000"
+
+
+def test_solve_errors():
+    with pytest.raises(ValueError):
+        aoc2023.solve(25, 1, "")
+    with pytest.raises(ValueError):
+        aoc2023.solve(9, 3, "0 3 6 9 12 15")
+    with pytest.raises(RuntimeError):
+        aoc2023.solve(9, 1, "0 3 x")
+
+
+def test_answers():
+    assert aoc2023.answers(1) == ("56049", "54530")
+    assert aoc2023.answers(12) is None
+
+
+def test_almanac():
+    almanac = aoc2023.Almanac(DAY5_EXAMPLE)
+    assert almanac.seeds == [79, 14, 55, 13]
+    assert almanac.location(79) == 82
+    assert almanac.locations(almanac.seeds) == [82, 43, 86, 35]
+
+
+def test_almanac_invalid_input():
+    with pytest.raises(ValueError):
+        aoc2023.Almanac("seeds: 1 2 3")
+
+
+def test_workflows():
+    workflows = aoc2023.Workflows(DAY19_EXAMPLE)
+    assert "in" in workflows.workflow_names()
+    assert workflows.accepts(787, 2655, 1222, 2876)
+    assert not workflows.accepts(1679, 44, 2067, 496)
+
+    only_workflows = aoc2023.Workflows(DAY19_EXAMPLE.split("\n\n")[0])
+    assert only_workflows.accepts(2127, 1623, 2188, 1013)
+
+
+def test_workflows_missing_in():
+    with pytest.raises(ValueError):
+        aoc2023.Workflows("px{a<2006:A,R}")
+
+
+def test_workflows_unknown_workflow():
+    with pytest.raises(ValueError):
+        aoc2023.Workflows("in{s>1:px,R}")
+
+
+def test_workflows_cycle():
+    with pytest.raises(ValueError):
+        aoc2023.Workflows("in{s>1:a,R}\na{in}")
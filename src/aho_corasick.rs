@@ -0,0 +1,286 @@
+//! Searching for many patterns at once with an Aho–Corasick automaton.
+//!
+//! The patterns are put into a trie, every node gets a link to the node of its longest proper
+//! suffix, and the links are followed ahead of time so that every state has a transition for every
+//! byte. Searching is then one table lookup per byte of the haystack, no matter how many patterns
+//! there are or how they overlap.
+//!
+//! Only the bytes which appear in the patterns get their own column in the table, all the other
+//! bytes share one column which leads back to the root.
+
+use std::collections::VecDeque;
+
+/// Pattern found in the haystack, `start..end` are the positions of its bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Match {
+    /// Index of the pattern in the list given to [`AhoCorasick::new`]
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    /// Column of every byte in `next`
+    classes: [u16; 256],
+    /// Number of columns
+    stride: usize,
+    /// Transitions, the row of a state starts at `state * stride`
+    next: Vec<u32>,
+    /// Longest pattern which ends in the state, with the suffix links followed
+    longest: Vec<Option<u32>>,
+    /// Pattern which is exactly the string of the state
+    own: Vec<Option<u32>>,
+    /// Nearest state along the suffix links which has an [`AhoCorasick::own`] pattern
+    output_link: Vec<Option<u32>>,
+    lens: Vec<usize>,
+}
+
+const ROOT: u32 = 0;
+const NONE: u32 = u32::MAX;
+
+impl AhoCorasick {
+    /// # Panics
+    /// If a pattern is empty. If the same pattern is given twice the first one is reported.
+    pub fn new<P: AsRef<[u8]>>(patterns: impl IntoIterator<Item = P>) -> Self {
+        let patterns = patterns.into_iter().collect::<Vec<_>>();
+
+        let mut classes = [0u16; 256];
+        let mut stride = 1;
+        for &b in patterns.iter().flat_map(|p| p.as_ref()) {
+            if classes[b as usize] == 0 {
+                classes[b as usize] = stride as u16;
+                stride += 1;
+            }
+        }
+
+        let mut automaton = AhoCorasick {
+            classes,
+            stride,
+            next: vec![NONE; stride],
+            longest: vec![None],
+            own: vec![None],
+            output_link: vec![None],
+            lens: Vec::new(),
+        };
+
+        // the trie
+        for (i, pattern) in patterns.iter().enumerate() {
+            let pattern = pattern.as_ref();
+            assert!(!pattern.is_empty(), "pattern {i} is empty");
+            let mut state = ROOT;
+            for &b in pattern {
+                let index = automaton.index(state, b);
+                if automaton.next[index] == NONE {
+                    automaton.next[index] = automaton.own.len() as u32;
+                    automaton.next.extend(std::iter::repeat_n(NONE, stride));
+                    automaton.longest.push(None);
+                    automaton.own.push(None);
+                    automaton.output_link.push(None);
+                }
+                state = automaton.next[index];
+            }
+            automaton.own[state as usize].get_or_insert(i as u32);
+            automaton.lens.push(pattern.len());
+        }
+
+        // breadth first, so the suffix of every state is done before the state
+        let mut fail = vec![ROOT; automaton.own.len()];
+        let mut queue = VecDeque::new();
+        for class in 0..stride {
+            match automaton.next[class] {
+                NONE => automaton.next[class] = ROOT,
+                child => queue.push_back(child),
+            }
+        }
+        while let Some(state) = queue.pop_front() {
+            let s = state as usize;
+            let suffix = fail[s] as usize;
+            automaton.output_link[s] = match automaton.own[suffix] {
+                Some(_) => Some(suffix as u32),
+                None => automaton.output_link[suffix],
+            };
+            automaton.longest[s] = automaton.own[s].or(automaton.longest[suffix]);
+
+            for class in 0..stride {
+                let index = s * stride + class;
+                let suffix_next = automaton.next[suffix * stride + class];
+                match automaton.next[index] {
+                    NONE => automaton.next[index] = suffix_next,
+                    child => {
+                        fail[child as usize] = suffix_next;
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+
+        automaton
+    }
+
+    fn index(&self, state: u32, byte: u8) -> usize {
+        state as usize * self.stride + self.classes[byte as usize] as usize
+    }
+
+    pub fn pattern_count(&self) -> usize {
+        self.lens.len()
+    }
+
+    pub fn pattern_len(&self, pattern: usize) -> usize {
+        self.lens[pattern]
+    }
+
+    fn found(&self, pattern: u32, end: usize) -> Match {
+        let pattern = pattern as usize;
+        Match {
+            pattern,
+            start: end - self.lens[pattern],
+            end,
+        }
+    }
+
+    /// The match which ends first, the longest one if several end at the same position
+    ///
+    /// The haystack is any sequence of bytes, for example a line read backwards.
+    pub fn find(&self, haystack: impl IntoIterator<Item = u8>) -> Option<Match> {
+        let mut state = ROOT;
+        for (i, b) in haystack.into_iter().enumerate() {
+            state = self.next[self.index(state, b)];
+            if let Some(pattern) = self.longest[state as usize] {
+                return Some(self.found(pattern, i + 1));
+            }
+        }
+        None
+    }
+
+    /// The match which starts first, the longest one if several start at the same position
+    ///
+    /// Unlike [`AhoCorasick::find`] a pattern inside a longer one doesn't hide it, the search
+    /// goes on until no pattern that starts earlier can end anymore.
+    pub fn find_leftmost(&self, haystack: impl IntoIterator<Item = u8>) -> Option<Match> {
+        let max_len = self.lens.iter().copied().max().unwrap_or(0);
+        let mut best: Option<Match> = None;
+        let mut state = ROOT;
+        for (i, b) in haystack.into_iter().enumerate() {
+            if best.is_some_and(|m| i + 1 > m.start + max_len) {
+                break;
+            }
+            state = self.next[self.index(state, b)];
+            for m in self.matches_ending(state, i + 1) {
+                // at the same end the longer ones come first, so equal starts only follow later
+                if best.is_none_or(|b| m.start < b.start || (m.start == b.start && m.end > b.end)) {
+                    best = Some(m);
+                }
+            }
+        }
+        best
+    }
+
+    /// All the matches, also the overlapping ones, ordered by their end and then from the longest
+    pub fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> impl Iterator<Item = Match> + 'a {
+        let mut state = ROOT;
+        haystack.iter().enumerate().flat_map(move |(i, &b)| {
+            state = self.next[self.index(state, b)];
+            self.matches_ending(state, i + 1)
+        })
+    }
+
+    /// The matches which end in `state` at `end`, from the longest
+    fn matches_ending(&self, state: u32, end: usize) -> impl Iterator<Item = Match> + '_ {
+        let first = match self.own[state as usize] {
+            Some(_) => Some(state),
+            None => self.output_link[state as usize],
+        };
+        std::iter::successors(first, |&s| self.output_link[s as usize])
+            .map(move |s| self.found(self.own[s as usize].unwrap(), end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_find_iter() {
+        let automaton = AhoCorasick::new(["he", "she", "his", "hers"]);
+        assert_eq!(automaton.pattern_count(), 4);
+        let matches = automaton
+            .find_iter(b"ushers")
+            .map(|m| (m.pattern, m.start, m.end))
+            .collect::<Vec<_>>();
+        assert_eq!(matches, [(1, 1, 4), (0, 2, 4), (3, 2, 6)]);
+    }
+
+    #[test]
+    fn test_find() {
+        let automaton = AhoCorasick::new(["one", "two", "eight", "1", "t"]);
+        let m = automaton.find(*b"xeightwo").unwrap();
+        assert_eq!((m.pattern, m.start, m.end), (2, 1, 6));
+        // `t` and `eight` end at the same byte, the longer one wins
+        assert_eq!(automaton.find(*b"eight").unwrap().pattern, 2);
+        assert_eq!(
+            automaton
+                .find(b"owt".iter().rev().copied())
+                .unwrap()
+                .pattern,
+            4
+        );
+        assert_eq!(automaton.find(*b"none").unwrap().pattern, 0);
+        assert_eq!(automaton.find(*b"xyz"), None);
+        assert_eq!(AhoCorasick::new(["a", "a"]).find(*b"a").unwrap().pattern, 0);
+    }
+
+    #[test]
+    fn test_find_leftmost() {
+        // `b` ends first, but `abc` starts first
+        let automaton = AhoCorasick::new(["abc", "b"]);
+        assert_eq!(automaton.find(*b"abc").unwrap().pattern, 1);
+        let m = automaton.find_leftmost(*b"xabc").unwrap();
+        assert_eq!((m.pattern, m.start, m.end), (0, 1, 4));
+        assert_eq!(automaton.find_leftmost(*b"ab").unwrap().pattern, 1);
+
+        // the longest of the ones that start at the same position
+        let automaton = AhoCorasick::new(["a", "abcd", "ab"]);
+        assert_eq!(automaton.find_leftmost(*b"abcd").unwrap().pattern, 1);
+        assert_eq!(automaton.find_leftmost(*b"abc").unwrap().pattern, 2);
+        assert_eq!(automaton.find_leftmost(*b"xyz"), None);
+    }
+
+    /// Every occurrence of every pattern, found by comparing at each position
+    fn naive(patterns: &[String], haystack: &str) -> Vec<Match> {
+        let mut matches = Vec::new();
+        for end in 1..=haystack.len() {
+            let mut at_end = patterns
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| haystack[..end].ends_with(p.as_str()))
+                .filter(|(i, p)| !patterns[..*i].contains(p))
+                .map(|(pattern, p)| Match {
+                    pattern,
+                    start: end - p.len(),
+                    end,
+                })
+                .collect::<Vec<_>>();
+            at_end.sort_by_key(|m| m.start);
+            matches.extend(at_end);
+        }
+        matches
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_against_naive(
+            patterns in prop::collection::vec("[abc]{1,4}", 1..8),
+            haystack in "[abcd]{0,30}",
+        ) {
+            let automaton = AhoCorasick::new(&patterns);
+            let expected = naive(&patterns, &haystack);
+            prop_assert_eq!(automaton.find_iter(haystack.as_bytes()).collect::<Vec<_>>(), expected.clone());
+            prop_assert_eq!(automaton.find(haystack.bytes()), expected.first().copied());
+            let leftmost = expected.iter().min_by_key(|m| (m.start, std::cmp::Reverse(m.end)));
+            prop_assert_eq!(automaton.find_leftmost(haystack.bytes()), leftmost.copied());
+        }
+    }
+}
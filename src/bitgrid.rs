@@ -0,0 +1,411 @@
+//! Grids of booleans with every row packed into the bits of an integer.
+//!
+//! Bit `c` of a row is the cell in column `c`, so a whole row can be compared, masked or counted
+//! with a single operation. The columns are the rows of [`BitGrid::transpose`]. The grids are at
+//! most [`Bits::BITS`] cells wide.
+
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, Not, Shl, Shr};
+
+use crate::grid::{Direction, Pos};
+use crate::parse::{self, ParseError};
+
+/// Unsigned integer used for the rows
+pub trait Bits:
+    Copy
+    + Eq
+    + Hash
+    + Debug
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+    + BitAndAssign
+    + BitOrAssign
+{
+    const BITS: u32;
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn count_ones(self) -> u32;
+
+    fn trailing_zeros(self) -> u32;
+
+    /// The lowest `n` bits set
+    fn low(n: u32) -> Self {
+        if n >= Self::BITS {
+            !Self::ZERO
+        } else {
+            !(!Self::ZERO << n)
+        }
+    }
+}
+
+macro_rules! impl_bits {
+    ($($t:ty),*) => {
+        $(
+            impl Bits for $t {
+                const BITS: u32 = <$t>::BITS;
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn count_ones(self) -> u32 {
+                    <$t>::count_ones(self)
+                }
+
+                fn trailing_zeros(self) -> u32 {
+                    <$t>::trailing_zeros(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_bits!(u32, u64, u128);
+
+/// Number of bits which differ
+pub fn hamming<B: Bits>(a: B, b: B) -> u32 {
+    (a ^ b).count_ones()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid<B = u128> {
+    rows: Vec<B>,
+    cols: usize,
+}
+
+impl<B: Bits> BitGrid<B> {
+    /// # Panics
+    /// If `cols` is more than [`Bits::BITS`].
+    pub fn new(rows: usize, cols: usize) -> Self {
+        assert!(
+            cols <= B::BITS as usize,
+            "{cols} columns don't fit into {} bits",
+            B::BITS
+        );
+        BitGrid {
+            rows: vec![B::ZERO; rows],
+            cols,
+        }
+    }
+
+    /// Parses the lines of `input`, `f` tells which characters are set
+    ///
+    /// The lines must all be as wide as the first one and at most [`Bits::BITS`] wide.
+    pub fn parse(input: &str, mut f: impl FnMut(char) -> bool) -> parse::Result<Self> {
+        let lines = input.lines().map(|l| l.trim_end_matches('\r'));
+        let cols = lines.clone().next().map_or(0, |l| l.chars().count());
+        if cols > B::BITS as usize {
+            return Err(ParseError::TooLarge {
+                what: "grid width",
+                max: B::BITS as usize,
+                found: cols,
+            });
+        }
+
+        let mut grid = Self::new(0, cols);
+        for (i, line) in lines.enumerate() {
+            let mut row = B::ZERO;
+            let mut width = 0;
+            for ch in line.chars() {
+                if width < cols && f(ch) {
+                    row |= B::ONE << width as u32;
+                }
+                width += 1;
+            }
+            if width != cols {
+                return Err(ParseError::WrongWidth {
+                    line: i + 1,
+                    expected: cols,
+                    found: width,
+                });
+            }
+            grid.rows.push(row);
+        }
+        Ok(grid)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, row: usize) -> B {
+        self.rows[row]
+    }
+
+    /// All the rows, from the top
+    pub fn as_rows(&self) -> &[B] {
+        &self.rows
+    }
+
+    pub fn get(&self, (row, col): Pos) -> bool {
+        self.rows[row] >> col as u32 & B::ONE == B::ONE
+    }
+
+    pub fn set(&mut self, (row, col): Pos, value: bool) {
+        let bit = B::ONE << col as u32;
+        if value {
+            self.rows[row] |= bit;
+        } else {
+            self.rows[row] &= !bit;
+        }
+    }
+
+    /// Number of set cells
+    pub fn count(&self) -> u32 {
+        self.rows.iter().map(|row| row.count_ones()).sum()
+    }
+
+    /// Grid where the rows are the columns of this grid
+    ///
+    /// # Panics
+    /// If there are more rows than [`Bits::BITS`].
+    pub fn transpose(&self) -> Self {
+        let mut transposed = Self::new(self.cols, self.rows());
+        for (r, &row) in self.rows.iter().enumerate() {
+            let mut bits = row;
+            while bits != B::ZERO {
+                let c = bits.trailing_zeros();
+                transposed.rows[c as usize] |= B::ONE << r as u32;
+                bits &= !(B::ONE << c);
+            }
+        }
+        transposed
+    }
+
+    /// Moves the set cells as far as they go in `dir`, they stop at the edge, at the set cells of
+    /// `walls` and at each other
+    ///
+    /// # Panics
+    /// If `dir` is diagonal.
+    pub fn tilt(&mut self, walls: &Self, dir: Direction) {
+        match dir {
+            Direction::Up => {
+                // the rows above are already tilted, so the cells of a row fall until they hit them
+                for r in 1..self.rows() {
+                    let mut moving = self.rows[r];
+                    for k in (1..=r).rev() {
+                        let moved = moving & !(self.rows[k - 1] | walls.rows[k - 1]);
+                        if moved == B::ZERO {
+                            break;
+                        }
+                        self.rows[k - 1] |= moved;
+                        self.rows[k] &= !moved;
+                        moving = moved;
+                    }
+                }
+            }
+            Direction::Down => {
+                for r in (0..self.rows().saturating_sub(1)).rev() {
+                    let mut moving = self.rows[r];
+                    for k in r..self.rows() - 1 {
+                        let moved = moving & !(self.rows[k + 1] | walls.rows[k + 1]);
+                        if moved == B::ZERO {
+                            break;
+                        }
+                        self.rows[k + 1] |= moved;
+                        self.rows[k] &= !moved;
+                        moving = moved;
+                    }
+                }
+            }
+            Direction::Left | Direction::Right => {
+                // count the cells between each pair of walls and pack them to one end
+                let cols = self.cols as u32;
+                for (row, &wall) in self.rows.iter_mut().zip(&walls.rows) {
+                    let mut tilted = B::ZERO;
+                    let mut start = 0;
+                    while start < cols {
+                        let rest = wall >> start;
+                        let end = if rest == B::ZERO {
+                            cols
+                        } else {
+                            (start + rest.trailing_zeros()).min(cols)
+                        };
+                        let segment = B::low(end) & !B::low(start);
+                        let n = (*row & segment).count_ones();
+                        tilted |= if dir == Direction::Left {
+                            B::low(n) << start
+                        } else {
+                            B::low(n) << (end - n)
+                        };
+                        start = end + 1;
+                    }
+                    *row = tilted;
+                }
+            }
+            _ => panic!("can't tilt diagonally"),
+        }
+    }
+}
+
+impl<B: Bits> fmt::Display for BitGrid<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for r in 0..self.rows() {
+            for c in 0..self.cols {
+                write!(f, "{}", if self.get((r, c)) { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::grid::Grid;
+
+    const ROCKS: &str = indoc::indoc! {"
+    O....#....
+    O.OO#....#
+    .....##...
+    OO.#O....O
+    .O.....O#.
+    O.#..O.#.#
+    ..O..#O..O
+    .......O..
+    #....###..
+    #OO..#....
+    "};
+
+    fn rocks_and_walls(input: &str) -> (BitGrid<u64>, BitGrid<u64>) {
+        (
+            BitGrid::parse(input, |c| c == 'O').unwrap(),
+            BitGrid::parse(input, |c| c == '#').unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = BitGrid::<u64>::parse("#..\n.##\n", |c| c == '#').unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid.as_rows(), [0b001, 0b110]);
+        assert!(grid.get((1, 2)));
+        assert!(!grid.get((1, 0)));
+        assert_eq!(grid.count(), 3);
+        assert_eq!(grid.to_string(), "#..\n.##\n");
+
+        let transposed = grid.transpose();
+        assert_eq!((transposed.rows(), transposed.cols()), (3, 2));
+        assert_eq!(transposed.to_string(), "#.\n.#\n.#\n");
+
+        assert_eq!(
+            BitGrid::<u64>::parse("#..\n.##.\n", |c| c == '#'),
+            Err(ParseError::WrongWidth {
+                line: 2,
+                expected: 3,
+                found: 4
+            })
+        );
+        assert!(BitGrid::<u64>::parse("#..\n.#\n", |c| c == '#').is_err());
+        assert_eq!(
+            BitGrid::<u32>::parse(&".".repeat(33), |c| c == '#'),
+            Err(ParseError::TooLarge {
+                what: "grid width",
+                max: 32,
+                found: 33
+            })
+        );
+        assert!(BitGrid::<u32>::parse(&".".repeat(32), |c| c == '#').is_ok());
+    }
+
+    #[test]
+    fn test_hamming() {
+        assert_eq!(hamming(0b1011u64, 0b1011), 0);
+        assert_eq!(hamming(0b1011u64, 0b0011), 1);
+        assert_eq!(hamming(u128::MAX, 0), 128);
+        assert_eq!(u128::low(128), u128::MAX);
+        assert_eq!(u64::low(3), 0b111);
+    }
+
+    #[test]
+    fn test_tilt() {
+        let (mut rocks, walls) = rocks_and_walls(ROCKS);
+        rocks.tilt(&walls, Direction::Up);
+        let expected = indoc::indoc! {"
+        OOOO.#.O..
+        OO..#....#
+        OO..O##..O
+        O..#.OO...
+        ........#.
+        ..#....#.#
+        ..O..#.O.O
+        ..O.......
+        #....###..
+        #....#....
+        "};
+        assert_eq!(rocks, BitGrid::parse(expected, |c| c == 'O').unwrap());
+
+        // a whole spin cycle, from the puzzle description
+        for dir in [Direction::Left, Direction::Down, Direction::Right] {
+            rocks.tilt(&walls, dir);
+        }
+        let expected = indoc::indoc! {"
+        .....#....
+        ....#...O#
+        ...OO##...
+        .OO#......
+        .....OOO#.
+        .O#...O#.#
+        ....O#....
+        ......OOOO
+        #...O###..
+        #..OO#....
+        "};
+        assert_eq!(rocks, BitGrid::parse(expected, |c| c == 'O').unwrap());
+    }
+
+    /// Moves the rocks one cell at a time until none of them can move
+    fn tilt_slowly(grid: &mut Grid<char>, dir: Direction) {
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for pos in grid.positions().collect::<Vec<_>>() {
+                if grid[pos] != 'O' {
+                    continue;
+                }
+                if let Some(next) = grid.step(pos, dir).filter(|&next| grid[next] == '.') {
+                    grid[next] = 'O';
+                    grid[pos] = '.';
+                    moved = true;
+                }
+            }
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_tilt_against_slow(
+            cells in (1..12usize, 1..12usize).prop_flat_map(|(rows, cols)| {
+                prop::collection::vec(
+                    prop::collection::vec(prop::sample::select(vec!['O', '#', '.']), cols),
+                    rows,
+                )
+            }),
+            dir in prop::sample::select(Direction::ORTHOGONAL.to_vec()),
+        ) {
+            let input = cells
+                .iter()
+                .map(|row| row.iter().chain(['\n'].iter()).collect::<String>())
+                .collect::<String>();
+
+            let (mut rocks, walls) = rocks_and_walls(&input);
+            rocks.tilt(&walls, dir);
+
+            let mut grid = Grid::parse(&input, |c| c);
+            tilt_slowly(&mut grid, dir);
+            let expected = BitGrid::parse(&grid.to_string(), |c| c == 'O').unwrap();
+            prop_assert_eq!(rocks, expected);
+        }
+    }
+}
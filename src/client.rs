@@ -0,0 +1,116 @@
+//! Client for the Advent of Code website, shared by the commands which download something from it.
+//!
+//! The session cookie is read from the `AOC_SESSION` environment variable or from the
+//! `~/.adventofcode.session` file. The base url defaults to <https://adventofcode.com> and can be
+//! changed with the `AOC_BASE_URL` environment variable, for example to point to a local mirror.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context};
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+pub struct Client {
+    base_url: String,
+    session: String,
+}
+
+impl Client {
+    pub fn new(base_url: impl Into<String>, session: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        while base_url.ends_with('/') {
+            base_url.pop();
+        }
+
+        Self {
+            base_url,
+            session: session.into(),
+        }
+    }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        let base_url =
+            std::env::var("AOC_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        Ok(Self::new(base_url, session()?))
+    }
+
+    pub fn with_base_url(self, base_url: impl Into<String>) -> Self {
+        Self::new(base_url, self.session)
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Sends a GET request to `path` on the site and returns the response body
+    pub fn get(&self, path: &str) -> anyhow::Result<String> {
+        let url = format!("{}/{}", self.base_url, path.trim_start_matches('/'));
+        let mut response = ureq::get(&url)
+            .header("Cookie", &format!("session={}", self.session))
+            .header("User-Agent", USER_AGENT)
+            .call()
+            .with_context(|| format!("request to {url} failed"))?;
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .with_context(|| format!("failed to read the response from {url}"))?;
+        Ok(body)
+    }
+}
+
+/// Path of the file the session cookie is read from if `AOC_SESSION` is not set
+pub fn session_file() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".adventofcode.session"))
+}
+
+fn session() -> anyhow::Result<String> {
+    if let Ok(session) = std::env::var("AOC_SESSION") {
+        return Ok(session.trim().to_string());
+    }
+
+    let Some(path) = session_file() else {
+        bail!(
+            "AOC_SESSION is not set and there is no home directory to read the session file from"
+        );
+    };
+    let session = std::fs::read_to_string(&path).with_context(|| {
+        format!(
+            "AOC_SESSION is not set and failed to read the session from {}",
+            path.display()
+        )
+    })?;
+    Ok(session.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use tiny_http::{Response, Server};
+
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        let handle = std::thread::spawn(move || {
+            let request = server.recv().unwrap();
+            let cookie = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Cookie"))
+                .map(|h| h.value.to_string());
+            let url = request.url().to_string();
+            request.respond(Response::from_string("hello")).unwrap();
+            (url, cookie)
+        });
+
+        let client = Client::new(format!("http://{addr}/"), "secret");
+        assert_eq!(client.get("/2023/day/1").unwrap(), "hello");
+
+        let (url, cookie) = handle.join().unwrap();
+        assert_eq!(url, "/2023/day/1");
+        assert_eq!(cookie.as_deref(), Some("session=secret"));
+    }
+}
@@ -0,0 +1,207 @@
+//! Cycle detection for sequences `x0, f(x0), f(f(x0)), ...` of a state function `f`.
+//!
+//! [`brent`] and [`floyd`] only keep a couple of states in memory but have to call `f` more
+//! often, [`hashed`] calls `f` once per state but keeps all of them.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Shape of an eventually periodic sequence
+///
+/// The states `prefix..prefix + period` repeat forever after the first `prefix` states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// Index of the first state which is equal to the `n`th state, always below `prefix + period`
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.prefix {
+            n
+        } else {
+            self.prefix + (n - self.prefix) % self.period
+        }
+    }
+}
+
+/// Returns the `n`th state of the sequence, only the first `cycle.prefix + cycle.period` states
+/// are calculated
+pub fn nth<T>(start: T, mut next: impl FnMut(&T) -> T, cycle: Cycle, n: usize) -> T {
+    let mut state = start;
+    for _ in 0..cycle.reduce(n) {
+        state = next(&state);
+    }
+    state
+}
+
+/// Finds the cycle with Brent's algorithm
+///
+/// Never returns if the sequence doesn't repeat.
+pub fn brent<T: PartialEq + Clone>(start: T, mut next: impl FnMut(&T) -> T) -> Cycle {
+    // Find the period by moving the hare until it meets the tortoise,
+    // which is moved to the hare on every power of two.
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = next(&start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = next(&hare);
+        period += 1;
+    }
+
+    // With the hare `period` steps ahead, both meet at the start of the cycle
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..period {
+        hare = next(&hare);
+    }
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        prefix += 1;
+    }
+
+    Cycle { prefix, period }
+}
+
+/// Finds the cycle with Floyd's tortoise and hare algorithm
+///
+/// Never returns if the sequence doesn't repeat.
+pub fn floyd<T: PartialEq + Clone>(start: T, mut next: impl FnMut(&T) -> T) -> Cycle {
+    // The hare moves twice as fast, they meet at a multiple of the period
+    let mut tortoise = next(&start);
+    let mut hare = next(&tortoise);
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        hare = next(&hare);
+    }
+
+    // The distance from the start to the cycle equals the distance from the meeting point
+    let mut prefix = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        prefix += 1;
+    }
+
+    let mut period = 1;
+    hare = next(&tortoise);
+    while tortoise != hare {
+        hare = next(&hare);
+        period += 1;
+    }
+
+    Cycle { prefix, period }
+}
+
+/// Finds the cycle by remembering every state
+///
+/// Returns the states `0..prefix + period`, the `n`th state is `states[cycle.reduce(n)]`.
+/// Never returns if the sequence doesn't repeat.
+pub fn hashed<T: Hash + Eq + Clone>(start: T, mut next: impl FnMut(&T) -> T) -> (Cycle, Vec<T>) {
+    let mut seen = HashMap::new();
+    let mut states = Vec::new();
+    let mut state = start;
+
+    loop {
+        if let Some(&prefix) = seen.get(&state) {
+            let period = states.len() - prefix;
+            return (Cycle { prefix, period }, states);
+        }
+
+        seen.insert(state.clone(), states.len());
+        let following = next(&state);
+        states.push(state);
+        state = following;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// `0, 1, ..., prefix - 1` followed by the repeating `prefix..prefix + period`
+    fn rho(prefix: usize, period: usize) -> impl Fn(&usize) -> usize {
+        move |&x| {
+            if x + 1 == prefix + period {
+                prefix
+            } else {
+                x + 1
+            }
+        }
+    }
+
+    #[test]
+    fn test_reduce() {
+        let cycle = Cycle {
+            prefix: 3,
+            period: 4,
+        };
+        assert_eq!(cycle.reduce(0), 0);
+        assert_eq!(cycle.reduce(2), 2);
+        assert_eq!(cycle.reduce(3), 3);
+        assert_eq!(cycle.reduce(6), 6);
+        assert_eq!(cycle.reduce(7), 3);
+        assert_eq!(cycle.reduce(1_000_000_000), 3 + (1_000_000_000 - 3) % 4);
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        let expected = Cycle {
+            prefix: 0,
+            period: 1,
+        };
+        assert_eq!(brent(5, |&x| x), expected);
+        assert_eq!(floyd(5, |&x| x), expected);
+        assert_eq!(hashed(5, |&x| x), (expected, vec![5]));
+    }
+
+    #[test]
+    fn test_collatz() {
+        // 6, 3, 10, 5, 16, 8, 4, 2, 1, 4, ...
+        let collatz = |&x: &u64| if x % 2 == 0 { x / 2 } else { 3 * x + 1 };
+        let expected = Cycle {
+            prefix: 6,
+            period: 3,
+        };
+        assert_eq!(brent(6, collatz), expected);
+        assert_eq!(floyd(6, collatz), expected);
+
+        let (cycle, states) = hashed(6, collatz);
+        assert_eq!(cycle, expected);
+        assert_eq!(states, [6, 3, 10, 5, 16, 8, 4, 2, 1]);
+        assert_eq!(states[cycle.reduce(100)], nth(6, collatz, cycle, 100));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_algorithms_agree(prefix in 0..50usize, period in 1..50usize, n in 0..1000usize) {
+            let expected = Cycle { prefix, period };
+            prop_assert_eq!(brent(0, rho(prefix, period)), expected);
+            prop_assert_eq!(floyd(0, rho(prefix, period)), expected);
+
+            let (cycle, states) = hashed(0, rho(prefix, period));
+            prop_assert_eq!(cycle, expected);
+            prop_assert_eq!(states.len(), prefix + period);
+
+            let mut state = 0;
+            for _ in 0..n {
+                state = rho(prefix, period)(&state);
+            }
+            prop_assert_eq!(nth(0, rho(prefix, period), cycle, n), state);
+            prop_assert_eq!(states[cycle.reduce(n)], state);
+        }
+    }
+}
@@ -0,0 +1,404 @@
+//! Interactive terminal dashboard for all the days.
+//!
+//! Shows the status of every day, the answers compared to the known answers and how long the last run took.
+//! The solvers run on separate threads, their panics are shown in the log pane.
+//! Other panics go to the hook installed by ratatui which restores the terminal first.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::days::{self, Day, Solution};
+
+const HELP: &str = "↑/↓ select, 1/2 run part, enter run day, a run all solved days, q quit";
+const MAX_LOGS: usize = 200;
+
+pub fn run() -> anyhow::Result<()> {
+    let (tx, rx) = mpsc::channel();
+
+    let terminal = ratatui::init();
+    let result = App::new(days::DAYS).run(terminal, tx, rx);
+    ratatui::restore();
+    result
+}
+
+#[derive(Debug)]
+enum Message {
+    Solved {
+        row: usize,
+        part: u8,
+        solution: Solution,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PartState {
+    NotRun,
+    Running,
+    Done(Solution),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Implemented,
+    Todo,
+    Panicking,
+}
+
+#[derive(Debug)]
+struct DayRow {
+    day: &'static Day,
+    parts: [PartState; 2],
+}
+
+impl DayRow {
+    fn status(&self) -> Status {
+        let mut status = if self.day.is_solved() {
+            Status::Implemented
+        } else {
+            Status::Todo
+        };
+
+        for part in &self.parts {
+            if let PartState::Done(Solution { answer: Err(e), .. }) = part {
+                if is_todo_panic(e) {
+                    status = Status::Todo;
+                } else {
+                    return Status::Panicking;
+                }
+            }
+        }
+
+        status
+    }
+}
+
+fn is_todo_panic(message: &str) -> bool {
+    message.starts_with("not yet implemented")
+}
+
+#[derive(Debug)]
+struct App {
+    rows: Vec<DayRow>,
+    table: TableState,
+    logs: Vec<String>,
+    quit: bool,
+}
+
+impl App {
+    fn new(days: &'static [Day]) -> Self {
+        Self {
+            rows: days
+                .iter()
+                .map(|day| DayRow {
+                    day,
+                    parts: [PartState::NotRun, PartState::NotRun],
+                })
+                .collect(),
+            table: TableState::default().with_selected(0),
+            logs: Vec::new(),
+            quit: false,
+        }
+    }
+
+    fn run(
+        mut self,
+        mut terminal: DefaultTerminal,
+        tx: Sender<Message>,
+        rx: Receiver<Message>,
+    ) -> anyhow::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if event::poll(Duration::from_millis(100))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        for (row, part) in self.handle_key(key.code) {
+                            self.spawn_solver(row, part, tx.clone());
+                        }
+                    }
+                }
+            }
+
+            while let Ok(message) = rx.try_recv() {
+                self.handle_message(message);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn spawn_solver(&mut self, row: usize, part: u8, tx: Sender<Message>) {
+        let day = self.rows[row].day;
+        let name = format!("day{}::part{part}", day.day);
+        self.log(format!("{name} started"));
+
+        let result = thread::Builder::new().name(name).spawn(move || {
+            // the panic message would be printed over the ui, it's logged with the answer instead
+            let solution = days::catch_quietly(|| day.solve(part, day.input).unwrap())
+                .expect("the solver panics are caught");
+            let _ = tx.send(Message::Solved {
+                row,
+                part,
+                solution,
+            });
+        });
+
+        if let Err(e) = result {
+            self.log(format!("failed to start the solver: {e}"));
+            self.rows[row].parts[part as usize - 1] = PartState::NotRun;
+        }
+    }
+
+    /// Returns the `(row, part)` pairs that need to be solved
+    fn handle_key(&mut self, key: KeyCode) -> Vec<(usize, u8)> {
+        let selected = self.table.selected().unwrap_or(0);
+        let requested = match key {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.quit = true;
+                vec![]
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.table.select(Some(selected.saturating_sub(1)));
+                vec![]
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.table
+                    .select(Some((selected + 1).min(self.rows.len() - 1)));
+                vec![]
+            }
+            KeyCode::Char('1') => vec![(selected, 1)],
+            KeyCode::Char('2') => vec![(selected, 2)],
+            KeyCode::Enter => vec![(selected, 1), (selected, 2)],
+            KeyCode::Char('a') => self
+                .rows
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.day.is_solved())
+                .flat_map(|(i, _)| [(i, 1), (i, 2)])
+                .collect(),
+            _ => vec![],
+        };
+
+        requested
+            .into_iter()
+            .filter(|&(row, part)| {
+                let state = &mut self.rows[row].parts[part as usize - 1];
+                if *state == PartState::Running {
+                    return false;
+                }
+                *state = PartState::Running;
+                true
+            })
+            .collect()
+    }
+
+    fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::Solved {
+                row,
+                part,
+                solution,
+            } => {
+                let name = format!("day{}::part{part}", self.rows[row].day.day);
+                match &solution.answer {
+                    Ok(answer) => self.log(format!(
+                        "{name} answer {answer} in {:.2?}",
+                        solution.elapsed
+                    )),
+                    Err(e) => self.log(format!("{name} failed: {e}")),
+                }
+                self.rows[row].parts[part as usize - 1] = PartState::Done(solution);
+            }
+        }
+    }
+
+    fn log(&mut self, log: String) {
+        self.logs.push(log);
+        if self.logs.len() > MAX_LOGS {
+            self.logs.remove(0);
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, help] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        let [days_area, logs_area] =
+            Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .areas(main);
+
+        let rows = self.rows.iter().map(|row| {
+            let (status, color) = match row.status() {
+                Status::Implemented => ("implemented", Color::Green),
+                Status::Todo => ("todo", Color::Yellow),
+                Status::Panicking => ("panicking", Color::Red),
+            };
+
+            let mut cells = vec![
+                Line::from(format!("day{}", row.day.day)),
+                Line::from(status).fg(color),
+            ];
+            for (part, state) in (1..).zip(&row.parts) {
+                let (answer, time) = part_cells(row.day.answer(part), state);
+                cells.push(answer);
+                cells.push(time);
+            }
+            Row::new(cells)
+        });
+
+        let widths = [
+            Constraint::Length(6),
+            Constraint::Length(12),
+            Constraint::Fill(1),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+            Constraint::Length(10),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["Day", "Status", "Part 1", "Time", "Part 2", "Time"])
+                    .add_modifier(Modifier::BOLD),
+            )
+            .row_highlight_style(Style::new().reversed())
+            .block(Block::bordered().title("Days"));
+        frame.render_stateful_widget(table, days_area, &mut self.table);
+
+        let visible_logs = logs_area.height.saturating_sub(2) as usize;
+        let logs = List::new(
+            self.logs[self.logs.len().saturating_sub(visible_logs)..]
+                .iter()
+                .map(String::as_str),
+        )
+        .block(Block::bordered().title("Logs"));
+        frame.render_widget(logs, logs_area);
+
+        frame.render_widget(Line::from(HELP).dim(), help);
+    }
+}
+
+/// Returns the answer and time cells for one part
+fn part_cells(known_answer: Option<&str>, state: &PartState) -> (Line<'static>, Line<'static>) {
+    match state {
+        PartState::NotRun => (Line::from("-").dim(), Line::from("")),
+        PartState::Running => (Line::from("running...").fg(Color::Cyan), Line::from("")),
+        PartState::Done(solution) => {
+            let time = Line::from(format!("{:.2?}", solution.elapsed));
+            let answer = match (&solution.answer, known_answer) {
+                (Ok(answer), Some(known)) if answer == known => {
+                    Line::from(format!("{answer} ✓")).fg(Color::Green)
+                }
+                (Ok(answer), Some(known)) => {
+                    Line::from(format!("{answer} ✗ ({known})")).fg(Color::Red)
+                }
+                (Ok(answer), None) => Line::from(answer.clone()),
+                (Err(e), _) if is_todo_panic(e) => Line::from("todo").fg(Color::Yellow),
+                (Err(_), _) => Line::from("panic").fg(Color::Red),
+            };
+            (answer, time)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+
+    fn solved(answer: Result<&str, &str>) -> PartState {
+        PartState::Done(Solution {
+            answer: answer.map(String::from).map_err(String::from),
+            elapsed: Duration::from_millis(1),
+        })
+    }
+
+    #[test]
+    fn test_status() {
+        let mut app = App::new(days::DAYS);
+        let day12 = app.rows.iter().position(|r| r.day.day == 12).unwrap();
+        assert_eq!(app.rows[0].status(), Status::Implemented);
+        assert_eq!(app.rows[day12].status(), Status::Todo);
+
+        app.rows[day12].parts[0] = solved(Err("not yet implemented"));
+        assert_eq!(app.rows[day12].status(), Status::Todo);
+
+        app.rows[0].parts[1] = solved(Err("index out of bounds"));
+        assert_eq!(app.rows[0].status(), Status::Panicking);
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut app = App::new(days::DAYS);
+
+        assert_eq!(app.handle_key(KeyCode::Down), vec![]);
+        assert_eq!(app.handle_key(KeyCode::Char('2')), vec![(1, 2)]);
+        // already running
+        assert_eq!(app.handle_key(KeyCode::Enter), vec![(1, 1)]);
+
+        app.handle_message(Message::Solved {
+            row: 1,
+            part: 2,
+            solution: Solution {
+                answer: Ok(String::from("83435")),
+                elapsed: Duration::from_millis(1),
+            },
+        });
+        assert_eq!(app.rows[1].parts[1], solved(Ok("83435")));
+
+        let all = app.handle_key(KeyCode::Char('a'));
+        let solved_days = days::DAYS.iter().filter(|d| d.is_solved()).count();
+        // day2 part1 is still running
+        assert_eq!(all.len(), solved_days * 2 - 1);
+
+        app.handle_key(KeyCode::Char('q'));
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn test_solver_panic() {
+        let mut app = App::new(days::DAYS);
+        let day12 = app.rows.iter().position(|r| r.day.day == 12).unwrap();
+        let (tx, rx) = mpsc::channel();
+        app.spawn_solver(day12, 1, tx);
+        let message = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        app.handle_message(message);
+
+        // the panic is logged once, with the answer
+        assert_eq!(app.logs.len(), 2, "{:?}", app.logs);
+        assert!(app.logs[1].starts_with("day12::part1 failed: not yet implemented"));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut app = App::new(days::DAYS);
+        app.rows[0].parts = [solved(Ok("56049")), solved(Ok("1"))];
+        app.log(String::from("hello from the logs"));
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+
+        let screen = terminal
+            .backend()
+            .buffer()
+            .content()
+            .chunks(120)
+            .map(|line| line.iter().map(|c| c.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        assert!(screen.contains("56049 ✓"), "{screen}");
+        assert!(screen.contains("1 ✗ (54530)"), "{screen}");
+        assert!(screen.contains("hello from the logs"), "{screen}");
+        let day12 = screen.lines().find(|l| l.contains("day12")).unwrap();
+        assert!(day12.contains("todo"), "{screen}");
+    }
+}
@@ -1,10 +1,18 @@
+use crate::aho_corasick::AhoCorasick;
+use crate::fastparse;
+use crate::parse;
+
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 1;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day1.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
     let answer_part1 = solve_part1(input);
     println!("day{DAY}::part1 answer: {}", answer_part1);
@@ -13,30 +21,126 @@ fn solve(input: &str) {
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u32 {
-    input
-        .lines()
+pub(crate) fn solve_part1(input: &str) -> u32 {
+    fastparse::lines(input)
         .map(|line| {
-            // iterating over bytes is much faster than looping over chars
-            // but we know that we are looking for ascii characters
-            let first = line
-                .bytes()
-                .find_map(|c| (c as char).to_digit(10))
+            // we are looking for ascii digits, so scan the bytes eight at a time
+            let bytes = line.as_bytes();
+            let first = fastparse::first_digit(bytes)
                 .expect("expected a line to contain at least one number");
-            let last = line
-                .bytes()
-                .rev()
-                .find_map(|c| (c as char).to_digit(10))
+            let last = fastparse::last_digit(bytes)
                 .expect("expected a line to contain at least one number");
 
-            first * 10 + last
+            ((bytes[first] - b'0') * 10 + (bytes[last] - b'0')) as u32
+        })
+        .sum()
+}
+
+pub(crate) fn solve_part2(input: &str) -> u32 {
+    calibration_sum(input, &Matcher::new(&Dictionary::english()))
+        .expect("expected a line to contain at least one number")
+}
+
+/// Words and the digits they stand for, the words are looked for in the calibration lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dictionary {
+    words: Vec<(String, u32)>,
+}
+
+impl Dictionary {
+    pub fn new<W: Into<String>>(words: impl IntoIterator<Item = (W, u32)>) -> Self {
+        Dictionary {
+            words: words.into_iter().map(|(w, v)| (w.into(), v)).collect(),
+        }
+    }
+
+    /// The digits from `0` to `9`, the dictionary of part 1
+    pub fn digits() -> Self {
+        Self::new((0..10).map(|d| (d.to_string(), d)))
+    }
+
+    /// The digits and the words from `one` to `nine`, the dictionary of part 2
+    pub fn english() -> Self {
+        let words = [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ];
+        let mut dictionary = Self::digits();
+        dictionary
+            .words
+            .extend(words.into_iter().map(String::from).zip(1..));
+        dictionary
+    }
+
+    /// Parses one `word value` pair per line, empty lines and lines starting with `#` are skipped
+    pub fn parse(s: &str) -> parse::Result<Self> {
+        let words = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (word, value) = parse::split_once(line, " ")?;
+                Ok((word.to_string(), parse::number(value)?))
+            })
+            .collect::<parse::Result<_>>()?;
+        Ok(Dictionary { words })
+    }
+
+    pub fn words(&self) -> &[(String, u32)] {
+        &self.words
+    }
+}
+
+/// Finds the first and the last word of a [`Dictionary`] in a line
+///
+/// The first word is the one which starts first and the last word the one which ends last, the
+/// longest one if several start or end at the same position. So a word inside a longer one, like
+/// `b` in `abc`, doesn't count before the longer one. Overlapping words like `eightwo` count for
+/// both ends.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    forward: AhoCorasick,
+    /// The words spelled backwards, for reading the lines from the end
+    backward: AhoCorasick,
+    values: Vec<u32>,
+}
+
+impl Matcher {
+    /// # Panics
+    /// If a word of the dictionary is empty.
+    pub fn new(dictionary: &Dictionary) -> Self {
+        let words = dictionary.words();
+        Matcher {
+            forward: AhoCorasick::new(words.iter().map(|(word, _)| word.as_bytes())),
+            backward: AhoCorasick::new(
+                words
+                    .iter()
+                    .map(|(word, _)| word.bytes().rev().collect::<Vec<_>>()),
+            ),
+            values: words.iter().map(|&(_, value)| value).collect(),
+        }
+    }
+
+    /// Values of the first and the last word in `line`, `None` if there is no word in it
+    pub fn first_and_last(&self, line: &str) -> Option<(u32, u32)> {
+        let first = self.forward.find_leftmost(line.bytes())?;
+        let last = self.backward.find_leftmost(line.bytes().rev())?;
+        Some((self.values[first.pattern], self.values[last.pattern]))
+    }
+}
+
+/// Sum of the calibration values of the lines, `None` if a line has none of the words
+pub fn calibration_sum(input: &str, matcher: &Matcher) -> Option<u32> {
+    fastparse::lines(input)
+        .map(|line| {
+            let (first, last) = matcher.first_and_last(line)?;
+            Some(first * 10 + last)
         })
         .sum()
 }
 
-fn solve_part2(input: &str) -> u32 {
-    input
-        .lines()
+/// Same as [`solve_part2`] but with the words matched by hand, first forwards and then backwards
+pub(crate) fn solve_part2_handwritten(input: &str) -> u32 {
+    fastparse::lines(input)
         .map(|line| {
             let first = line
                 .bytes()
@@ -56,8 +160,8 @@ fn solve_part2(input: &str) -> u32 {
 }
 
 fn rget_digit(char: u8, line: &str, pos: usize) -> Option<u32> {
-    if let Some(d) = (char as char).to_digit(10) {
-        return Some(d);
+    if let Some(d) = fastparse::digit(char) {
+        return Some(d as u32);
     }
     match char {
         b'e' if line.get(pos.saturating_sub(2)..pos) == Some("on") => Some(1),
@@ -74,8 +178,8 @@ fn rget_digit(char: u8, line: &str, pos: usize) -> Option<u32> {
 }
 
 fn get_digit(char: u8, line: &str, pos: usize) -> Option<u32> {
-    if let Some(d) = (char as char).to_digit(10) {
-        return Some(d);
+    if let Some(d) = fastparse::digit(char) {
+        return Some(d as u32);
     }
     match char {
         b'o' if line.get(pos + 1..pos + 3) == Some("ne") => Some(1),
@@ -116,9 +220,61 @@ mod tests {
         zoneight234
         7pqrstsixteen";
         let answer = solve_part2(input);
+        assert_eq!(answer, 281);
 
+        let answer = solve_part2_handwritten(input);
         assert_eq!(answer, 281);
     }
+
+    #[test]
+    fn test_matcher() {
+        let matcher = Matcher::new(&Dictionary::english());
+        assert_eq!(matcher.first_and_last("eightwo"), Some((8, 2)));
+        assert_eq!(matcher.first_and_last("xtwone3four"), Some((2, 4)));
+        assert_eq!(matcher.first_and_last("7pqrstsixteen"), Some((7, 6)));
+        assert_eq!(matcher.first_and_last("oneight"), Some((1, 8)));
+        assert_eq!(matcher.first_and_last("nothing"), None);
+
+        let matcher = Matcher::new(&Dictionary::digits());
+        assert_eq!(matcher.first_and_last("two1nine"), Some((1, 1)));
+        assert_eq!(
+            calibration_sum("a1b2c3d4e5f\ntreb7uchet", &matcher),
+            Some(15 + 77)
+        );
+        assert_eq!(calibration_sum("a1\nb", &matcher), None);
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let dictionary = Dictionary::parse(indoc::indoc! {"
+        # German
+        eins 1
+        zwei 2
+        drei 3
+        fünf 5
+        sieben 7
+        acht 8
+
+        neun 9
+        "})
+        .unwrap();
+        assert_eq!(dictionary.words()[3], ("fünf".to_string(), 5));
+
+        let input = "xzweinsx\nfünfacht\nsiebeneunx";
+        let matcher = Matcher::new(&dictionary);
+        assert_eq!(calibration_sum(input, &matcher), Some(21 + 58 + 79));
+
+        // nested words, the longer one starts first and ends last
+        let dictionary = Dictionary::parse("abc 1\nb 2\n").unwrap();
+        let matcher = Matcher::new(&dictionary);
+        assert_eq!(matcher.first_and_last("abc"), Some((1, 1)));
+        assert_eq!(matcher.first_and_last("babc"), Some((2, 1)));
+        assert_eq!(matcher.first_and_last("abcb"), Some((1, 2)));
+        assert_eq!(matcher.first_and_last("xbx"), Some((2, 2)));
+
+        assert!(Dictionary::parse("eins\n").is_err());
+        assert!(Dictionary::parse("eins x\n").is_err());
+    }
 }
 
 #[cfg(feature = "divan")]
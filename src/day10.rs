@@ -1,29 +1,29 @@
+use crate::graph::Graph;
+use crate::grid::{Cursor, Direction, Grid, Pos};
+use crate::math;
+use crate::visualize::{self, Canvas, Cell, Rgb};
+
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 10;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day10.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
-    let answer_part1 = solve_part1::<140>(input);
+    let answer_part1 = solve_part1(input);
     println!("day{DAY}::part1 answer: {}", answer_part1);
 
-    let answer_part2 = solve_part2::<140>(input);
+    let answer_part2 = solve_part2(input);
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
-    Unknown,
-}
-
-fn solve_part1<const SIZE: usize>(input: &str) -> u64 {
-    let (data, start) = parse::<SIZE>(input);
+pub(crate) fn solve_part1(input: &str) -> u64 {
+    let (data, start) = parse(input);
     let mut cursors = [data.cursor(start), data.cursor(start)];
 
     // Two pipes must be connected to the start, find them
@@ -31,16 +31,16 @@ fn solve_part1<const SIZE: usize>(input: &str) -> u64 {
 
     let mut steps = 1;
     loop {
+        let mut back_at_start = false;
         for i in [0, 1] {
             cursors[i].step(next_step_dirs[i]);
-            let current_pipe = cursors[i].current();
-            next_step_dirs[i] =
-                next_step_dir(next_step_dirs[i], *current_pipe).unwrap_or(Dir::Unknown);
+            match next_step_dir(next_step_dirs[i], *cursors[i].current()) {
+                Some(dir) => next_step_dirs[i] = dir,
+                None => back_at_start = true,
+            }
         }
 
-        if matches!(next_step_dirs[0], Dir::Unknown)
-            || cursors[0].current_pos() == cursors[1].current_pos()
-        {
+        if back_at_start || cursors[0].pos() == cursors[1].pos() {
             break;
         }
 
@@ -58,9 +58,11 @@ enum PipeKind {
 }
 
 impl PipeKind {
-    fn from_pipe(pipe: &Pipe) -> PipeKind {
+    /// `corners` are the corners which are counted as horizontal pipes, see [`count_inside`]
+    fn from_pipe(pipe: &Pipe, corners: [Pipe; 2]) -> PipeKind {
         match pipe {
-            Pipe::Horizontal | Pipe::URCorner | Pipe::DRCorner => PipeKind::Horizontal,
+            Pipe::Horizontal => PipeKind::Horizontal,
+            p if corners.contains(p) => PipeKind::Horizontal,
             Pipe::None => PipeKind::Not,
             _ => PipeKind::Other,
         }
@@ -75,7 +77,51 @@ impl PipeKind {
     }
 }
 
-fn solve_part2<const SIZE: usize>(input: &str) -> u64 {
+pub(crate) fn solve_part2(input: &str) -> u64 {
+    count_inside(input, [Pipe::DRCorner, Pipe::URCorner])
+}
+
+/// Same as [`solve_part2`] but counts the 7 and J corners instead of F and L
+pub(crate) fn solve_part2_7j(input: &str) -> u64 {
+    count_inside(input, [Pipe::DLCorner, Pipe::ULCorner])
+}
+
+/// Same as [`solve_part2`] but counts the tiles inside the loop with the shoelace formula and Pick's theorem
+pub(crate) fn solve_part2_pick(input: &str) -> u64 {
+    let (data, start) = parse(input);
+    let vertices = loop_path(&data, start)
+        .into_iter()
+        .map(|(row, col)| (row as i64, col as i64))
+        .collect::<Vec<_>>();
+
+    math::interior_points(&vertices)
+}
+
+/// The tiles of the loop in order, starting from `start`
+fn loop_path(data: &Grid<Pipe>, start: Pos) -> Vec<Pos> {
+    let mut cursor = data.cursor(start);
+
+    let mut next_dir = find_all_start_dirs(&cursor)[0];
+    let mut path = vec![start];
+    loop {
+        cursor.step(next_dir);
+        let Some(next) = next_step_dir(next_dir, *cursor.current()) else {
+            break;
+        };
+        path.push(cursor.pos());
+        next_dir = next;
+    }
+    path
+}
+
+fn count_inside(input: &str, corners: [Pipe; 2]) -> u64 {
+    let mut count_inside = 0;
+    for_each_inside(input, corners, |_| count_inside += 1);
+    count_inside
+}
+
+/// Calls `f` with every tile inside the loop
+fn for_each_inside(input: &str, corners: [Pipe; 2], mut f: impl FnMut(Pos)) {
     // 1. Find the path
     // 2. Move up to down and count horizontal pipes
     //    Note that ┌---┘ and └---┐ are form a horizontal pipe as well.
@@ -116,14 +162,13 @@ fn solve_part2<const SIZE: usize>(input: &str) -> u64 {
     // answer.
     // (Fixed now. The answer was that we didn't handle the start corner at all and it could be the corner we need to count.)
 
-    let (data, start) = parse::<SIZE>(input);
+    let (data, start) = parse(input);
     let mut cursor = data.cursor(start);
-    let mut path = [[PipeKind::Not; SIZE]; SIZE];
+    let mut path = Grid::filled(data.rows(), data.cols(), PipeKind::Not);
 
     let next_dirs = find_all_start_dirs(&cursor);
     let start_pipe = start_pipe_kind(&next_dirs);
-    let (x, y) = cursor.current_pos();
-    path[x][y] = PipeKind::from_pipe(&start_pipe);
+    path[start] = PipeKind::from_pipe(&start_pipe, corners);
 
     let mut next_dir = next_dirs[0];
     loop {
@@ -136,87 +181,119 @@ fn solve_part2<const SIZE: usize>(input: &str) -> u64 {
             break;
         };
 
-        let (x, y) = cursor.current_pos();
-        path[x][y] = PipeKind::from_pipe(current_pipe);
+        path[cursor.pos()] = PipeKind::from_pipe(current_pipe, corners);
 
         next_dir = next;
     }
 
-    let mut count_inside = 0;
-    for c in path {
+    for (col, tiles) in path.iter_cols().enumerate() {
         let mut inside = false;
-        for &it in &c {
+        for (row, it) in tiles.enumerate() {
             if it.is_horizontal() {
                 inside = !inside;
             } else if inside && it.is_not() {
-                count_inside += 1;
+                f((row, col));
             }
         }
     }
-
-    count_inside
 }
 
-fn find_all_start_dirs<const SIZE: usize>(cursor: &MatrixCursor<'_, Pipe, SIZE>) -> [Dir; 2] {
-    let mut dirs = [Dir::Unknown; 2];
-
-    if let Some(Pipe::Vertical | Pipe::DLCorner | Pipe::DRCorner) = cursor.peek_up() {
-        dirs[0] = Dir::Up;
-    } else {
-        // cannot be part of the loop
-    }
-
-    if let Some(Pipe::Horizontal | Pipe::ULCorner | Pipe::DLCorner) = cursor.peek_right() {
-        match dirs[0] {
-            Dir::Unknown => dirs[0] = Dir::Right,
-            _ => dirs[1] = Dir::Right,
-        }
-    } else {
-        // cannot be part of the loop
-    }
-
-    if let Some(Pipe::Vertical | Pipe::URCorner | Pipe::ULCorner) = cursor.peek_down() {
-        match dirs[0] {
-            Dir::Unknown => dirs[0] = Dir::Down,
-            _ => dirs[1] = Dir::Down,
-        }
-    } else {
-        // cannot be part of the loop
-    }
+/// Frames of the loop being traced from the start, the last one also highlights the tiles inside it
+pub fn visualize(input: &str) -> Vec<Canvas> {
+    let (mut data, start) = parse(input);
+    let path = loop_path(&data, start);
+    data[start] = start_pipe_kind(&find_all_start_dirs(&data.cursor(start)));
+
+    let canvas = Canvas::from_grid(&data, |pipe| {
+        let ch = match pipe {
+            Pipe::Vertical => '│',
+            Pipe::Horizontal => '─',
+            Pipe::ULCorner => '┘',
+            Pipe::URCorner => '└',
+            Pipe::DLCorner => '┐',
+            Pipe::DRCorner => '┌',
+            Pipe::None | Pipe::Start => ' ',
+        };
+        Cell::new(ch).fg(Rgb::GREY)
+    });
+
+    let mut frames = visualize::evenly(path.len(), 100)
+        .map(|len| {
+            let mut frame = canvas.clone();
+            frame.path(path[..len].iter().copied(), Rgb::YELLOW);
+            frame
+        })
+        .collect::<Vec<_>>();
+
+    let mut last = canvas;
+    last.path(path.iter().chain([&start]).copied(), Rgb::YELLOW);
+    let mut inside = Vec::new();
+    for_each_inside(input, [Pipe::DRCorner, Pipe::URCorner], |pos| {
+        inside.push(pos)
+    });
+    last.highlight(inside, Rgb::GREEN);
+    last.highlight([start], Rgb::RED);
+    frames.push(last);
+    frames
+}
 
-    if let Some(Pipe::Horizontal | Pipe::URCorner | Pipe::DRCorner) = cursor.peek_left() {
-        match dirs[0] {
-            Dir::Unknown => dirs[0] = Dir::Left,
-            _ => dirs[1] = Dir::Left,
+/// The tiles as nodes with an edge in both directions between the pipes which connect to each other
+pub fn graph(input: &str) -> Graph<Pos> {
+    let (mut data, start) = parse(input);
+    data[start] = start_pipe_kind(&find_all_start_dirs(&data.cursor(start)));
+
+    let mut graph = Graph::new();
+    for pos in data.positions() {
+        for &dir in data[pos].connections() {
+            let connected = data
+                .step(pos, dir)
+                .filter(|&next| data[next].connections().contains(&dir.opposite()));
+            if let Some(next) = connected {
+                graph.add_edge(pos, next, ());
+            }
         }
-    } else {
-        // cannot be part of the loop
     }
-    dirs
+    graph
 }
 
-fn find_any_start_dir<const N: usize>(cursor: &mut MatrixCursor<'_, Pipe, N>) -> Dir {
-    if let Some(Pipe::Vertical | Pipe::DLCorner | Pipe::DRCorner) = cursor.peek_up() {
-        return Dir::Up;
-    }
-
-    if let Some(Pipe::Horizontal | Pipe::ULCorner | Pipe::DLCorner) = cursor.peek_right() {
-        return Dir::Right;
-    }
-
-    if let Some(Pipe::Vertical | Pipe::URCorner | Pipe::ULCorner) = cursor.peek_down() {
-        return Dir::Down;
-    }
-
-    if let Some(Pipe::Horizontal | Pipe::URCorner | Pipe::DRCorner) = cursor.peek_left() {
-        return Dir::Left;
-    }
+/// Same as [`solve_part1`] but searches the farthest tile of the loop with a breadth first search on the [`graph`]
+pub(crate) fn solve_part1_bfs(input: &str) -> u64 {
+    let (_, start) = parse(input);
+    let graph = graph(input);
+    let start = graph.id(&start).unwrap();
+    graph
+        .bfs(start)
+        .map(|(_, distance)| distance)
+        .max()
+        .unwrap() as u64
+}
 
-    unreachable!()
+fn find_all_start_dirs(cursor: &Cursor<'_, Pipe>) -> [Direction; 2] {
+    let mut dirs = Direction::ORTHOGONAL.into_iter().filter(|&dir| {
+        matches!(
+            (dir, cursor.peek(dir)),
+            (
+                Direction::Up,
+                Some(Pipe::Vertical | Pipe::DLCorner | Pipe::DRCorner)
+            ) | (
+                Direction::Right,
+                Some(Pipe::Horizontal | Pipe::ULCorner | Pipe::DLCorner)
+            ) | (
+                Direction::Down,
+                Some(Pipe::Vertical | Pipe::URCorner | Pipe::ULCorner)
+            ) | (
+                Direction::Left,
+                Some(Pipe::Horizontal | Pipe::URCorner | Pipe::DRCorner)
+            )
+        )
+    });
+
+    // anything else cannot be part of the loop
+    [dirs.next().unwrap(), dirs.next().unwrap()]
 }
 
-fn start_pipe_kind(dirs: &[Dir; 2]) -> Pipe {
-    use Dir::*;
+fn start_pipe_kind(dirs: &[Direction; 2]) -> Pipe {
+    use Direction::*;
     match dirs {
         [Up | Right, Right | Up] => Pipe::URCorner,
         [Up | Left, Left | Up] => Pipe::ULCorner,
@@ -228,42 +305,32 @@ fn start_pipe_kind(dirs: &[Dir; 2]) -> Pipe {
     }
 }
 
-fn next_step_dir(came_from: Dir, current_pipe: Pipe) -> Option<Dir> {
+fn next_step_dir(came_from: Direction, current_pipe: Pipe) -> Option<Direction> {
     Some(match (came_from, current_pipe) {
-        (Dir::Up, Pipe::Vertical) => Dir::Up,
-        (Dir::Up, Pipe::DLCorner) => Dir::Left,
-        (Dir::Up, Pipe::DRCorner) => Dir::Right,
+        (Direction::Up, Pipe::Vertical) => Direction::Up,
+        (Direction::Up, Pipe::DLCorner) => Direction::Left,
+        (Direction::Up, Pipe::DRCorner) => Direction::Right,
 
-        (Dir::Down, Pipe::Vertical) => Dir::Down,
-        (Dir::Down, Pipe::ULCorner) => Dir::Left,
-        (Dir::Down, Pipe::URCorner) => Dir::Right,
+        (Direction::Down, Pipe::Vertical) => Direction::Down,
+        (Direction::Down, Pipe::ULCorner) => Direction::Left,
+        (Direction::Down, Pipe::URCorner) => Direction::Right,
 
-        (Dir::Left, Pipe::Horizontal) => Dir::Left,
-        (Dir::Left, Pipe::URCorner) => Dir::Up,
-        (Dir::Left, Pipe::DRCorner) => Dir::Down,
+        (Direction::Left, Pipe::Horizontal) => Direction::Left,
+        (Direction::Left, Pipe::URCorner) => Direction::Up,
+        (Direction::Left, Pipe::DRCorner) => Direction::Down,
 
-        (Dir::Right, Pipe::Horizontal) => Dir::Right,
-        (Dir::Right, Pipe::ULCorner) => Dir::Up,
-        (Dir::Right, Pipe::DLCorner) => Dir::Down,
+        (Direction::Right, Pipe::Horizontal) => Direction::Right,
+        (Direction::Right, Pipe::ULCorner) => Direction::Up,
+        (Direction::Right, Pipe::DLCorner) => Direction::Down,
         (_, Pipe::Start) => return None, // Both cursors must loop at the same time
         s => unreachable!("{:?}", s),
     })
 }
 
-fn parse<const N: usize>(input: &str) -> (Matrix<Pipe, N>, (usize, usize)) {
-    let mut data = [[Pipe::None; N]; N];
-
-    let mut start = (0, 0);
-    for (y, l) in input.lines().enumerate() {
-        for (x, c) in l.chars().enumerate() {
-            data[x][y] = Pipe::from_char(c);
-            if c == 'S' {
-                start = (x, y);
-            }
-        }
-    }
-
-    (Matrix { data }, start)
+fn parse(input: &str) -> (Grid<Pipe>, Pos) {
+    let data = Grid::parse(input, Pipe::from_char);
+    let start = data.position(|&p| p == Pipe::Start).unwrap();
+    (data, start)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -293,140 +360,29 @@ impl Pipe {
         }
     }
 
-    /// Returns `true` if the pipe is [`Horizontal`].
-    ///
-    /// [`Horizontal`]: Pipe::Horizontal
-    #[must_use]
-    fn is_horizontal(&self) -> bool {
-        matches!(self, Self::Horizontal)
-    }
-
-    /// Returns `true` if the pipe is [`None`].
-    ///
-    /// [`None`]: Pipe::None
-    #[must_use]
-    fn is_none(&self) -> bool {
-        matches!(self, Self::None)
-    }
-}
-
-#[derive(Debug, Clone)]
-struct Matrix<T, const N: usize> {
-    data: [[T; N]; N],
-}
-
-impl<T, const N: usize> Matrix<T, N> {
-    fn cursor(&self, pos: (usize, usize)) -> MatrixCursor<T, N> {
-        MatrixCursor::new(&self.data, pos)
-    }
-}
-
-#[derive(Debug, Clone)]
-struct MatrixCursor<'a, T, const N: usize> {
-    data: &'a [[T; N]; N],
-    posx: usize,
-    posy: usize,
-}
-
-impl<'a, T, const N: usize> MatrixCursor<'a, T, N> {
-    fn new(data: &'a [[T; N]; N], pos: (usize, usize)) -> Self {
-        Self {
-            data,
-            posx: pos.0,
-            posy: pos.1,
-        }
-    }
-
-    fn current(&self) -> &T {
-        &self.data[self.posx][self.posy]
-    }
-
-    fn current_pos(&self) -> (usize, usize) {
-        (self.posx, self.posy)
-    }
-
-    fn peek_up(&self) -> Option<&T> {
-        if self.posy > 0 {
-            Some(&self.data[self.posx][self.posy - 1])
-        } else {
-            None
+    /// Directions to which the pipe connects, none for the start
+    fn connections(&self) -> &'static [Direction] {
+        match self {
+            Self::Vertical => &[Direction::Up, Direction::Down],
+            Self::Horizontal => &[Direction::Left, Direction::Right],
+            Self::ULCorner => &[Direction::Up, Direction::Left],
+            Self::URCorner => &[Direction::Up, Direction::Right],
+            Self::DLCorner => &[Direction::Down, Direction::Left],
+            Self::DRCorner => &[Direction::Down, Direction::Right],
+            Self::None | Self::Start => &[],
         }
     }
-
-    fn peek_down(&self) -> Option<&T> {
-        if self.posy < N - 1 {
-            Some(&self.data[self.posx][self.posy + 1])
-        } else {
-            None
-        }
-    }
-
-    fn peek_left(&self) -> Option<&T> {
-        if self.posx > 0 {
-            Some(&self.data[self.posx - 1][self.posy])
-        } else {
-            None
-        }
-    }
-
-    fn peek_right(&self) -> Option<&T> {
-        if self.posx < N - 1 {
-            Some(&self.data[self.posx + 1][self.posy])
-        } else {
-            None
-        }
-    }
-
-    fn step(&mut self, dir: Dir) -> bool {
-        match dir {
-            Dir::Up => self.move_up(),
-            Dir::Down => self.move_down(),
-            Dir::Left => self.move_left(),
-            Dir::Right => self.move_right(),
-            Dir::Unknown => unreachable!(),
-        }
-    }
-
-    fn move_up(&mut self) -> bool {
-        if self.posy > 0 {
-            self.posy -= 1;
-            return true;
-        }
-
-        false
-    }
-
-    fn move_down(&mut self) -> bool {
-        if self.posy < N - 1 {
-            self.posy += 1;
-            return true;
-        }
-
-        false
-    }
-
-    fn move_left(&mut self) -> bool {
-        if self.posx > 0 {
-            self.posx -= 1;
-            return true;
-        }
-
-        false
-    }
-
-    fn move_right(&mut self) -> bool {
-        if self.posx < N - 1 {
-            self.posx += 1;
-            return true;
-        }
-
-        false
-    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+    use std::ops::Range;
+
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::generate;
 
     const TEST_INPUT1: &str = indoc::indoc! {"
     ..F7.
@@ -438,21 +394,50 @@ mod tests {
 
     #[test]
     fn test_parse() {
-        let cursor = parse::<5>(TEST_INPUT1);
-        println!("{:?}", cursor);
-
-        println!("{:?}", cursor.0.cursor((2, 2)).peek_right());
+        let (data, start) = parse(TEST_INPUT1);
+        assert_eq!(start, (2, 0));
+        assert_eq!(
+            data.cursor((2, 2)).peek(Direction::Right),
+            Some(&Pipe::URCorner)
+        );
     }
 
     #[test]
     fn test_part1() {
-        let answer = solve_part1::<5>(TEST_INPUT1);
+        let answer = solve_part1(TEST_INPUT1);
+        assert_eq!(answer, 8);
+
+        let answer = solve_part1_bfs(TEST_INPUT1);
         assert_eq!(answer, 8);
     }
 
+    #[test]
+    fn test_graph() {
+        let graph = graph(TEST_INPUT1);
+
+        // Tiles without connected pipes are left out, here everything else is the loop
+        assert_eq!(graph.len(), 16);
+        assert_eq!(graph.edge_count(), 2 * 16);
+
+        let start = graph.id(&(2, 0)).unwrap();
+        let loop_component = graph
+            .components()
+            .into_iter()
+            .find(|component| component.contains(&start))
+            .unwrap();
+        assert_eq!(loop_component.len(), 16);
+        assert_eq!(graph.dfs(start).count(), 16);
+    }
+
     #[test]
     fn test_part2() {
-        let answer = solve_part2::<5>(TEST_INPUT1);
+        let answer = solve_part2(TEST_INPUT1);
+        assert_eq!(answer, 1);
+
+        let answer = solve_part2_7j(TEST_INPUT1);
+        assert_eq!(answer, 1);
+
+        let answer = solve_part2_pick(TEST_INPUT1);
         assert_eq!(answer, 1);
     }
 
@@ -470,8 +455,29 @@ mod tests {
 
     #[test]
     fn test_part2_2() {
-        let answer = solve_part2::<11>(TEST_INPUT2);
+        let answer = solve_part2(TEST_INPUT2);
+        assert_eq!(answer, 4);
+
+        let answer = solve_part2_7j(TEST_INPUT2);
         assert_eq!(answer, 4);
+
+        let answer = solve_part2_pick(TEST_INPUT2);
+        assert_eq!(answer, 4);
+    }
+
+    #[test]
+    fn test_visualize() {
+        let frames = visualize(TEST_INPUT2);
+        let last = frames.last().unwrap();
+        assert_eq!(last.cell((1, 1)).ch, '┌');
+        assert_eq!(last.cell((1, 1)).bg, Some(Rgb::RED));
+
+        let inside = (0..last.rows())
+            .flat_map(|row| (0..last.cols()).map(move |col| (row, col)))
+            .filter(|&pos| last.cell(pos).bg == Some(Rgb::GREEN))
+            .collect::<Vec<_>>();
+        assert_eq!(inside, [(6, 2), (6, 3), (6, 7), (6, 8)]);
+        assert_eq!(frames.len(), 47);
     }
     const TEST_INPUT3: &str = indoc::indoc! {"
     FF7FSF7F7F7F7F7F---7
@@ -488,8 +494,143 @@ mod tests {
 
     #[test]
     fn test_part2_3() {
-        let answer = solve_part2::<25>(TEST_INPUT3);
+        let answer = solve_part2(TEST_INPUT3);
         assert_eq!(answer, 10);
+
+        let answer = solve_part2_7j(TEST_INPUT3);
+        assert_eq!(answer, 10);
+
+        let answer = solve_part2_pick(TEST_INPUT3);
+        assert_eq!(answer, 10);
+    }
+
+    /// Returns the length of the loop and the number of enclosed tiles.
+    ///
+    /// Every tile is scaled up to 3x3 with the pipe drawn in the middle, the enclosed tiles are those whose center
+    /// cannot be reached by flood filling from the outside.
+    fn solve_brute_force(input: &str) -> (u64, u64) {
+        fn connections(c: u8) -> &'static [(isize, isize)] {
+            match c {
+                b'|' => &[(0, -1), (0, 1)],
+                b'-' => &[(-1, 0), (1, 0)],
+                b'L' => &[(0, -1), (1, 0)],
+                b'J' => &[(0, -1), (-1, 0)],
+                b'7' => &[(0, 1), (-1, 0)],
+                b'F' => &[(0, 1), (1, 0)],
+                b'S' => &[(0, -1), (0, 1), (-1, 0), (1, 0)],
+                _ => &[],
+            }
+        }
+
+        let grid = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        let (rows, cols) = (grid.len() as isize, grid[0].len() as isize);
+        let get = |x: isize, y: isize| {
+            if x < 0 || y < 0 || x >= cols || y >= rows {
+                b'.'
+            } else {
+                grid[y as usize][x as usize]
+            }
+        };
+        let connected = |x: isize, y: isize, (dx, dy): (isize, isize)| {
+            connections(get(x, y)).contains(&(dx, dy))
+                && connections(get(x + dx, y + dy)).contains(&(-dx, -dy))
+        };
+
+        let start = (0..rows)
+            .flat_map(|y| (0..cols).map(move |x| (x, y)))
+            .find(|&(x, y)| get(x, y) == b'S')
+            .unwrap();
+        let mut on_loop = HashSet::from([start]);
+        let mut stack = vec![start];
+        while let Some((x, y)) = stack.pop() {
+            for &(dx, dy) in connections(get(x, y)) {
+                if connected(x, y, (dx, dy)) && on_loop.insert((x + dx, y + dy)) {
+                    stack.push((x + dx, y + dy));
+                }
+            }
+        }
+
+        // scaled grid with an extra empty tile around so that the outside is connected
+        let (scaled_cols, scaled_rows) = (3 * cols + 6, 3 * rows + 6);
+        let mut blocked = HashSet::new();
+        for &(x, y) in &on_loop {
+            let (cx, cy) = (3 * x + 4, 3 * y + 4);
+            blocked.insert((cx, cy));
+            for &(dx, dy) in connections(get(x, y)) {
+                if connected(x, y, (dx, dy)) {
+                    blocked.insert((cx + dx, cy + dy));
+                }
+            }
+        }
+
+        let mut outside = HashSet::from([(0, 0)]);
+        let mut stack = vec![(0, 0)];
+        while let Some((x, y)) = stack.pop() {
+            for (dx, dy) in [(0, -1), (0, 1), (-1, 0), (1, 0)] {
+                let next = (x + dx, y + dy);
+                if next.0 >= 0
+                    && next.1 >= 0
+                    && next.0 < scaled_cols
+                    && next.1 < scaled_rows
+                    && !blocked.contains(&next)
+                    && outside.insert(next)
+                {
+                    stack.push(next);
+                }
+            }
+        }
+
+        let enclosed = (0..rows)
+            .flat_map(|y| (0..cols).map(move |x| (x, y)))
+            .filter(|&(x, y)| {
+                !on_loop.contains(&(x, y)) && !outside.contains(&(3 * x + 4, 3 * y + 4))
+            })
+            .count();
+
+        (on_loop.len() as u64 / 2, enclosed as u64)
+    }
+
+    #[test]
+    fn test_brute_force() {
+        assert_eq!(solve_brute_force(TEST_INPUT1), (8, 1));
+        assert_eq!(solve_brute_force(TEST_INPUT3).1, 10);
+    }
+
+    const PROP_SIZE: usize = 19;
+
+    /// Grid with a random loop, see [`generate::pipe_loop`]
+    fn loop_input() -> impl Strategy<Value = String> {
+        let cells = (PROP_SIZE - 1) / 2;
+        (
+            0..cells / 2,
+            prop::collection::vec((0..cells, 0..cells), 1..=cells / 2),
+            prop::collection::vec(prop::sample::select(b"|-LJ7F..".to_vec()), 1..10),
+        )
+            .prop_map(move |(first_col, raw_cols, junk)| {
+                // make neighbouring columns overlap
+                let mut cols = Vec::<Range<usize>>::new();
+                for (top, bottom) in raw_cols {
+                    let prev = cols.last().cloned().unwrap_or(0..cells);
+                    let top = top.min(prev.end - 1);
+                    let bottom = bottom.max(top).max(prev.start);
+                    cols.push(top..bottom + 1);
+                }
+
+                let mut junk = junk.into_iter().cycle();
+                generate::pipe_loop(PROP_SIZE, first_col, &cols, || junk.next().unwrap() as char)
+            })
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_against_brute_force(input in loop_input()) {
+            let (part1, part2) = solve_brute_force(&input);
+            prop_assert_eq!(solve_part1(&input), part1);
+            prop_assert_eq!(solve_part1_bfs(&input), part1);
+            prop_assert_eq!(solve_part2(&input), part2);
+            prop_assert_eq!(solve_part2_7j(&input), part2);
+            prop_assert_eq!(solve_part2_pick(&input), part2);
+        }
     }
 }
 
@@ -500,13 +641,13 @@ mod benches {
 
     #[divan::bench]
     fn part1() {
-        let answer = solve_part1::<140>(black_box(INPUT));
+        let answer = solve_part1(black_box(INPUT));
         assert_eq!(answer, 6907);
     }
 
     #[divan::bench]
     fn part2() {
-        let answer = solve_part2::<140>(black_box(INPUT));
+        let answer = solve_part2(black_box(INPUT));
         assert_eq!(answer, 541);
     }
 }
@@ -1,13 +1,17 @@
-use std::collections::btree_set::Difference;
-use std::collections::{BTreeSet, HashSet};
+use crate::grid::Grid;
+use crate::math;
 
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 11;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day11.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
     let answer_part1 = solve_part1(input);
     println!("day{DAY}::part1 answer: {}", answer_part1);
@@ -16,16 +20,32 @@ fn solve(input: &str) {
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u64 {
-    solve_core(input, 2)
+pub(crate) fn solve_part1(input: &str) -> u64 {
+    solve_core(input, 2, expand_by_count)
 }
 
-fn solve_part2(input: &str) -> u64 {
-    solve_core(input, 1_000_000)
+pub(crate) fn solve_part2(input: &str) -> u64 {
+    solve_core(input, 1_000_000, expand_by_count)
 }
 
-fn solve_core(input: &str, expansion_rate: usize) -> u64 {
-    let galaxies = get_galaxy_locations(input, expansion_rate);
+pub(crate) fn solve_part1_reverse(input: &str) -> u64 {
+    solve_core(input, 2, expand_in_reverse)
+}
+
+pub(crate) fn solve_part2_reverse(input: &str) -> u64 {
+    solve_core(input, 1_000_000, expand_in_reverse)
+}
+
+pub(crate) fn solve_part1_sorted(input: &str) -> u64 {
+    solve_core(input, 2, expand_sorted)
+}
+
+pub(crate) fn solve_part2_sorted(input: &str) -> u64 {
+    solve_core(input, 1_000_000, expand_sorted)
+}
+
+fn solve_core(input: &str, expansion_rate: usize, expand: Expand) -> u64 {
+    let galaxies = get_galaxy_locations(input, expansion_rate, expand);
 
     galaxies
         .iter()
@@ -45,90 +65,107 @@ struct Galaxy {
     y: usize,
 }
 
-fn get_galaxy_locations(input: &str, expansion_multiplier: usize) -> Vec<Galaxy> {
-    let grid_size = input.lines().next().unwrap().chars().count();
+/// Shifts the galaxies by `shift` for every empty column in `empty_xs` and row in `empty_ys` before them.
+/// The empty rows and columns are sorted.
+type Expand = fn(galaxies: &mut [Galaxy], empty_xs: &[usize], empty_ys: &[usize], shift: usize);
+
+fn get_galaxy_locations(input: &str, expansion_multiplier: usize, expand: Expand) -> Vec<Galaxy> {
+    let grid = Grid::parse(input, |c| c == '#');
+
+    let mut galaxies = grid
+        .positions()
+        .filter(|&pos| grid[pos])
+        .map(|(y, x)| Galaxy { x, y })
+        .collect::<Vec<_>>();
+
+    // Rows and columns without a galaxy, in increasing order
+    let empty_xs = (0..grid.cols())
+        .filter(|&x| !grid.col(x).any(|&g| g))
+        .collect::<Vec<_>>();
+    let empty_ys = (0..grid.rows())
+        .filter(|&y| !grid.row(y).contains(&true))
+        .collect::<Vec<_>>();
+
+    // if expansion_multiplier == 2, then we have 1 more row, eg shift is 1 less than the rate
+    expand(
+        &mut galaxies,
+        &empty_xs,
+        &empty_ys,
+        expansion_multiplier - 1,
+    );
+
+    galaxies
+}
+
+fn expand_by_count(galaxies: &mut [Galaxy], empty_xs: &[usize], empty_ys: &[usize], shift: usize) {
+    for galaxy in galaxies.iter_mut() {
+        let xshift = empty_xs.iter().filter(|&&x| galaxy.x > x).count();
+        let yshift = empty_ys.iter().filter(|&&y| galaxy.y > y).count();
+
+        galaxy.x += xshift * shift;
+        galaxy.y += yshift * shift;
+    }
+}
 
-    let mut galaxies = Vec::new();
-    // Keep track of which rows and columns haven't seen a galaxy
-    let mut empty_xs = BTreeSet::from_iter(0usize..grid_size);
-    let mut empty_ys = BTreeSet::from_iter(0usize..grid_size);
+// Below are couple other variants on how to shift the galaxies
+// They are slightly slower..
+
+/// Iterate other way around
+fn expand_in_reverse(
+    galaxies: &mut [Galaxy],
+    empty_xs: &[usize],
+    empty_ys: &[usize],
+    shift: usize,
+) {
+    // Going from the last empty column makes sure that already shifted galaxies are still after the next column
+    for &x in empty_xs.iter().rev() {
+        for galaxy in galaxies.iter_mut() {
+            if galaxy.x > x {
+                galaxy.x += shift;
+            }
+        }
+    }
 
-    for (y, l) in input.lines().enumerate() {
-        for (x, c) in l.chars().enumerate() {
-            if c == '#' {
-                empty_ys.remove(&y);
-                empty_xs.remove(&x);
-                let loc = Galaxy { x, y };
-                galaxies.push(loc);
+    for &y in empty_ys.iter().rev() {
+        for galaxy in galaxies.iter_mut() {
+            if galaxy.y > y {
+                galaxy.y += shift;
             }
         }
     }
+}
+
+/// Sort and iterate only once
+fn expand_sorted(galaxies: &mut [Galaxy], empty_xs: &[usize], empty_ys: &[usize], shift: usize) {
+    galaxies.sort_by_key(|g| g.x);
 
-    // Since we need to iterate over the sets many times, we collect them before iterating
-    let empty_xs = empty_xs.into_iter().collect::<Vec<_>>();
-    let empty_ys = empty_ys.into_iter().collect::<Vec<_>>();
+    let mut shifts = empty_xs.iter();
+    let mut next_shift_x = *shifts.next().unwrap_or(&usize::MAX);
+    let mut total_shift = 0;
 
     for galaxy in galaxies.iter_mut() {
-        let xshift = empty_xs.iter().filter(|&&x| galaxy.x > x).count();
-        let yshift = empty_ys.iter().filter(|&&y| galaxy.y > y).count();
+        while galaxy.x > next_shift_x {
+            next_shift_x = *shifts.next().unwrap_or(&usize::MAX);
+            total_shift += shift;
+        }
 
-        // if expansion_multiplier == 2, then we have 1 more row, eg shift is 1 less than the rate
-        galaxy.x += xshift * (expansion_multiplier - 1);
-        galaxy.y += yshift * (expansion_multiplier - 1);
+        galaxy.x += total_shift;
     }
 
-    // Below are couple other variants on how to shift the galaxies
-    // They are slightly slower..
-
-    // Iterate other way around
-    // for (shift, x) in (1..empty_xs.len() + 1).zip(empty_xs).rev() {
-    //     for galaxy in galaxies.iter_mut() {
-    //         if galaxy.x > x {
-    //             galaxy.x += (expansion_multiplier - 1);
-    //         }
-    //     }
-    // }
-
-    // for (shift, y) in (1..empty_ys.len() + 1).zip(empty_ys).rev() {
-    //     for galaxy in galaxies.iter_mut() {
-    //         if galaxy.y > y {
-    //             galaxy.y += (expansion_multiplier - 1);
-    //         }
-    //     }
-    // }
-
-    // Sort and iterate only once
-    // galaxies.sort_by(|g1, g2| g1.x.cmp(&g2.x));
-
-    // let mut shifts = empty_xs.iter();
-    // let mut next_shift_x = *shifts.next().unwrap_or(&grid_size);
-    // let mut shift = 0;
-
-    // for galaxy in galaxies.iter_mut() {
-    //     while galaxy.x > next_shift_x {
-    //         next_shift_x = *shifts.next().unwrap_or(&grid_size);
-    //         shift += expansion_multiplier - 1;
-    //     }
-
-    //     galaxy.x += shift;
-    // }
-
-    // galaxies.sort_by(|g1, g2| g1.y.cmp(&g2.y));
-
-    // let mut shifts = empty_ys.iter();
-    // let mut next_shift_y = *shifts.next().unwrap_or(&grid_size);
-    // let mut shift = 0;
-
-    // for galaxy in galaxies.iter_mut() {
-    //     while galaxy.y > next_shift_y {
-    //         next_shift_y = *shifts.next().unwrap_or(&grid_size);
-    //         shift += expansion_multiplier - 1;
-    //     }
-
-    //     galaxy.y += shift;
-    // }
+    galaxies.sort_by_key(|g| g.y);
 
-    galaxies
+    let mut shifts = empty_ys.iter();
+    let mut next_shift_y = *shifts.next().unwrap_or(&usize::MAX);
+    let mut total_shift = 0;
+
+    for galaxy in galaxies.iter_mut() {
+        while galaxy.y > next_shift_y {
+            next_shift_y = *shifts.next().unwrap_or(&usize::MAX);
+            total_shift += shift;
+        }
+
+        galaxy.y += total_shift;
+    }
 }
 
 fn distance(loc1: &Galaxy, loc2: &Galaxy) -> usize {
@@ -141,8 +178,7 @@ fn distance(loc1: &Galaxy, loc2: &Galaxy) -> usize {
     // ....##...7...
     // 8....9.......
     // Pipes and # result in same distance.
-    (loc1.x as isize - loc2.x as isize).unsigned_abs()
-        + (loc1.y as isize - loc2.y as isize).unsigned_abs()
+    math::manhattan((loc1.y, loc1.x), (loc2.y, loc2.x))
 }
 
 #[cfg(test)]
@@ -177,7 +213,7 @@ mod tests {
 
     #[test]
     fn test_get_locations() {
-        let locations = get_galaxy_locations(TEST_INPUT1, 2);
+        let locations = get_galaxy_locations(TEST_INPUT1, 2, expand_by_count);
         println!("locations: {:?}", locations);
     }
 
@@ -189,12 +225,24 @@ mod tests {
 
     #[test]
     fn test_part2_example() {
-        let answer = solve_core(TEST_INPUT1, 10);
+        let answer = solve_core(TEST_INPUT1, 10, expand_by_count);
         assert_eq!(answer, 1030);
 
-        let answer = solve_core(TEST_INPUT1, 100);
+        let answer = solve_core(TEST_INPUT1, 100, expand_by_count);
         assert_eq!(answer, 8410);
     }
+
+    #[test]
+    fn test_expand_variants() {
+        let mut expected = get_galaxy_locations(TEST_INPUT1, 10, expand_by_count);
+        expected.sort_by_key(|g| (g.x, g.y));
+
+        for expand in [expand_in_reverse as Expand, expand_sorted] {
+            let mut locations = get_galaxy_locations(TEST_INPUT1, 10, expand);
+            locations.sort_by_key(|g| (g.x, g.y));
+            assert_eq!(locations, expected);
+        }
+    }
 }
 
 #[cfg(feature = "divan")]
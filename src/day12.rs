@@ -1,10 +1,14 @@
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 0;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day1.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
     let answer_part1 = solve_part1(input);
     println!("day{DAY}::part1 answer: {}", answer_part1);
@@ -13,11 +17,11 @@ fn solve(input: &str) {
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u64 {
+pub(crate) fn solve_part1(_input: &str) -> u64 {
     todo!()
 }
 
-fn solve_part2(input: &str) -> u64 {
+pub(crate) fn solve_part2(_input: &str) -> u64 {
     todo!()
 }
 #[cfg(test)]
@@ -31,13 +35,13 @@ mod tests {
     #[test]
     fn test_part1() {
         let answer = solve_part1(TEST_INPUT1);
-        assert_eq!(answer, todo!());
+        assert_eq!(answer, todo!() as u64);
     }
 
     #[test]
     fn test_part2() {
         let answer = solve_part2(TEST_INPUT1);
-        assert_eq!(answer, todo!());
+        assert_eq!(answer, todo!() as u64);
     }
 }
 
@@ -49,12 +53,12 @@ mod benches {
     #[divan::bench]
     fn part1() {
         let answer = solve_part1(black_box(INPUT));
-        assert_eq!(answer, todo!());
+        assert_eq!(answer, todo!() as u64);
     }
 
     #[divan::bench]
     fn part2() {
         let answer = solve_part2(black_box(INPUT));
-        assert_eq!(answer, todo!());
+        assert_eq!(answer, todo!() as u64);
     }
 }
@@ -1,98 +1,59 @@
-use core::panic;
+use crate::bitgrid::{self, BitGrid};
+use crate::parse;
 
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 13;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day13.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
-    let answer_part1 = solve_part1(input);
+    let answer_part1 = solve_part1(input).unwrap();
     println!("day{DAY}::part1 answer: {}", answer_part1);
 
-    let answer_part2 = solve_part2(input);
+    let answer_part2 = solve_part2(input).unwrap();
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u64 {
-    solve_core(input, solve_one_grid)
+pub(crate) fn solve_part1(input: &str) -> parse::Result<u64> {
+    solve_core(input, find_reflection)
 }
 
-fn solve_part2(input: &str) -> u64 {
-    solve_core(input, solve_one_grid_with_smudge)
+pub(crate) fn solve_part2(input: &str) -> parse::Result<u64> {
+    solve_core(input, find_reflection_with_smudge)
 }
 
-fn solve_core(input: &str, solve_one_grid: fn(&[&str]) -> u64) -> u64 {
-    let mut lines = Vec::new();
-    let mut sum = 0;
-    for line in input.lines() {
-        if line.is_empty() {
-            sum += solve_one_grid(&lines);
-            lines.clear();
-        } else {
-            lines.push(line);
-        }
-    }
-
-    sum + solve_one_grid(&lines)
-}
-
-fn solve_one_grid(lines: &[&str]) -> u64 {
-    // Look for horizontal reflection first if not found, look for vertical
-
-    let horizontal_reflection = find_reflection(lines);
-    match horizontal_reflection {
-        Some(reflection) => (reflection * 100) as u64,
-        None => {
-            let num_cols = lines[0].chars().count();
-            let mut cols = Vec::with_capacity(num_cols);
-            for c in 0..num_cols {
-                let mut s = String::with_capacity(lines.len());
-                for l in lines.iter() {
-                    s.push(l.chars().nth(c).unwrap());
-                }
-                cols.push(s);
-            }
-
-            //println!("{:?}", cols);
-            match find_reflection(&cols) {
-                Some(reflection) => reflection as u64,
-                None => {
-                    println!("{}", lines.join("\n"));
-                    panic!();
-                }
+fn solve_core(input: &str, find_reflection: fn(&[u64]) -> Option<usize>) -> parse::Result<u64> {
+    parse::blocks(input)
+        .map(|pattern| {
+            let grid = BitGrid::parse(pattern, |c| c == '#')?;
+            // the columns are the rows of the transposed grid, so they have to fit into the bits too
+            if grid.rows() > u64::BITS as usize {
+                return Err(parse::ParseError::TooLarge {
+                    what: "pattern height",
+                    max: u64::BITS as usize,
+                    found: grid.rows(),
+                });
             }
-        }
-    }
+            Ok(summarize(&grid, find_reflection))
+        })
+        .sum()
 }
 
-fn solve_one_grid_with_smudge(lines: &[&str]) -> u64 {
+fn summarize(grid: &BitGrid<u64>, find_reflection: fn(&[u64]) -> Option<usize>) -> u64 {
     // Look for horizontal reflection first if not found, look for vertical
 
-    let horizontal_reflection = find_reflection_with_smudge(lines);
-    match horizontal_reflection {
+    match find_reflection(grid.as_rows()) {
         Some(reflection) => (reflection * 100) as u64,
-        None => {
-            let num_cols = lines[0].chars().count();
-            let mut cols = Vec::with_capacity(num_cols);
-            for c in 0..num_cols {
-                let mut s = String::with_capacity(lines.len());
-                for l in lines.iter() {
-                    s.push(l.chars().nth(c).unwrap());
-                }
-                cols.push(s);
-            }
-
-            //println!("{:?}", cols);
-            match find_reflection_with_smudge(&cols) {
-                Some(reflection) => reflection as u64,
-                None => {
-                    println!("{}", lines.join("\n"));
-                    panic!();
-                }
-            }
-        }
+        None => match find_reflection(grid.transpose().as_rows()) {
+            Some(reflection) => reflection as u64,
+            None => panic!("no reflection found in\n{grid}"),
+        },
     }
 }
 
@@ -124,7 +85,8 @@ fn find_reflection<T: PartialEq>(items: &[T]) -> Option<usize> {
     None
 }
 
-fn find_reflection_with_smudge<T: AsRef<str>>(items: &[T]) -> Option<usize> {
+/// `items` are the rows or columns as bits, so the number of differing cells is their Hamming distance
+fn find_reflection_with_smudge(items: &[u64]) -> Option<usize> {
     // 1. Find items next to each other that are equal or only differ by one char (smudge),
     //    this is possible reflection point
     // 2. Iterate outward from that point and check if every next item outward is equal or differs by one too.
@@ -135,9 +97,8 @@ fn find_reflection_with_smudge<T: AsRef<str>>(items: &[T]) -> Option<usize> {
     //    Or if we find a not equal pair, then go to the next possible reflection point and repeat from 2
 
     'outer: for (possible_reflection_point, ls) in items.windows(2).enumerate() {
-        let ls0 = ls[0].as_ref();
-        let ls1 = ls[1].as_ref();
-        let diffs_by_one = differs_by_one(ls0.chars(), ls1.chars());
+        let (ls0, ls1) = (ls[0], ls[1]);
+        let diffs_by_one = bitgrid::hamming(ls0, ls1) == 1;
 
         if !(ls0 == ls1 || diffs_by_one) {
             continue;
@@ -149,12 +110,9 @@ fn find_reflection_with_smudge<T: AsRef<str>>(items: &[T]) -> Option<usize> {
             let backward = possible_reflection_point - step;
             let forward = possible_reflection_point + step + 1;
 
-            match (
-                items.get(backward).map(AsRef::as_ref),
-                items.get(forward).map(AsRef::as_ref),
-            ) {
+            match (items.get(backward), items.get(forward)) {
                 (Some(a), Some(b)) if a == b => {}
-                (Some(a), Some(b)) if !is_smudge_fixed && differs_by_one(a.chars(), b.chars()) => {
+                (Some(&a), Some(&b)) if !is_smudge_fixed && bitgrid::hamming(a, b) == 1 => {
                     is_smudge_fixed = true;
                 }
                 (Some(_), Some(_)) => continue 'outer, // lines not equal, go to next possible reflection point
@@ -169,10 +127,6 @@ fn find_reflection_with_smudge<T: AsRef<str>>(items: &[T]) -> Option<usize> {
     None
 }
 
-fn differs_by_one<T: PartialEq>(l1: impl Iterator<Item = T>, l2: impl Iterator<Item = T>) -> bool {
-    l1.zip(l2).filter(|(a, b)| a != b).count() == 1
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,19 +168,40 @@ mod tests {
     #[test]
     fn test_part1() {
         let answer = solve_part1(TEST_INPUT1);
-        assert_eq!(answer, 405);
+        assert_eq!(answer, Ok(405));
     }
 
     #[test]
     fn test_part1_2() {
         let answer = solve_part1(TEST_INPUT2);
-        assert_eq!(answer, 6);
+        assert_eq!(answer, Ok(6));
     }
 
     #[test]
     fn test_part2() {
         let answer = solve_part2(TEST_INPUT1);
-        assert_eq!(answer, 400);
+        assert_eq!(answer, Ok(400));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        let ragged = "#.#\n#.\n#.#\n";
+        assert!(matches!(
+            solve_part1(ragged),
+            Err(parse::ParseError::WrongWidth { line: 2, .. })
+        ));
+
+        let wide = format!("{}\n{}\n", "#".repeat(65), "#".repeat(65));
+        assert!(matches!(
+            solve_part1(&wide),
+            Err(parse::ParseError::TooLarge { found: 65, .. })
+        ));
+
+        let tall = "#.\n".repeat(65);
+        assert!(matches!(
+            solve_part2(&tall),
+            Err(parse::ParseError::TooLarge { found: 65, .. })
+        ));
     }
 }
 
@@ -238,12 +213,12 @@ mod benches {
     #[divan::bench]
     fn part1() {
         let answer = solve_part1(black_box(INPUT));
-        assert_eq!(answer, 31956);
+        assert_eq!(answer, Ok(31956));
     }
 
     #[divan::bench]
     fn part2() {
         let answer = solve_part2(black_box(INPUT));
-        assert_eq!(answer, 37617);
+        assert_eq!(answer, Ok(37617));
     }
 }
@@ -1,40 +1,47 @@
-use std::borrow::Cow;
-use std::collections::HashMap;
-
+use crate::bitgrid::BitGrid;
+use crate::cycle;
+use crate::grid::{Direction, Grid};
+use crate::math;
+use crate::parse::{self, ParseError};
+use crate::visualize::{Canvas, Cell, Rgb};
+
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 14;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day14.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
-    let answer_part1 = solve_part1(input);
+    let answer_part1 = solve_part1(input).unwrap();
     println!("day{DAY}::part1 answer: {}", answer_part1);
 
-    let answer_part2 = solve_part2(input);
+    let answer_part2 = solve_part2(input).unwrap();
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u64 {
+pub(crate) fn solve_part1(input: &str) -> parse::Result<u64> {
     // Iterate the rows in reverse and count the rolling rocks in each column.
     // If we find a square rock in a column, we know where all of the rolling rocks
     // below it will stop. Hence we can calculate the weight of those rocks.
     // We continue until we reach the end of the input.
     // Then finally we'll need to add the final rolling rocks too that stopped at the top edge.
 
-    let cols = input.lines().next().unwrap().chars().count();
+    let grid = parse(input)?;
 
-    let mut rolling_rocks = vec![0; cols];
+    let mut rolling_rocks = vec![0; grid.cols()];
     let mut total_load = 0;
     let mut max_weight = 0;
-    for (l, weight) in input.lines().rev().zip(1..) {
-        // dbg!(weight, l);
-        for (i, c) in l.chars().enumerate() {
+    for (l, weight) in grid.iter_rows().rev().zip(1..) {
+        for (i, &c) in l.iter().enumerate() {
             match c {
-                'O' => rolling_rocks[i] += 1,
-                '.' => {}
-                '#' => {
+                b'O' => rolling_rocks[i] += 1,
+                b'.' => {}
+                b'#' => {
                     // for i in 0..rolling_rocks[i] {
                     //     // -1 because the square rock is on the line,
                     //     // rolling rocks start from previous line
@@ -44,8 +51,8 @@ fn solve_part1(input: &str) -> u64 {
                     // above has closed form solution
                     let num_rolling_rocks = rolling_rocks[i];
                     if num_rolling_rocks != 0 {
-                        total_load +=
-                            num_rolling_rocks * (weight - 1) - triag_number(num_rolling_rocks - 1);
+                        total_load += num_rolling_rocks * (weight - 1)
+                            - math::triangular(num_rolling_rocks - 1);
                     }
                     rolling_rocks[i] = 0;
                 }
@@ -56,155 +63,176 @@ fn solve_part1(input: &str) -> u64 {
     }
 
     for num_rolling_rocks in rolling_rocks.into_iter().filter(|&r| r != 0) {
-        total_load += num_rolling_rocks * max_weight - triag_number(num_rolling_rocks - 1);
+        total_load += num_rolling_rocks * max_weight - math::triangular(num_rolling_rocks - 1);
     }
 
-    total_load
+    Ok(total_load)
 }
 
-/// Calculates the triangular number of n.
-///
-/// That is 1 + 2 + 3 + 4 + ... + n
-fn triag_number(n: u64) -> u64 {
-    n * (n + 1) / 2
-}
+// Note to simplify the comments, we define a rotation as one cycle of north, west, south and east tilts.
+// This is defined as cycle in the AoC problem.
+const ROTATIONS: usize = 1_000_000_000;
 
-fn solve_part2(input: &str) -> u64 {
-    const ROTATIONS: usize = 1_000_000_000;
+pub(crate) fn solve_part2(input: &str) -> parse::Result<u64> {
     solve_part2_core(input, ROTATIONS)
 }
-fn solve_part2_core(input: &str, num_rotations: usize) -> u64 {
-    // Note to simplify the comment, we define a rotation as one cycle of north, west, south and east tilts.
-    // This is defined as cycle in the AoC problem.
-    // A cycle is then defined as some cycle of rotations.
-    //
-    // General idea is to use a cache to map one grid to the one after rotation (north, west, south, east tilts).
-    // Now whenever we hit this cache, we have reached the start of a cycle since the next grid must also be in the cache.
-    // Thus we can find the length of this cycle and directly calculate how many times we need to repeat it
-    // before we reach the desired number of rotations (`num_rotations`). Note that this doesn't
-    // necessarily exactly reach `num_rotations`. Thus finally we continue the rotations until we have exactly
-    // reached the desired number of rotations.
+
+/// Same as [`solve_part2`] but finds the cycle with Brent's algorithm, which keeps only a couple of grids in
+/// memory but has to rotate them more often.
+pub(crate) fn solve_part2_brent(input: &str) -> parse::Result<u64> {
+    let (rocks, walls) = match parse_bits(input) {
+        Ok(grids) => grids,
+        Err(ParseError::TooLarge { .. }) => return solve_part2_bytes(input),
+        Err(e) => return Err(e),
+    };
+    let rotated = |rocks: &BitGrid| rotated_bits(rocks, &walls);
+    let cycle = cycle::brent(rocks.clone(), rotated);
+    Ok(north_load_bits(&cycle::nth(
+        rocks, rotated, cycle, ROTATIONS,
+    )))
+}
+
+/// Same as [`solve_part2`] but moves the rocks one byte at a time in a [`Grid`]
+pub(crate) fn solve_part2_bytes(input: &str) -> parse::Result<u64> {
+    solve_part2_bytes_core(input, ROTATIONS)
+}
+
+fn solve_part2_core(input: &str, num_rotations: usize) -> parse::Result<u64> {
+    // The grids after every rotation eventually repeat. Remember every grid until we find the first
+    // repeated one, after that the grid after `num_rotations` is one of the remembered grids.
+    // Only the rolling rocks change, the square rocks are kept in a separate grid.
     //
     // And finally calculate the load on north support beam.
 
-    let mut cache = HashMap::<Vec<u8>, (usize, Vec<u8>)>::new();
-    let num_cols = input.lines().next().unwrap().len() + 1;
+    let (rocks, walls) = match parse_bits(input) {
+        Ok(grids) => grids,
+        // the rows don't fit into the bits, the bytes are slower but work for any width
+        Err(ParseError::TooLarge { .. }) => return solve_part2_bytes_core(input, num_rotations),
+        Err(e) => return Err(e),
+    };
+    let (cycle, grids) = cycle::hashed(rocks, |rocks| rotated_bits(rocks, &walls));
+    Ok(north_load_bits(&grids[cycle.reduce(num_rotations)]))
+}
 
-    let mut grid = input.as_bytes().to_vec();
+fn solve_part2_bytes_core(input: &str, num_rotations: usize) -> parse::Result<u64> {
+    let (cycle, grids) = cycle::hashed(parse(input)?, rotated);
+    Ok(north_load(&grids[cycle.reduce(num_rotations)]))
+}
 
-    let mut rotation_num = 0;
-    let grid_ref = loop {
-        if rotation_num >= num_rotations {
-            // finished without finding a cycle
-            break None;
-        }
-        rotation_num += 1;
-
-        // Cache hit, find the length of the cycle and get as close as possible to desired number of rotations
-        if let Some((cy, next)) = cache.get(&grid) {
-            let cycle_len = rotation_num - cy;
-            let x = (num_rotations - rotation_num) / cycle_len;
-            rotation_num += cycle_len * x;
-            break Some(next);
-        }
+/// Calculate the load on north support beam
+fn north_load(grid: &Grid<u8>) -> u64 {
+    let mut total_load = 0;
+    for (l, weight) in grid.iter_rows().rev().zip(1..) {
+        total_load += weight * l.iter().filter(|&&c| c == b'O').count();
+    }
 
-        // No cache hit, calculate the next grid after a rotation
-        let prev_grid = grid.clone();
-        rotate_grid(&mut grid, num_cols);
-        cache.insert(prev_grid, (rotation_num, grid.clone()));
-    };
+    total_load as u64
+}
 
-    //dbg!(cache.len());
+/// Same as [`north_load`] for the rolling rocks of a [`BitGrid`]
+fn north_load_bits(rocks: &BitGrid) -> u64 {
+    (1..)
+        .zip(rocks.as_rows().iter().rev())
+        .map(|(weight, row)| weight * row.count_ones() as u64)
+        .sum()
+}
 
-    // Finish the rest of the rotations
-    let grid = if let Some(mut grid_ref) = grid_ref {
-        while rotation_num < num_rotations {
-            rotation_num += 1;
-            grid_ref = &cache[grid_ref].1;
+/// Directions of the tilts in one rotation
+const SPIN_CYCLE: [Direction; 4] = [
+    Direction::Up,
+    Direction::Left,
+    Direction::Down,
+    Direction::Right,
+];
+
+/// Perform one rotation on the rolling rocks, which stop at the `walls`
+fn rotated_bits(rocks: &BitGrid, walls: &BitGrid) -> BitGrid {
+    let mut rocks = rocks.clone();
+    for dir in SPIN_CYCLE {
+        rocks.tilt(walls, dir);
+    }
+    rocks
+}
+
+/// Frames of the first spin cycles, one after every tilt
+///
+/// # Panics
+/// If the grid is wider than 128 cells or its rows aren't all as wide.
+pub fn visualize(input: &str) -> Vec<Canvas> {
+    const CYCLES: usize = 3;
+
+    let (mut rocks, walls) = parse_bits(input).unwrap_or_else(|e| panic!("{e}"));
+    let frame = |rocks: &BitGrid| {
+        let mut canvas = Canvas::new(rocks.rows(), rocks.cols());
+        for pos in (0..rocks.rows()).flat_map(|row| (0..rocks.cols()).map(move |col| (row, col))) {
+            if rocks.get(pos) {
+                canvas.set(pos, Cell::new('O').fg(Rgb::ORANGE));
+            } else if walls.get(pos) {
+                canvas.set(pos, Cell::new('#').fg(Rgb::GREY));
+            }
         }
-        grid_ref
-    } else {
-        &grid
+        canvas
     };
 
-    // Calculate the load on north support beam
-    let mut total_load = 0;
-    for (l, weight) in grid
-        .split(|&c| c == b'\n')
-        .filter(|l| !l.is_empty())
-        .rev()
-        .zip(1..)
-    {
-        total_load += weight * l.iter().filter(|&&c| c == b'O').count();
+    let mut frames = vec![frame(&rocks)];
+    for _ in 0..CYCLES {
+        for dir in SPIN_CYCLE {
+            rocks.tilt(&walls, dir);
+            frames.push(frame(&rocks));
+        }
     }
+    frames
+}
 
-    total_load as u64
+fn rotated(grid: &Grid<u8>) -> Grid<u8> {
+    let mut grid = grid.clone();
+    rotate_grid(&mut grid);
+    grid
 }
 
 /// Perform one rotation on the grid
-///
-/// `grid` is assumed to be in a row major order
-fn rotate_grid(grid: &mut [u8], num_cols: usize) {
+fn rotate_grid(grid: &mut Grid<u8>) {
     // Go through the grid and rotate it
     //
     // Move from the the tilt direction one row (north, south tilt) or column (west, east tilt) at the time,
     // while keeping track where the next rolling rock will go in each row or column.
     // When a rolling rock was found, move it to the correct place.
 
+    let (num_rows, num_cols) = (grid.rows(), grid.cols());
+
     // tilt north
     // first rolling rock will go to the first row
-    let mut next_rolling_rock_location = (0..num_cols).collect::<Vec<_>>();
-
-    let mut line_index = 0;
-    let mut column_index = 0;
-    for char_index in 0..grid.len() {
-        let c = &mut grid[char_index];
-        //print!("'{}' ", *c as char);
-        match c {
-            b'.' => {}
-            b'#' => {
-                next_rolling_rock_location[column_index] = char_index + num_cols;
-            }
-            b'O' => {
-                *c = b'.';
-                grid[next_rolling_rock_location[column_index]] = b'O';
-                next_rolling_rock_location[column_index] += num_cols;
-            }
-            b'\n' => {
-                line_index += 1;
-                column_index = 0;
-                continue;
+    let mut next_rolling_rock_location = vec![0; num_cols];
+    for row in 0..num_rows {
+        for col in 0..num_cols {
+            match grid[(row, col)] {
+                b'.' => {}
+                b'#' => next_rolling_rock_location[col] = row + 1,
+                b'O' => {
+                    grid[(row, col)] = b'.';
+                    grid[(next_rolling_rock_location[col], col)] = b'O';
+                    next_rolling_rock_location[col] += 1;
+                }
+                _ => unreachable!(),
             }
-            _ => unreachable!(),
         }
-        column_index += 1;
     }
 
-    let num_rows = line_index;
-
     // tilt west
     // first rolling rock will go to the first column
-    let mut next_rolling_rock_location = (0..num_rows).map(|c| c * num_cols).collect::<Vec<_>>();
+    let mut next_rolling_rock_location = vec![0; num_rows];
 
     // move one column at a time from the left (west)
     for col in 0..num_cols {
         for row in 0..num_rows {
-            let char_index = row * num_cols + col;
-            let c = &mut grid[char_index];
-            // dbg!(&c);
-            match c {
+            match grid[(row, col)] {
                 b'.' => {}
-                b'#' => {
-                    next_rolling_rock_location[row] = char_index + 1;
-                }
+                b'#' => next_rolling_rock_location[row] = col + 1,
                 b'O' => {
-                    *c = b'.';
-                    grid[next_rolling_rock_location[row]] = b'O';
+                    grid[(row, col)] = b'.';
+                    grid[(row, next_rolling_rock_location[row])] = b'O';
                     next_rolling_rock_location[row] += 1;
                 }
-                b'\n' => {
-                    continue;
-                }
                 _ => unreachable!(),
             }
         }
@@ -212,25 +240,18 @@ fn rotate_grid(grid: &mut [u8], num_cols: usize) {
 
     // tilt south
     // first rolling rock will go to the last row
-    let mut next_rolling_rock_location = (grid.len() - num_cols..grid.len()).collect::<Vec<_>>();
+    let mut next_rolling_rock_location = vec![num_rows - 1; num_cols];
 
     for row in (0..num_rows).rev() {
         for col in 0..num_cols {
-            let char_index = row * num_cols + col;
-            let c = &mut grid[char_index];
-            match c {
+            match grid[(row, col)] {
                 b'.' => {}
-                b'#' => {
-                    next_rolling_rock_location[col] = char_index.saturating_sub(num_cols);
-                }
+                b'#' => next_rolling_rock_location[col] = row.saturating_sub(1),
                 b'O' => {
-                    *c = b'.';
-                    grid[next_rolling_rock_location[col]] = b'O';
+                    grid[(row, col)] = b'.';
+                    grid[(next_rolling_rock_location[col], col)] = b'O';
                     next_rolling_rock_location[col] =
-                        next_rolling_rock_location[col].saturating_sub(num_cols);
-                }
-                b'\n' => {
-                    continue;
+                        next_rolling_rock_location[col].saturating_sub(1);
                 }
                 _ => unreachable!(),
             }
@@ -239,37 +260,60 @@ fn rotate_grid(grid: &mut [u8], num_cols: usize) {
 
     // tilt east
     // first rolling rock will go to the last column
-    let mut next_rolling_rock_location = (0..num_rows)
-        .map(|c| c * num_cols + num_cols - 2)
-        .collect::<Vec<_>>();
+    let mut next_rolling_rock_location = vec![num_cols - 1; num_rows];
+
     // move one column at a time from the right (east)
     for col in (0..num_cols).rev() {
         for row in 0..num_rows {
-            let char_index = row * num_cols + col;
-            let c = &mut grid[char_index];
-            // dbg!(&c);
-            match c {
+            match grid[(row, col)] {
                 b'.' => {}
-                b'#' => {
-                    next_rolling_rock_location[row] = char_index.saturating_sub(1);
-                }
+                b'#' => next_rolling_rock_location[row] = col.saturating_sub(1),
                 b'O' => {
-                    *c = b'.';
-                    grid[next_rolling_rock_location[row]] = b'O';
+                    grid[(row, col)] = b'.';
+                    grid[(row, next_rolling_rock_location[row])] = b'O';
                     next_rolling_rock_location[row] =
                         next_rolling_rock_location[row].saturating_sub(1);
                 }
-                b'\n' => {
-                    continue;
-                }
                 _ => unreachable!(),
             }
         }
     }
 }
 
+fn parse(input: &str) -> parse::Result<Grid<u8>> {
+    Grid::try_parse(input, |c| {
+        check_cell(c)?;
+        Ok(c as u8)
+    })
+}
+
+/// Returns the rolling rocks and the square rocks
+fn parse_bits(input: &str) -> parse::Result<(BitGrid, BitGrid)> {
+    for c in input.chars().filter(|&c| c != '\n' && c != '\r') {
+        check_cell(c)?;
+    }
+
+    Ok((
+        BitGrid::parse(input, |c| c == 'O')?,
+        BitGrid::parse(input, |c| c == '#')?,
+    ))
+}
+
+/// Only rolling rocks `O`, square rocks `#` and empty space `.` are allowed
+fn check_cell(c: char) -> parse::Result<()> {
+    match c {
+        'O' | '#' | '.' => Ok(()),
+        _ => Err(ParseError::InvalidValue {
+            expected: "rock or empty space",
+            input: c.to_string(),
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     const TEST_INPUT1: &str = indoc::indoc! {"
@@ -288,13 +332,134 @@ mod tests {
     #[test]
     fn test_part1() {
         let answer = solve_part1(TEST_INPUT1);
-        assert_eq!(answer, 136);
+        assert_eq!(answer, Ok(136));
     }
 
     #[test]
     fn test_part2() {
         let answer = solve_part2(TEST_INPUT1);
-        assert_eq!(answer, 64);
+        assert_eq!(answer, Ok(64));
+
+        let answer = solve_part2_brent(TEST_INPUT1);
+        assert_eq!(answer, Ok(64));
+
+        let answer = solve_part2_bytes(TEST_INPUT1);
+        assert_eq!(answer, Ok(64));
+    }
+
+    #[test]
+    fn test_part2_few_rotations() {
+        // loads after 1, 2 and 3 rotations from the puzzle description, before the grids start repeating
+        assert_eq!(solve_part2_core(TEST_INPUT1, 1), Ok(87));
+        assert_eq!(solve_part2_core(TEST_INPUT1, 2), Ok(69));
+        assert_eq!(solve_part2_core(TEST_INPUT1, 3), Ok(69));
+    }
+
+    #[test]
+    fn test_part2_wide() {
+        // the rows don't fit into a u128 anymore, so the bytes are used instead
+        let input = TEST_INPUT1
+            .lines()
+            .map(|line| line.repeat(13) + "\n")
+            .collect::<String>();
+        let answer = solve_part2_bytes(&input);
+        assert!(answer.is_ok());
+        assert_eq!(solve_part2(&input), answer);
+        assert_eq!(solve_part2_brent(&input), answer);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let solvers = [
+            solve_part1,
+            solve_part2,
+            solve_part2_brent,
+            solve_part2_bytes,
+        ];
+        for solve in solvers {
+            assert!(matches!(
+                solve("O.#\n.O\n"),
+                Err(ParseError::WrongWidth { line: 2, .. })
+            ));
+            assert!(matches!(
+                solve("O.#\n.Ox\n"),
+                Err(ParseError::InvalidValue { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_visualize() {
+        let frames = visualize(TEST_INPUT1);
+        assert_eq!(frames.len(), 13);
+        assert_eq!(frames[0].to_string(), TEST_INPUT1.replace('.', " "));
+
+        // after one rotation, from the puzzle description
+        let expected = indoc::indoc! {"
+        .....#....
+        ....#...O#
+        ...OO##...
+        .OO#......
+        .....OOO#.
+        .O#...O#.#
+        ....O#....
+        ......OOOO
+        #...O###..
+        #..OO#....
+        "};
+        assert_eq!(frames[4].to_string(), expected.replace('.', " "));
+        assert_eq!(frames[4].cell((1, 8)).fg, Rgb::ORANGE);
+    }
+
+    /// Moves the rocks north one step at a time and then sums the loads
+    fn solve_part1_brute_force(input: &str) -> u64 {
+        let mut grid = input
+            .lines()
+            .map(|l| l.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for row in 1..grid.len() {
+                for col in 0..grid[row].len() {
+                    if grid[row][col] == 'O' && grid[row - 1][col] == '.' {
+                        grid[row - 1][col] = 'O';
+                        grid[row][col] = '.';
+                        moved = true;
+                    }
+                }
+            }
+        }
+
+        (1..)
+            .zip(grid.iter().rev())
+            .map(|(weight, row)| weight * row.iter().filter(|&&c| c == 'O').count() as u64)
+            .sum()
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_part1_against_brute_force(
+            input in (1..12usize, 1..12usize).prop_flat_map(|(rows, cols)| {
+                prop::collection::vec(
+                    prop::collection::vec(prop::sample::select(vec!['O', '#', '.']), cols),
+                    rows,
+                )
+            })
+            .prop_map(|grid| {
+                grid.into_iter()
+                    .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+                    .collect::<String>()
+            })
+        ) {
+            prop_assert_eq!(solve_part1(&input), Ok(solve_part1_brute_force(&input)));
+        }
+
+        #[test]
+        fn test_triag_number(n in 0..10_000u64) {
+            prop_assert_eq!(math::triangular(n), (1..=n).sum::<u64>());
+        }
     }
 }
 
@@ -306,12 +471,12 @@ mod benches {
     #[divan::bench]
     fn part1() {
         let answer = solve_part1(black_box(INPUT));
-        assert_eq!(answer, 108144);
+        assert_eq!(answer, Ok(108144));
     }
 
     #[divan::bench]
     fn part2() {
         let answer = solve_part2(black_box(INPUT));
-        assert_eq!(answer, 108404);
+        assert_eq!(answer, Ok(108404));
     }
 }
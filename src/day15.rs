@@ -1,10 +1,14 @@
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 15;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day15.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
     let answer_part1 = solve_part1(input);
     println!("day{DAY}::part1 answer: {}", answer_part1);
@@ -13,11 +17,11 @@ fn solve(input: &str) {
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u64 {
+pub(crate) fn solve_part1(input: &str) -> u64 {
     input.split(',').map(|s| hash(s) as u64).sum()
 }
 
-fn solve_part2(input: &str) -> u64 {
+pub(crate) fn solve_part2(input: &str) -> u64 {
     let mut map = HashMap::new();
 
     for op in input.split(',') {
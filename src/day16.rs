@@ -1,12 +1,19 @@
 use std::collections::VecDeque;
 
+use crate::grid::{CursorMut, Direction, DirectionSet, Grid, Pos};
+use crate::visualize::{self, Canvas, Cell, Rgb};
+
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 16;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day16.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
     let answer_part1 = solve_part1(input);
     println!("day{DAY}::part1 answer: {}", answer_part1);
@@ -15,27 +22,25 @@ fn solve(input: &str) {
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-type Pos = (usize, usize);
-
-fn solve_part1(input: &str) -> u64 {
+pub(crate) fn solve_part1(input: &str) -> u64 {
     let matrix = parse(input);
     solve_core(matrix, Direction::Right, (0, 0))
 }
 
-fn solve_part2(input: &str) -> u64 {
+pub(crate) fn solve_part2(input: &str) -> u64 {
     let matrix = parse(input);
 
-    assert_eq!(matrix.rows, matrix.cols);
+    assert_eq!(matrix.rows(), matrix.cols());
 
     // go through all the starting positions
     let mut max = 0;
-    for i in 0..matrix.rows {
+    for i in 0..matrix.rows() {
         // start from first column
         let result = solve_core(matrix.clone(), Direction::Right, (i, 0));
         max = max.max(result);
 
         // start from last column
-        let result = solve_core(matrix.clone(), Direction::Left, (i, matrix.rows - 1));
+        let result = solve_core(matrix.clone(), Direction::Left, (i, matrix.rows() - 1));
         max = max.max(result);
 
         // start from first row
@@ -43,7 +48,7 @@ fn solve_part2(input: &str) -> u64 {
         max = max.max(result);
 
         // start from last row
-        let result = solve_core(matrix.clone(), Direction::Up, (matrix.rows - 1, i));
+        let result = solve_core(matrix.clone(), Direction::Up, (matrix.rows() - 1, i));
         max = max.max(result);
 
         //dbg!(i, max);
@@ -73,7 +78,19 @@ impl<T> Queue<T> {
 }
 
 // Assumes that start_dir and start_pos combo is valid
-fn solve_core(mut matrix: Matrix<Tile>, start_dir: Direction, start_pos: Pos) -> u64 {
+fn solve_core(mut matrix: Grid<Tile>, start_dir: Direction, start_pos: Pos) -> u64 {
+    energize(&mut matrix, start_dir, start_pos, |_| {});
+    matrix.iter().filter(|t| t.is_energized()).count() as u64
+}
+
+/// Follows the light path and marks the directions in which it leaves the tiles, calls `visit` with
+/// every tile the light passes in the order it goes through them
+fn energize(
+    matrix: &mut Grid<Tile>,
+    start_dir: Direction,
+    start_pos: Pos,
+    mut visit: impl FnMut(Pos),
+) {
     // General idea here is to loop through the light path
     // and for every visited tile mark in which direction we moved from it.
     // `matrix` contain the token type and the seen directions.
@@ -81,14 +98,15 @@ fn solve_core(mut matrix: Matrix<Tile>, start_dir: Direction, start_pos: Pos) ->
     // Light path ends if it exits the grid or maps onto a loop.
     // A loop starts if we find a tile from which we already moved in that same direction.
 
-    let mut cursor = MatrixCursor::new(&mut matrix, start_pos);
+    let mut cursor = matrix.cursor_mut(start_pos);
     // Store the splits in the light path that we cannot follow right away and come back to them
     let mut splits_queue = Queue::new();
 
     // Match the first position and get the first step direction
     let (next_dir, split) = get_next_step(&mut cursor, start_dir).unwrap();
-    cursor.get_current_mut().add_moved_direction(next_dir);
-    splits_queue.push((next_dir, cursor.get_current_pos()));
+    cursor.current_mut().add_moved_direction(next_dir);
+    visit(cursor.pos());
+    splits_queue.push((next_dir, cursor.pos()));
     if let Some(split) = split {
         splits_queue.push(split);
     }
@@ -98,12 +116,9 @@ fn solve_core(mut matrix: Matrix<Tile>, start_dir: Direction, start_pos: Pos) ->
         cursor.set_pos(pos);
         cursor.step(dir);
 
-        loop {
-            //dbg!(prev_direction);
-            let Some((next_dir, split)) = get_next_step(&mut cursor, prev_direction) else {
-                break;
-            };
-            cursor.get_current_mut().add_moved_direction(next_dir);
+        while let Some((next_dir, split)) = get_next_step(&mut cursor, prev_direction) {
+            cursor.current_mut().add_moved_direction(next_dir);
+            visit(cursor.pos());
 
             if let Some(split) = split {
                 splits_queue.push(split);
@@ -116,59 +131,54 @@ fn solve_core(mut matrix: Matrix<Tile>, start_dir: Direction, start_pos: Pos) ->
             prev_direction = next_dir;
         }
     }
+}
 
-    matrix.data.into_iter().filter(Tile::is_energized).count() as u64
+/// Frames of the light of part 1 spreading, the energized tiles are highlighted
+pub fn visualize(input: &str) -> Vec<Canvas> {
+    let mut matrix = parse(input);
+    let mut visited = Vec::new();
+    energize(&mut matrix, Direction::Right, (0, 0), |pos| {
+        visited.push(pos)
+    });
+
+    let canvas = Canvas::from_grid(&matrix, |tile| match tile.token {
+        Token::Empty => Cell::new(' '),
+        token => Cell::new(token.to_string().chars().next().unwrap()).fg(Rgb::BLUE),
+    });
+    visualize::evenly(visited.len(), 100)
+        .map(|len| {
+            let mut frame = canvas.clone();
+            frame.highlight(visited[..len].iter().copied(), Rgb::YELLOW);
+            frame.highlight([visited[len - 1]], Rgb::RED);
+            frame
+        })
+        .collect()
 }
 
 /// Get the next step direction and the next split position
 fn get_next_step(
-    cursor: &mut MatrixCursor<'_, Tile>,
+    cursor: &mut CursorMut<'_, Tile>,
     prev_direction: Direction,
 ) -> Option<(Direction, Option<(Direction, Pos)>)> {
     let mut next_dir = prev_direction;
     let mut split = None;
-    let current_token = &cursor.get_current_mut().token;
 
-    match current_token {
-        Token::LeftMirror if prev_direction.contains(Direction::Up) => {
+    match (cursor.current().token, prev_direction) {
+        (Token::LeftMirror, Direction::Up) => next_dir = Direction::Left,
+        (Token::RightMirror, Direction::Up) => next_dir = Direction::Right,
+        (Token::LeftMirror, Direction::Down) => next_dir = Direction::Right,
+        (Token::RightMirror, Direction::Down) => next_dir = Direction::Left,
+        (Token::HorizontalSplitter, Direction::Up | Direction::Down) => {
+            split = Some((Direction::Right, cursor.pos()));
             next_dir = Direction::Left;
         }
-        Token::RightMirror if prev_direction.contains(Direction::Up) => {
-            next_dir = Direction::Right;
-        }
-
-        Token::LeftMirror if prev_direction.contains(Direction::Down) => {
-            next_dir = Direction::Right;
-        }
-        Token::RightMirror if prev_direction.contains(Direction::Down) => {
-            next_dir = Direction::Left;
-        }
-        Token::HorizontalSplitter
-            if prev_direction.contains(Direction::Down)
-                || prev_direction.contains(Direction::Up) =>
-        {
-            split = Some((Direction::Right, cursor.get_current_pos()));
-            next_dir = Direction::Left;
-        }
-
-        Token::LeftMirror if prev_direction.contains(Direction::Left) => {
-            next_dir = Direction::Up;
-        }
-        Token::RightMirror if prev_direction.contains(Direction::Left) => {
-            next_dir = Direction::Down
-        }
-        Token::LeftMirror if prev_direction.contains(Direction::Right) => {
-            next_dir = Direction::Down;
-        }
-        Token::RightMirror if prev_direction.contains(Direction::Right) => {
-            next_dir = Direction::Up;
-        }
 
-        Token::VerticalSplitter
-            if prev_direction.contains(Direction::Right)
-                || prev_direction.contains(Direction::Left) =>
-        {
-            split = Some((Direction::Up, cursor.get_current_pos()));
+        (Token::LeftMirror, Direction::Left) => next_dir = Direction::Up,
+        (Token::RightMirror, Direction::Left) => next_dir = Direction::Down,
+        (Token::LeftMirror, Direction::Right) => next_dir = Direction::Down,
+        (Token::RightMirror, Direction::Right) => next_dir = Direction::Up,
+        (Token::VerticalSplitter, Direction::Left | Direction::Right) => {
+            split = Some((Direction::Up, cursor.pos()));
             next_dir = Direction::Down;
         }
         _ => {
@@ -176,8 +186,7 @@ fn get_next_step(
         }
     }
 
-    let tile = cursor.get_current_mut();
-    if tile.moved_directions.contains(next_dir) {
+    if cursor.current().moved_directions.contains(next_dir) {
         // We have reached a loop, since we have already moved from this tile in the same direction
         return None;
     }
@@ -188,7 +197,7 @@ fn get_next_step(
 #[derive(Debug, Clone, Copy)]
 struct Tile {
     token: Token,
-    moved_directions: Direction,
+    moved_directions: DirectionSet,
 }
 
 impl Tile {
@@ -197,7 +206,7 @@ impl Tile {
     }
 
     fn add_moved_direction(&mut self, direction: Direction) {
-        self.moved_directions.insert(direction)
+        self.moved_directions.insert(direction);
     }
 }
 
@@ -210,18 +219,6 @@ enum Token {
     RightMirror,
 }
 
-use bitflags::bitflags;
-
-bitflags! {
-    #[derive(Debug, Clone, Copy)]
-    pub struct Direction: u8 {
-        const Up = 1 << 0;
-        const Down = 1 << 1;
-        const Left = 1 << 2;
-        const Right = 1 << 3;
-    }
-}
-
 impl std::fmt::Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -234,151 +231,22 @@ impl std::fmt::Display for Token {
     }
 }
 
-#[derive(Debug, Clone)]
-struct Matrix<T> {
-    data: Vec<T>,
-    rows: usize,
-    cols: usize,
-}
-
-impl<T> std::fmt::Display for Matrix<T>
-where
-    T: std::fmt::Display,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                write!(f, "{}", self.data[row * self.cols + col])?;
-            }
-            writeln!(f)?;
-        }
-
-        Ok(())
-    }
-}
-
-struct MatrixCursor<'a, T> {
-    matrix: &'a mut Matrix<T>,
-    row: usize,
-    col: usize,
-}
-
-impl<'a, T> MatrixCursor<'a, T> {
-    fn new(matrix: &'a mut Matrix<T>, pos: Pos) -> Self {
-        Self {
-            matrix,
-            row: pos.0,
-            col: pos.1,
-        }
-    }
-
-    fn get_current(&self) -> &T {
-        &self.matrix.data[self.row * self.matrix.cols + self.col]
-    }
-
-    fn get_current_mut(&mut self) -> &mut T {
-        &mut self.matrix.data[self.row * self.matrix.cols + self.col]
-    }
-
-    fn set_current(&mut self, value: T) {
-        *self.get_current_mut() = value
-    }
-
-    fn get_current_pos(&self) -> Pos {
-        (self.row, self.col)
-    }
-
-    fn set_pos(&mut self, pos: Pos) -> bool {
-        if pos.0 < self.matrix.rows && pos.1 < self.matrix.cols {
-            self.row = pos.0;
-            self.col = pos.1;
-            return true;
-        }
-
-        false
-    }
-
-    fn step_up(&mut self) -> bool {
-        if self.row > 0 {
-            self.row -= 1;
-            return true;
-        }
-
-        false
-    }
-
-    fn step_down(&mut self) -> bool {
-        if self.row < self.matrix.rows - 1 {
-            self.row += 1;
-            return true;
-        }
-
-        false
-    }
-
-    fn step_left(&mut self) -> bool {
-        if self.col > 0 {
-            self.col -= 1;
-            return true;
-        }
-
-        false
-    }
-
-    fn step_right(&mut self) -> bool {
-        if self.col < self.matrix.cols - 1 {
-            self.col += 1;
-            return true;
-        }
-
-        false
-    }
-
-    fn step(&mut self, direction: Direction) -> bool {
-        if direction.contains(Direction::Up) {
-            self.step_up()
-        } else if direction.contains(Direction::Down) {
-            self.step_down()
-        } else if direction.contains(Direction::Left) {
-            self.step_left()
-        } else if direction.contains(Direction::Right) {
-            self.step_right()
-        } else {
-            false
-        }
-    }
-}
-
-fn parse(input: &str) -> Matrix<Tile> {
-    let num_cols = input.lines().next().unwrap().len();
-
-    let mut data = Vec::new();
-    let mut num_rows = 0;
-    for l in input.lines() {
-        num_rows += 1;
-
-        for c in l.chars() {
-            let token = match c {
-                '|' => Token::VerticalSplitter,
-                '-' => Token::HorizontalSplitter,
-                '/' => Token::RightMirror,
-                '\\' => Token::LeftMirror,
-                '.' => Token::Empty,
-                _ => unreachable!(),
-            };
-
-            data.push(Tile {
-                token,
-                moved_directions: Direction::empty(),
-            });
+fn parse(input: &str) -> Grid<Tile> {
+    Grid::parse(input, |c| {
+        let token = match c {
+            '|' => Token::VerticalSplitter,
+            '-' => Token::HorizontalSplitter,
+            '/' => Token::RightMirror,
+            '\\' => Token::LeftMirror,
+            '.' => Token::Empty,
+            _ => unreachable!(),
+        };
+
+        Tile {
+            token,
+            moved_directions: DirectionSet::empty(),
         }
-    }
-
-    Matrix {
-        data,
-        rows: num_rows,
-        cols: num_cols,
-    }
+    })
 }
 
 #[cfg(test)]
@@ -409,6 +277,20 @@ mod tests {
         let answer = solve_part2(TEST_INPUT1);
         assert_eq!(answer, 51);
     }
+
+    #[test]
+    fn test_visualize() {
+        let frames = visualize(TEST_INPUT1);
+        let last = frames.last().unwrap();
+        let energized = (0..last.rows())
+            .flat_map(|row| (0..last.cols()).map(move |col| (row, col)))
+            .filter(|&pos| last.cell(pos).bg.is_some())
+            .count();
+        assert_eq!(energized, 46);
+        assert_eq!(last.cell((0, 1)).ch, '|');
+        assert_eq!(last.cell((0, 0)).bg, Some(Rgb::YELLOW));
+        assert!(frames.len() <= 100);
+    }
 }
 
 #[cfg(feature = "divan")]
@@ -1,45 +1,96 @@
+use crate::graph::Graph;
+use crate::intern::{Id, Interner};
+use crate::interval::Cuboid;
+use crate::parse;
+use std::borrow::Borrow;
 use std::collections::HashMap;
-use std::ops::Range;
+use std::hash::Hash;
 
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 19;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day19.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
-    let answer_part1 = solve_part1(input);
+    let answer_part1 = solve_part1(input).unwrap();
     println!("day{DAY}::part1 answer: {}", answer_part1);
 
-    let answer_part2 = solve_part2(input);
+    let answer_part2 = solve_part2(input).unwrap();
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u64 {
-    let (workflows, parts) = parse(input);
+pub(crate) fn solve_part1(input: &str) -> parse::Result<u64> {
+    let workflows = Workflows::parse(input)?;
+    let (_, parts) = parse::split_once(input, "\n\n")?;
 
-    parts
-        .filter(|p| is_part_accepted(&workflows, p))
-        .map(|p| p.sum_categories())
-        .sum()
+    let mut sum = 0;
+    for part in parse_parts(parts) {
+        let part = part?;
+        if workflows.is_accepted(&part) {
+            sum += part.sum_categories();
+        }
+    }
+    Ok(sum)
+}
+
+/// Same as [`solve_part1`] but the rules are closures and the workflows are looked up by their names in a `HashMap`
+pub(crate) fn solve_part1_hashmap(input: &str) -> parse::Result<u64> {
+    let (workflows, parts) = parse(input)?;
+
+    let mut sum = 0;
+    for part in parts {
+        let part = part?;
+        if is_part_accepted(&workflows, &part)? {
+            sum += part.sum_categories();
+        }
+    }
+    Ok(sum)
 }
 
-fn is_part_accepted(workflows: &HashMap<&str, Workflow>, part: &Part) -> bool {
+fn is_part_accepted(workflows: &HashMap<&str, Workflow>, part: &Part) -> parse::Result<bool> {
     let mut workflow = String::from("in");
 
-    loop {
-        let result = workflows[workflow.as_str()].eval(part);
-        match result {
-            RuleResult::Accept => return true,
-            RuleResult::Reject => return false,
+    // without a cycle the part can't go through more workflows than there are
+    for _ in 0..=workflows.len() {
+        let (_, rules) = get_workflow(workflows, &workflow)?;
+        match rules.eval(part) {
+            RuleResult::Accept => return Ok(true),
+            RuleResult::Reject => return Ok(false),
             RuleResult::NextWorkflow(name) => workflow = name,
             RuleResult::NextRule => unreachable!(),
         }
     }
+
+    Err(cycle_error(&workflow))
+}
+
+/// Looks up a workflow by its name, the rules can refer to workflows which aren't defined
+fn get_workflow<'a, K, W>(workflows: &'a HashMap<K, W>, name: &str) -> parse::Result<(&'a K, &'a W)>
+where
+    K: Borrow<str> + Hash + Eq,
+{
+    workflows
+        .get_key_value(name)
+        .ok_or_else(|| parse::ParseError::InvalidValue {
+            expected: "workflow",
+            input: name.to_string(),
+        })
 }
 
-fn solve_part2(input: &str) -> u64 {
+fn cycle_error(name: &str) -> parse::ParseError {
+    parse::ParseError::InvalidValue {
+        expected: "non-cyclic workflow",
+        input: name.to_string(),
+    }
+}
+
+pub(crate) fn solve_part2(input: &str) -> parse::Result<u64> {
     // The idea here is to use a range based part (RangePart), which at start
     // includes all possibilities. We run it through the workflows and keep reducing
     // the accepted category ranges. For rules which split the range in two (for example a<1500)
@@ -47,14 +98,30 @@ fn solve_part2(input: &str) -> u64 {
     // If one part reaches the accepted result, we calculate the number of combinations
     // that part includes and add it to the total.
 
-    let workflows = parse2(input);
-    let part = RangePart::new(1..4001);
-    part2_core(&workflows, "in", part)
+    let workflows = Workflows::parse(input)?;
+    Ok(workflows.count_accepted(workflows.start, RangePart::cube(1..4001)))
+}
+
+/// Same as [`solve_part2`] but the workflows are looked up by their names in a `HashMap`
+pub(crate) fn solve_part2_hashmap(input: &str) -> parse::Result<u64> {
+    let workflows = parse2(input)?;
+    let part = RangePart::cube(1..4001);
+    part2_core(&workflows, "in", part, 0)
 }
 
-fn part2_core(workflows: &HashMap<&str, Vec<RuleDef>>, workflow: &str, mut part: RangePart) -> u64 {
+/// `depth` is the number of workflows the part went through before `workflow`
+fn part2_core(
+    workflows: &HashMap<&str, Vec<RuleDef>>,
+    workflow: &str,
+    mut part: RangePart,
+    depth: usize,
+) -> parse::Result<u64> {
+    if depth > workflows.len() {
+        return Err(cycle_error(workflow));
+    }
+
     let mut out = 0;
-    let rules = &workflows[workflow];
+    let (_, rules) = get_workflow(workflows, workflow)?;
 
     for rule in rules {
         let result = split_part_by_rule(rule, part.clone());
@@ -62,13 +129,13 @@ fn part2_core(workflows: &HashMap<&str, Vec<RuleDef>>, workflow: &str, mut part:
         if let Some(accepted) = result.accepted {
             match accepted.1 {
                 RuleResult::Accept => {
-                    out += accepted.0.num_combinations();
+                    out += accepted.0.volume();
                 }
                 RuleResult::Reject => {
                     // nothing to do
                 }
                 RuleResult::NextWorkflow(wf) => {
-                    out += part2_core(workflows, wf.as_str(), accepted.0);
+                    out += part2_core(workflows, wf.as_str(), accepted.0, depth + 1)?;
                 }
                 RuleResult::NextRule => {
                     // this is the rejected part, it's not possible for accepted
@@ -84,7 +151,7 @@ fn part2_core(workflows: &HashMap<&str, Vec<RuleDef>>, workflow: &str, mut part:
         }
     }
 
-    out
+    Ok(out)
 }
 
 #[derive(Debug, Clone)]
@@ -94,63 +161,179 @@ struct RuleSplitResult {
 }
 
 fn split_part_by_rule(rule: &RuleDef, part: RangePart) -> RuleSplitResult {
-    let Some(condition) = &rule.condition else {
+    let (accepted, rejected) = split_part_by_condition(rule.condition.as_ref(), part);
+    RuleSplitResult {
+        accepted: accepted.map(|accepted| (accepted, rule.result.clone())),
+        rejected,
+    }
+}
+
+/// Splits the part into the ranges that meet the condition and those that don't
+fn split_part_by_condition(
+    condition: Option<&Condition>,
+    part: RangePart,
+) -> (Option<RangePart>, Option<RangePart>) {
+    let Some(condition) = condition else {
         // if no condition, then we accept all
-        return RuleSplitResult {
-            accepted: Some((part, rule.result.clone())),
-            rejected: None,
-        };
+        return (Some(part), None);
     };
 
-    macro_rules! split_rule {
-        (@createResult $cat:ident, $accepted:ident, $rejected:ident) => {
-            RuleSplitResult {
-                accepted: $accepted.map(|accepted| {
-                    (
-                        RangePart {
-                            $cat: accepted,
-                            ..part.clone()
-                        },
-                        rule.result.clone(),
-                    )
-                }),
-                rejected: $rejected.map(|rejected| RangePart {
-                    $cat: rejected,
-                    ..part
-                }),
+    let axis = condition.category as usize;
+    match condition.op {
+        Op::Gt => {
+            // a>1500, accept 1501.., reject ..1501
+            let (rejected, accepted) = part.split(axis, condition.value + 1);
+            (accepted, rejected)
+        }
+        // a<1500, accept ..1500, reject 1500..
+        Op::Lt => part.split(axis, condition.value),
+    }
+}
+
+/// Where a rule sends the part, the workflows are referred to by their interned ids
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Accept,
+    Reject,
+    Workflow(u16),
+}
+
+/// The workflows indexed by the interned ids of their names
+pub struct Workflows {
+    rules: Vec<Vec<(Option<Condition>, Target)>>,
+    /// Id of the `in` workflow
+    start: u16,
+}
+
+impl Workflows {
+    /// Parses the workflows block of `input`, every workflow which is referred to must exist
+    pub fn parse(input: &str) -> parse::Result<Self> {
+        let mut names = Interner::<u16>::new();
+        let mut rules = Vec::new();
+
+        for line in workflows_block(input)?.lines() {
+            let (name, workflow) = parse::braced(line)?;
+            let id = names.intern(name).index();
+
+            let mut workflow_rules = Vec::new();
+            for rule in workflow.split(',') {
+                let RuleDef { condition, .. } = parse_rule2(rule)?;
+                let target = match split_rule(rule).1 {
+                    "A" => Target::Accept,
+                    "R" => Target::Reject,
+                    next => Target::Workflow(names.intern(next)),
+                };
+                workflow_rules.push((condition, target));
+            }
+
+            if rules.len() <= id {
+                rules.resize_with(id + 1, || None);
             }
+            rules[id] = Some(workflow_rules);
+        }
+
+        rules.resize_with(names.len(), || None);
+        let unknown = |name: &str| parse::ParseError::InvalidValue {
+            expected: "workflow",
+            input: name.to_string(),
         };
-        ($cat:ident, Op::Gt) => {{
-            // a>1500, accept 1501.., reject ..1501
-            let (rejected, accepted) = split_range(&part.$cat, condition.value + 1);
-            split_rule!(@createResult $cat, accepted, rejected)
-        }};
-        ($cat:ident, Op::Lt) => {{
-            // a<1500, accept ..1500, reject 1500..
-            let (accepted, rejected) = split_range(&part.$cat, condition.value);
-            split_rule!(@createResult $cat, accepted, rejected)
-        }};
+        let workflows = Workflows {
+            rules: (0..)
+                .zip(rules)
+                .map(|(id, rules)| rules.ok_or_else(|| unknown(names.name(id))))
+                .collect::<parse::Result<_>>()?,
+            start: names.get("in").ok_or_else(|| unknown("in"))?,
+        };
+
+        // a part could be sent around a cycle forever
+        if let Some(id) = workflows.find_cycle() {
+            return Err(cycle_error(names.name(id)));
+        }
+
+        Ok(workflows)
+    }
+
+    /// A workflow on a cycle, if there is one
+    fn find_cycle(&self) -> Option<u16> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            New,
+            OnPath,
+            Done,
+        }
+
+        let mut states = vec![State::New; self.rules.len()];
+        for root in 0..self.rules.len() as u16 {
+            if states[root as usize] != State::New {
+                continue;
+            }
+
+            // the path from `root`, with the index of the next rule to follow for each workflow
+            let mut path = vec![(root, 0)];
+            states[root as usize] = State::OnPath;
+            while let Some((workflow, rule)) = path.last_mut() {
+                let Some((_, target)) = self.rules[*workflow as usize].get(*rule) else {
+                    states[*workflow as usize] = State::Done;
+                    path.pop();
+                    continue;
+                };
+                *rule += 1;
+
+                if let Target::Workflow(next) = *target {
+                    match states[next as usize] {
+                        State::New => {
+                            states[next as usize] = State::OnPath;
+                            path.push((next, 0));
+                        }
+                        State::OnPath => return Some(next),
+                        State::Done => {}
+                    }
+                }
+            }
+        }
+
+        None
     }
 
-    match (&condition.category, &condition.op) {
-        (Category::X, Op::Gt) => split_rule!(x, Op::Gt),
-        (Category::X, Op::Lt) => split_rule!(x, Op::Lt),
-        (Category::M, Op::Gt) => split_rule!(m, Op::Gt),
-        (Category::M, Op::Lt) => split_rule!(m, Op::Lt),
-        (Category::A, Op::Gt) => split_rule!(a, Op::Gt),
-        (Category::A, Op::Lt) => split_rule!(a, Op::Lt),
-        (Category::S, Op::Gt) => split_rule!(s, Op::Gt),
-        (Category::S, Op::Lt) => split_rule!(s, Op::Lt),
+    /// Whether `part` is accepted when starting from the `in` workflow
+    pub fn is_accepted(&self, part: &Part) -> bool {
+        let mut workflow = self.start;
+
+        loop {
+            let (_, target) = self.rules[workflow as usize]
+                .iter()
+                .find(|(condition, _)| condition.as_ref().is_none_or(|c| c.is_met(part)))
+                .unwrap();
+            match *target {
+                Target::Accept => return true,
+                Target::Reject => return false,
+                Target::Workflow(next) => workflow = next,
+            }
+        }
     }
-}
 
-fn split_range(range: &Range<u16>, index: u16) -> (Option<Range<u16>>, Option<Range<u16>>) {
-    if index < range.start {
-        (None, Some(range.clone()))
-    } else if index >= range.end {
-        (Some(range.clone()), None)
-    } else {
-        (Some(range.start..index), Some(index..range.end))
+    /// Number of combinations in `part` which are accepted when starting from `workflow`
+    fn count_accepted(&self, workflow: u16, mut part: RangePart) -> u64 {
+        let mut out = 0;
+
+        for (condition, target) in &self.rules[workflow as usize] {
+            let (accepted, rejected) = split_part_by_condition(condition.as_ref(), part);
+
+            match (accepted, *target) {
+                (Some(accepted), Target::Accept) => out += accepted.volume(),
+                (Some(accepted), Target::Workflow(next)) => {
+                    out += self.count_accepted(next, accepted)
+                }
+                _ => {}
+            }
+
+            match rejected {
+                Some(rejected) => part = rejected,
+                None => break,
+            }
+        }
+
+        out
     }
 }
 
@@ -182,11 +365,11 @@ enum RuleResult {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Part {
-    x: u16,
-    m: u16,
-    a: u16,
-    s: u16,
+pub struct Part {
+    pub x: u16,
+    pub m: u16,
+    pub a: u16,
+    pub s: u16,
 }
 
 impl Part {
@@ -195,179 +378,235 @@ impl Part {
     }
 }
 
-#[derive(Debug, Clone)]
-struct RangePart {
-    x: Range<u16>,
-    m: Range<u16>,
-    a: Range<u16>,
-    s: Range<u16>,
-}
-
-impl RangePart {
-    fn new(range: Range<u16>) -> Self {
-        Self {
-            x: range.clone(),
-            m: range.clone(),
-            a: range.clone(),
-            s: range,
-        }
-    }
-
-    fn num_combinations(&self) -> u64 {
-        fn range_len(range: &Range<u16>) -> u64 {
-            (range.end - range.start) as u64
-        }
+/// Ranges of the x, m, a and s categories, in that order
+type RangePart = Cuboid<u16, 4>;
 
-        range_len(&self.x) * range_len(&self.m) * range_len(&self.a) * range_len(&self.s)
-    }
-}
+fn parse(
+    input: &str,
+) -> parse::Result<(
+    HashMap<&str, Workflow>,
+    impl Iterator<Item = parse::Result<Part>> + '_,
+)> {
+    let (workflows_input, parts_input) = parse::split_once(input, "\n\n")?;
 
-fn parse(input: &str) -> (HashMap<&str, Workflow>, impl Iterator<Item = Part> + '_) {
     let mut workflows = HashMap::new();
-
-    let (workflows_input, parts_input) = input.split_once("\n\n").unwrap();
-
     for line in workflows_input.lines() {
-        let (name, workflow) = line.split_once('{').unwrap();
-        let workflow = workflow.trim_end_matches('}');
-        let rules = workflow.split(',').map(parse_rule).collect::<Vec<_>>();
+        let (name, workflow) = parse::braced(line)?;
+        let rules = parse::separated(workflow, ",", parse_rule).collect::<parse::Result<_>>()?;
         workflows.insert(name, Workflow { rules });
     }
 
-    (workflows, parse_parts(parts_input))
+    Ok((workflows, parse_parts(parts_input)))
 }
 
-fn parse2(input: &str) -> HashMap<&str, Vec<RuleDef>> {
+pub fn parse2(input: &str) -> parse::Result<HashMap<&str, Vec<RuleDef>>> {
     let mut workflows = HashMap::new();
+    for line in workflows_block(input)?.lines() {
+        let (name, workflow) = parse::braced(line)?;
+        let rules = parse::separated(workflow, ",", parse_rule2).collect::<parse::Result<_>>()?;
+        workflows.insert(name, rules);
+    }
 
-    let (workflows_input, _) = input.split_once("\n\n").unwrap();
+    Ok(workflows)
+}
 
-    for line in workflows_input.lines() {
-        let (name, workflow) = line.split_once('{').unwrap();
-        let workflow = workflow.trim_end_matches('}');
-        let rules = workflow.split(',').map(parse_rule2).collect::<Vec<_>>();
-        workflows.insert(name, rules);
+/// The workflows and the `A` and `R` results as nodes, with an edge for every rule labelled with its condition
+pub fn graph(input: &str) -> parse::Result<Graph<&str, Option<&str>>> {
+    let mut graph = Graph::new();
+    for line in workflows_block(input)?.lines() {
+        let (name, workflow) = parse::braced(line)?;
+        graph.add_node(name);
+        for rule in workflow.split(',') {
+            parse_rule2(rule)?;
+            let (condition, result) = split_rule(rule);
+            graph.add_edge(name, result, condition);
+        }
     }
 
-    workflows
+    Ok(graph)
 }
-fn parse_parts(input: &str) -> impl Iterator<Item = Part> + '_ {
-    input.lines().map(|line| {
-        let line = line.trim_start_matches('{').trim_end_matches('}');
 
-        let mut parts_line = line.split(',');
-        let x = parts_line
-            .next()
-            .unwrap()
-            .trim_start_matches("x=")
-            .parse()
-            .unwrap();
-        let m = parts_line
-            .next()
-            .unwrap()
-            .trim_start_matches("m=")
-            .parse()
-            .unwrap();
-        let a = parts_line
-            .next()
-            .unwrap()
-            .trim_start_matches("a=")
-            .parse()
-            .unwrap();
-        let s = parts_line
-            .next()
-            .unwrap()
-            .trim_start_matches("s=")
-            .parse()
-            .unwrap();
+/// The first block of the input, which has the workflows
+fn workflows_block(input: &str) -> parse::Result<&str> {
+    parse::blocks(input)
+        .next()
+        .ok_or(parse::ParseError::UnexpectedEnd {
+            expected: "workflows",
+        })
+}
+
+/// Splits a rule into its condition, if it has one, and the result
+fn split_rule(rule: &str) -> (Option<&str>, &str) {
+    match rule.split_once(':') {
+        Some((condition, result)) => (Some(condition), result),
+        None => (None, rule),
+    }
+}
 
-        Part { x, m, a, s }
+/// Parses the `{x=787,m=2655,a=1222,s=2876}` lines
+pub fn parse_parts(input: &str) -> impl Iterator<Item = parse::Result<Part>> + '_ {
+    input.lines().map(|line| {
+        let (_, categories) = parse::braced(line)?;
+        let [x, m, a, s] = parse::exactly(parse::separated(categories, ",", Ok))?;
+        let category = |s, name| parse::number(parse::prefix(s, name)?);
+
+        Ok(Part {
+            x: category(x, "x=")?,
+            m: category(m, "m=")?,
+            a: category(a, "a=")?,
+            s: category(s, "s=")?,
+        })
     })
 }
 
-fn parse_rule(input: &str) -> Box<Rule> {
-    if let Some((condition, result)) = input.split_once(':') {
-        let category = &condition[..1];
-        let op = &condition[1..2];
-        let value = condition[2..].parse::<u16>().unwrap();
+fn parse_rule(input: &str) -> parse::Result<Box<Rule>> {
+    let RuleDef { condition, result } = parse_rule2(input)?;
 
-        let result = match result {
-            "A" => RuleResult::Accept,
-            "R" => RuleResult::Reject,
-            s => RuleResult::NextWorkflow(s.into()),
+    let Some(Condition {
+        category,
+        op,
+        value,
+    }) = condition
+    else {
+        return Ok(Box::new(move |_: &Part| -> RuleResult { result.clone() }));
+    };
+
+    macro_rules! rule {
+        ($cat:ident, $op:tt) => {
+            Box::new(move |p: &Part| -> RuleResult {
+                if p.$cat $op value {
+                    result.clone()
+                } else {
+                    RuleResult::NextRule
+                }
+            })
         };
+    }
 
-        macro_rules! rule {
-            ($cat:ident, $op:tt) => {
-                Box::new(move |p: &Part| -> RuleResult {
-                    if p.$cat $op value {
-                        result.clone()
-                    } else {
-                        RuleResult::NextRule
-                    }
-                })
-            };
-        }
+    Ok(match (category, op) {
+        (Category::X, Op::Gt) => rule!(x, >),
+        (Category::X, Op::Lt) => rule!(x, <),
+        (Category::M, Op::Gt) => rule!(m, >),
+        (Category::M, Op::Lt) => rule!(m, <),
+        (Category::A, Op::Gt) => rule!(a, >),
+        (Category::A, Op::Lt) => rule!(a, <),
+        (Category::S, Op::Gt) => rule!(s, >),
+        (Category::S, Op::Lt) => rule!(s, <),
+    })
+}
 
-        match (category, op) {
-            ("x", ">") => rule!(x, >),
-            ("x", "<") => rule!(x, <),
-            ("m", ">") => rule!(m, >),
-            ("m", "<") => rule!(m, <),
-            ("a", ">") => rule!(a, >),
-            ("a", "<") => rule!(a, <),
-            ("s", ">") => rule!(s, >),
-            ("s", "<") => rule!(s, <),
-            _ => todo!(),
-        }
-    } else {
-        let result = match input {
-            "A" => RuleResult::Accept,
-            "R" => RuleResult::Reject,
-            s => RuleResult::NextWorkflow(s.into()),
-        };
+fn parse_rule2(input: &str) -> parse::Result<RuleDef> {
+    let Some((condition, result)) = input.split_once(':') else {
+        return Ok(RuleDef {
+            condition: None,
+            result: parse_rule_result(input),
+        });
+    };
+
+    let op_index = condition.find(['<', '>']).unwrap_or(condition.len());
+    let (category, op_value) = condition.split_at(op_index);
+    let (op, value) = op_value.split_at(op_value.len().min(1));
+
+    Ok(RuleDef {
+        condition: Some(Condition {
+            category: Category::from_str(category)?,
+            op: Op::from_str(op)?,
+            value: parse::number(value)?,
+        }),
+        result: parse_rule_result(result),
+    })
+}
 
-        Box::new(move |_: &Part| -> RuleResult { result.clone() })
+fn parse_rule_result(s: &str) -> RuleResult {
+    match s {
+        "A" => RuleResult::Accept,
+        "R" => RuleResult::Reject,
+        s => RuleResult::NextWorkflow(s.into()),
     }
 }
 
-fn parse_rule2(input: &str) -> RuleDef {
-    if let Some((condition, result)) = input.split_once(':') {
-        let category = &condition[..1];
-        let op = &condition[1..2];
-        let value = condition[2..].parse::<u16>().unwrap();
+pub struct RuleDef {
+    condition: Option<Condition>,
+    result: RuleResult,
+}
 
-        let result = match result {
-            "A" => RuleResult::Accept,
-            "R" => RuleResult::Reject,
-            s => RuleResult::NextWorkflow(s.into()),
-        };
+impl RuleDef {
+    pub fn is_accept(&self) -> bool {
+        matches!(self.result, RuleResult::Accept)
+    }
+}
 
-        RuleDef {
-            condition: Some(Condition {
-                category: Category::from_str(category),
-                op: Op::from_str(op),
-                value,
-            }),
-            result,
+impl std::fmt::Display for RuleDef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(condition) = &self.condition {
+            let category = match condition.category {
+                Category::X => 'x',
+                Category::M => 'm',
+                Category::A => 'a',
+                Category::S => 's',
+            };
+            let op = match condition.op {
+                Op::Gt => '>',
+                Op::Lt => '<',
+            };
+            write!(f, "{category}{op}{}:", condition.value)?;
         }
-    } else {
-        let result = match input {
-            "A" => RuleResult::Accept,
-            "R" => RuleResult::Reject,
-            s => RuleResult::NextWorkflow(s.into()),
-        };
 
-        RuleDef {
-            condition: None,
-            result,
+        match &self.result {
+            RuleResult::Accept => write!(f, "A"),
+            RuleResult::Reject => write!(f, "R"),
+            RuleResult::NextWorkflow(name) => write!(f, "{name}"),
+            RuleResult::NextRule => unreachable!(),
         }
     }
 }
 
-struct RuleDef {
-    condition: Option<Condition>,
-    result: RuleResult,
+impl RuleDef {
+    /// Returns the result of this rule for `part` or `None` if the condition isn't met
+    fn eval(&self, part: &Part) -> Option<&RuleResult> {
+        let is_met = self.condition.as_ref().is_none_or(|c| c.is_met(part));
+        is_met.then_some(&self.result)
+    }
+}
+
+/// Same as [`is_part_accepted`] but for the workflows from [`parse2`]
+pub fn is_part_accepted_by_rules<K>(
+    workflows: &HashMap<K, Vec<RuleDef>>,
+    part: &Part,
+) -> parse::Result<bool>
+where
+    K: Borrow<str> + Hash + Eq,
+{
+    let trace = trace_part(workflows, part)?;
+    Ok(trace.last().is_some_and(|(_, rule)| rule.is_accept()))
+}
+
+/// Returns the workflows and the matched rules the `part` goes through
+///
+/// The last rule in the trace either accepts or rejects the part.
+pub fn trace_part<'a, K>(
+    workflows: &'a HashMap<K, Vec<RuleDef>>,
+    part: &Part,
+) -> parse::Result<Vec<(&'a str, &'a RuleDef)>>
+where
+    K: Borrow<str> + Hash + Eq,
+{
+    let mut trace = Vec::new();
+    let mut workflow = "in";
+
+    // without a cycle the part can't go through more workflows than there are
+    for _ in 0..=workflows.len() {
+        let (name, rules) = get_workflow(workflows, workflow)?;
+        let rule = rules.iter().find(|rule| rule.eval(part).is_some()).unwrap();
+        trace.push((name.borrow(), rule));
+
+        match &rule.result {
+            RuleResult::NextWorkflow(name) => workflow = name,
+            _ => return Ok(trace),
+        }
+    }
+
+    Err(cycle_error(workflow))
 }
 
 struct Condition {
@@ -376,6 +615,22 @@ struct Condition {
     value: u16,
 }
 
+impl Condition {
+    fn is_met(&self, part: &Part) -> bool {
+        let value = match self.category {
+            Category::X => part.x,
+            Category::M => part.m,
+            Category::A => part.a,
+            Category::S => part.s,
+        };
+        match self.op {
+            Op::Gt => value > self.value,
+            Op::Lt => value < self.value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 enum Category {
     X,
     M,
@@ -384,14 +639,19 @@ enum Category {
 }
 
 impl Category {
-    fn from_str(s: &str) -> Self {
-        match s {
+    fn from_str(s: &str) -> parse::Result<Self> {
+        Ok(match s {
             "x" => Category::X,
             "m" => Category::M,
             "a" => Category::A,
             "s" => Category::S,
-            _ => unreachable!(),
-        }
+            _ => {
+                return Err(parse::ParseError::InvalidValue {
+                    expected: "category",
+                    input: s.to_string(),
+                })
+            }
+        })
     }
 }
 
@@ -401,12 +661,17 @@ enum Op {
 }
 
 impl Op {
-    fn from_str(s: &str) -> Self {
-        match s {
+    fn from_str(s: &str) -> parse::Result<Self> {
+        Ok(match s {
             ">" => Op::Gt,
             "<" => Op::Lt,
-            _ => unreachable!(),
-        }
+            _ => {
+                return Err(parse::ParseError::InvalidValue {
+                    expected: "operator",
+                    input: s.to_string(),
+                })
+            }
+        })
     }
 }
 
@@ -437,18 +702,129 @@ mod tests {
     #[test]
     fn test_part1() {
         let answer = solve_part1(TEST_INPUT1);
-        assert_eq!(answer, 19114);
+        assert_eq!(answer, Ok(19114));
+
+        let answer = solve_part1_hashmap(TEST_INPUT1);
+        assert_eq!(answer, Ok(19114));
     }
 
     #[test]
     fn test_part2() {
         let answer = solve_part2(TEST_INPUT1);
-        assert_eq!(answer, 167_409_079_868_000);
+        assert_eq!(answer, Ok(167_409_079_868_000));
+
+        let answer = solve_part2_hashmap(TEST_INPUT1);
+        assert_eq!(answer, Ok(167_409_079_868_000));
+    }
+
+    #[test]
+    fn test_workflows_parse() {
+        let workflows = Workflows::parse(TEST_INPUT1).unwrap();
+        assert_eq!(workflows.rules.len(), 11);
+        assert_eq!(workflows.rules[workflows.start as usize].len(), 2);
+
+        let error = Workflows::parse("in{a<5:foo,R}\n\n").err().unwrap();
+        assert_eq!(error.to_string(), "invalid workflow `foo`");
+        let error = Workflows::parse("px{A}\n\n").err().unwrap();
+        assert_eq!(error.to_string(), "invalid workflow `in`");
+
+        let error = Workflows::parse("in{x<10:in,A}\n\n").err().unwrap();
+        assert_eq!(error.to_string(), "invalid non-cyclic workflow `in`");
+        let error = Workflows::parse("in{s>1:a,R}\na{b}\nb{x<5:A,in}\n\n")
+            .err()
+            .unwrap();
+        assert!(error.to_string().starts_with("invalid non-cyclic workflow"));
+        assert!(solve_part2("in{x<10:in,A}\n\n").is_err());
+    }
+
+    #[test]
+    fn test_graph() {
+        let graph = graph(TEST_INPUT1).unwrap();
+        assert_eq!(graph.len(), 13);
+        assert_eq!(graph.edge_count(), 25);
+
+        // The workflows form a DAG starting at `in` and ending at the results
+        let order = graph.topological_sort().unwrap();
+        assert_eq!(graph.node(order[0]), &"in");
+        assert_eq!(graph.dfs(order[0]).count(), graph.len());
+
+        let px = graph.id("px").unwrap();
+        let edges = graph
+            .edges(px)
+            .iter()
+            .map(|&(to, condition)| (*graph.node(to), condition))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            edges,
+            [
+                ("qkq", Some("a<2006")),
+                ("A", Some("m>2090")),
+                ("rfg", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn workflows_are_dag() {
+        let graph = graph(INPUT).unwrap();
+        let order = graph.topological_sort().unwrap();
+        assert_eq!(graph.node(order[0]), &"in");
+    }
+
+    #[test]
+    fn test_is_part_accepted_by_rules() {
+        let (workflows, parts) = parse(TEST_INPUT1).unwrap();
+        let rules = parse2(TEST_INPUT1).unwrap();
+
+        for part in parts.map(Result::unwrap) {
+            assert_eq!(
+                is_part_accepted_by_rules(&rules, &part),
+                is_part_accepted(&workflows, &part)
+            );
+        }
+    }
+
+    #[test]
+    fn test_trace_part() {
+        let workflows = parse2(TEST_INPUT1).unwrap();
+        let part = parse_parts("{x=787,m=2655,a=1222,s=2876}")
+            .next()
+            .unwrap()
+            .unwrap();
+
+        let trace = trace_part(&workflows, &part)
+            .unwrap()
+            .into_iter()
+            .map(|(name, rule)| format!("{name}: {rule}"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            trace,
+            ["in: qqz", "qqz: s>2770:qs", "qs: lnx", "lnx: m>1548:A"]
+        );
+
+        let workflows = parse2("in{x<1000:in,A}\n\n").unwrap();
+        let error = trace_part(&workflows, &part).err().unwrap();
+        assert_eq!(error.to_string(), "invalid non-cyclic workflow `in`");
+    }
+
+    #[test]
+    fn test_hashmap_invalid_workflows() {
+        let input = "in{a<5:foo,R}\n\n{x=1,m=2,a=3,s=4}";
+        let error = Err(parse::ParseError::InvalidValue {
+            expected: "workflow",
+            input: "foo".to_string(),
+        });
+        assert_eq!(solve_part1_hashmap(input), error);
+        assert_eq!(solve_part2_hashmap(input), error);
+
+        let input = "in{a<5:foo,A}\nfoo{in}\n\n{x=1,m=2,a=3,s=4}";
+        assert!(solve_part1_hashmap(input).is_err());
+        assert!(solve_part2_hashmap(input).is_err());
     }
 
     #[test]
     fn test_split_part_by_rule() {
-        let part = RangePart::new(2001..2005);
+        let part = RangePart::cube(2001..2005);
         let rule = RuleDef {
             condition: Some(Condition {
                 category: Category::X,
@@ -458,8 +834,23 @@ mod tests {
             result: RuleResult::Accept,
         };
 
-        let result = split_part_by_rule(&rule, part);
+        let result = split_part_by_rule(&rule, part.clone());
         println!("{:#?}", result);
+        assert!(result.accepted.is_none());
+        assert_eq!(result.rejected.as_ref(), Some(&part));
+
+        let rule = RuleDef {
+            condition: Some(Condition {
+                category: Category::A,
+                op: Op::Lt,
+                value: 2003,
+            }),
+            result: RuleResult::Reject,
+        };
+        let result = split_part_by_rule(&rule, part);
+        let (accepted, _) = result.accepted.unwrap();
+        assert_eq!(accepted.ranges[2], 2001..2003);
+        assert_eq!(result.rejected.unwrap().ranges[2], 2003..2005);
     }
 }
 
@@ -471,12 +862,12 @@ mod benches {
     #[divan::bench]
     fn part1() {
         let answer = solve_part1(black_box(INPUT));
-        assert_eq!(answer, 480738);
+        assert_eq!(answer, Ok(480738));
     }
 
     #[divan::bench]
     fn part2() {
         let answer = solve_part2(black_box(INPUT));
-        assert_eq!(answer, 131_550_418_841_958);
+        assert_eq!(answer, Ok(131_550_418_841_958));
     }
 }
@@ -1,161 +1,226 @@
-use std::str::FromStr;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeFrom;
 
+use crate::fastparse;
+use crate::intern::Interner;
+use crate::parse;
+
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 2;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day2.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
-    let answer_part1 = solve_part1(input);
+    let answer_part1 = solve_part1(input).unwrap();
     println!("day{DAY}::part1 answer: {}", answer_part1);
 
-    let answer_part2 = solve_part2(input);
+    let answer_part2 = solve_part2(input).unwrap();
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-#[derive(Debug)]
-struct Game {
-    id: u64,
-    subsets: Vec<Subset>,
+/// Number of cubes of each colour in the bag, colours which are not in it have no cubes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bag {
+    counts: BTreeMap<String, u64>,
 }
 
-impl Game {
-    fn from_str(s: &str) -> Self {
-        let (game, subsets) = s
-            .split_once(": ")
-            .unwrap_or_else(|| panic!("invalid input for game `{}`", s));
-        let (_, id) = game
-            .split_once(' ')
-            .unwrap_or_else(|| panic!("invalid input for game `{}`", s));
-        let id = u64::from_str(id)
-            .unwrap_or_else(|e| panic!("failed to parse game id `{id}` with error {e}"));
-
-        Self {
-            id,
-            subsets: subsets.split("; ").map(Subset::from_str).collect(),
+impl Bag {
+    pub fn new<C: Into<String>>(counts: impl IntoIterator<Item = (C, u64)>) -> Self {
+        Bag {
+            counts: counts.into_iter().map(|(c, n)| (c.into(), n)).collect(),
         }
     }
-}
 
-impl Game {
-    fn is_possible_with(&self, reds: u64, greens: u64, blues: u64) -> bool {
-        for s in self.subsets.iter() {
-            for cube in s.cubes.iter() {
-                match cube.color {
-                    Color::Red => {
-                        if cube.count > reds {
-                            return false;
-                        }
-                    }
-                    Color::Green => {
-                        if cube.count > greens {
-                            return false;
-                        }
-                    }
-                    Color::Blue => {
-                        if cube.count > blues {
-                            return false;
-                        }
-                    }
-                }
-            }
-        }
-        true
+    /// Parses the cubes like they are written in the games, for example `12 red, 13 green`
+    pub fn parse(s: &str) -> parse::Result<Self> {
+        let counts = parse::separated(s, ",", cubes)
+            .map(|cubes| cubes.map(|(color, count)| (color.to_string(), count)))
+            .collect::<parse::Result<_>>()?;
+        Ok(Bag { counts })
     }
 
-    fn power_of_min_set_of_cubes(&self) -> u64 {
-        let mut reds = 0;
-        let mut greens = 0;
-        let mut blues = 0;
+    pub fn count(&self, color: &str) -> u64 {
+        self.counts.get(color).copied().unwrap_or(0)
+    }
 
-        for s in self.subsets.iter() {
-            for cube in s.cubes.iter() {
-                match cube.color {
-                    Color::Red => reds = reds.max(cube.count),
-                    Color::Green => greens = greens.max(cube.count),
-                    Color::Blue => blues = blues.max(cube.count),
-                }
+    /// Colours and their counts, ordered by the name of the colour
+    pub fn counts(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.counts
+            .iter()
+            .map(|(color, &count)| (color.as_str(), count))
+    }
+}
+
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (color, count)) in self.counts().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
             }
+            write!(f, "{count} {color}")?;
         }
+        Ok(())
+    }
+}
 
-        reds * greens * blues
+/// Parses `3 blue`
+fn cubes(s: &str) -> parse::Result<(&str, u64)> {
+    let (count, color) = parse::split_once(s.trim(), " ")?;
+    let color = color.trim();
+    if color.is_empty() || color.contains(char::is_whitespace) {
+        return Err(parse::ParseError::InvalidValue {
+            expected: "colour",
+            input: color.to_string(),
+        });
     }
+    Ok((color, parse::number(count)?))
 }
 
-#[derive(Debug)]
-struct Subset {
-    cubes: Vec<Cubes>,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: u64,
+    /// Ids of the colours and the number of cubes of them, of all the handfuls shown
+    cubes: Vec<(u16, u64)>,
+    /// Where the handfuls end in `cubes`
+    subset_ends: Vec<usize>,
 }
 
-impl Subset {
-    fn from_str(input: &str) -> Self {
-        Self {
-            cubes: input.split(", ").map(Cubes::from_str).collect(),
-        }
+impl Game {
+    /// The cubes of each handful shown, as colour ids and counts
+    pub fn subsets(&self) -> impl Iterator<Item = &[(u16, u64)]> {
+        let starts = std::iter::once(0).chain(self.subset_ends.iter().copied());
+        starts
+            .zip(&self.subset_ends)
+            .map(|(start, &end)| &self.cubes[start..end])
+    }
+
+    /// The cubes of all the handfuls
+    pub fn cubes(&self) -> &[(u16, u64)] {
+        &self.cubes
     }
 }
 
-#[derive(Debug)]
-struct Cubes {
-    count: u64,
-    color: Color,
+/// Games of the input with the names of the colours which appear in them
+#[derive(Debug, Clone)]
+pub struct Games<'a> {
+    colors: Interner<'a, u16>,
+    games: Vec<Game>,
 }
 
-impl Cubes {
-    fn from_str(input: &str) -> Self {
-        let (count, color) = input
-            .split_once(' ')
-            .unwrap_or_else(|| panic!("invalid input for cube `{}`", input));
-        let count = u64::from_str(count).unwrap_or_else(|e| {
-            panic!("failed to parse cube count `{count}` for cube `{input}` with error {e}")
-        });
-        let color = Color::from_str(color)
-            .unwrap_or_else(|_| panic!("failed to parse cube color `{color}` for cube `{input}`"));
-        Self { count, color }
+impl<'a> Games<'a> {
+    pub fn parse(input: &'a str) -> parse::Result<Self> {
+        let mut colors = Interner::new();
+        let mut games = Vec::new();
+        for line in fastparse::lines(input) {
+            let (game, subsets) = parse::header(line)?;
+            let id = parse::number(parse::prefix(game, "Game ")?)?;
+            let mut game = Game {
+                id,
+                cubes: Vec::new(),
+                subset_ends: Vec::new(),
+            };
+            for subset in subsets.split(';') {
+                for s in subset.split(',') {
+                    let (color, count) = cubes(s)?;
+                    // there are only a few colours, finding them is quicker than hashing them
+                    let id = match colors.names().iter().position(|&c| c == color) {
+                        Some(id) => id as u16,
+                        None => colors.intern(color),
+                    };
+                    game.cubes.push((id, count));
+                }
+                game.subset_ends.push(game.cubes.len());
+            }
+            games.push(game);
+        }
+        Ok(Games { colors, games })
     }
-}
 
-#[derive(Debug)]
-enum Color {
-    Red,
-    Green,
-    Blue,
-}
+    pub fn games(&self) -> &[Game] {
+        &self.games
+    }
 
-impl Color {
-    fn from_str(input: &str) -> Result<Self, ()> {
-        Ok(match input {
-            "red" => Color::Red,
-            "green" => Color::Green,
-            "blue" => Color::Blue,
-            _ => return Err(()),
+    /// Names of the colours, in the order of their ids
+    pub fn colors(&self) -> &[&'a str] {
+        self.colors.names()
+    }
+
+    /// Fewest cubes of each colour the game could have been played with, indexed by colour id
+    fn minimum(&self, game: &Game) -> Vec<u64> {
+        let mut minimum = vec![0; self.colors.len()];
+        for &(color, count) in game.cubes() {
+            minimum[color as usize] = minimum[color as usize].max(count);
+        }
+        minimum
+    }
+
+    /// Smallest bag the game could have been played with
+    pub fn minimal_bag(&self, game: &Game) -> Bag {
+        let minimum = self.minimum(game);
+        Bag::new(
+            self.colors()
+                .iter()
+                .zip(minimum)
+                .filter(|&(_, count)| count > 0)
+                .map(|(&color, count)| (color, count)),
+        )
+    }
+
+    /// Product of the counts of the [`Games::minimal_bag`], zero if a colour of the input doesn't
+    /// appear in the game
+    pub fn power(&self, game: &Game) -> u64 {
+        self.minimum(game).into_iter().product()
+    }
+
+    /// Games which could have been played with the cubes in `bag`
+    pub fn possible<'g>(&'g self, bag: &Bag) -> impl Iterator<Item = &'g Game> + 'g {
+        let limits = self
+            .colors()
+            .iter()
+            .map(|color| bag.count(color))
+            .collect::<Vec<_>>();
+        self.games.iter().filter(move |game| {
+            game.cubes()
+                .iter()
+                .all(|&(color, count)| count <= limits[color as usize])
         })
     }
+
+    /// Bags with which all the games are possible, every colour needs at least as many cubes as
+    /// the start of its range and any number of cubes of other colours can be added
+    pub fn bags_admitting_all(&self) -> BTreeMap<&'a str, RangeFrom<u64>> {
+        let mut minimum = vec![0; self.colors.len()];
+        for game in &self.games {
+            for (m, count) in minimum.iter_mut().zip(self.minimum(game)) {
+                *m = (*m).max(count);
+            }
+        }
+        self.colors()
+            .iter()
+            .copied()
+            .zip(minimum.into_iter().map(|m| m..))
+            .collect()
+    }
 }
 
-fn solve_part1(input: &str) -> u64 {
-    const MAX_RED: u64 = 12;
-    const MAX_GREEN: u64 = 13;
-    const MAX_BLUE: u64 = 14;
-
-    input
-        .lines()
-        .map(Game::from_str)
-        .filter_map(|g| {
-            g.is_possible_with(MAX_RED, MAX_GREEN, MAX_BLUE)
-                .then_some(g.id)
-        })
-        .sum()
+/// The bag of part 1
+pub const ELF_BAG: [(&str, u64); 3] = [("red", 12), ("green", 13), ("blue", 14)];
+
+pub(crate) fn solve_part1(input: &str) -> parse::Result<u64> {
+    let games = Games::parse(input)?;
+    Ok(games.possible(&Bag::new(ELF_BAG)).map(|g| g.id).sum())
 }
 
-fn solve_part2(input: &str) -> u64 {
-    input
-        .lines()
-        .map(Game::from_str)
-        .map(|g| g.power_of_min_set_of_cubes())
-        .sum()
+pub(crate) fn solve_part2(input: &str) -> parse::Result<u64> {
+    let games = Games::parse(input)?;
+    Ok(games.games().iter().map(|g| games.power(g)).sum())
 }
 
 #[cfg(test)]
@@ -171,13 +236,68 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
     #[test]
     fn test_part1() {
         let answer = solve_part1(TEST_INPUT1);
-        assert_eq!(answer, 8);
+        assert_eq!(answer, Ok(8));
     }
 
     #[test]
     fn test_part2() {
         let answer = solve_part2(TEST_INPUT1);
-        assert_eq!(answer, 2286);
+        assert_eq!(answer, Ok(2286));
+    }
+
+    #[test]
+    fn test_queries() {
+        let games = Games::parse(TEST_INPUT1).unwrap();
+        assert_eq!(games.colors(), ["blue", "red", "green"]);
+        let subsets = games.games()[0].subsets().collect::<Vec<_>>();
+        assert_eq!(
+            subsets,
+            [&[(0, 3), (1, 4)][..], &[(1, 1), (2, 2), (0, 6)], &[(2, 2)]]
+        );
+
+        let bag = Bag::parse("12 red, 13 green, 14 blue").unwrap();
+        let ids = games.possible(&bag).map(|g| g.id).collect::<Vec<_>>();
+        assert_eq!(ids, [1, 2, 5]);
+        assert_eq!(games.possible(&Bag::default()).count(), 0);
+
+        let minimal = games.minimal_bag(&games.games()[2]);
+        assert_eq!(minimal.to_string(), "6 blue, 13 green, 20 red");
+        assert_eq!(games.power(&games.games()[2]), 1560);
+
+        let admitting = games.bags_admitting_all();
+        assert_eq!(admitting["red"], 20..);
+        assert_eq!(admitting["green"], 13..);
+        assert_eq!(admitting["blue"], 15..);
+        let bag = Bag::new(admitting.iter().map(|(&color, range)| (color, range.start)));
+        assert_eq!(games.possible(&bag).count(), 5);
+    }
+
+    #[test]
+    fn test_other_colors() {
+        let input = indoc::indoc! {"
+        Game 1: 2 purple, 1 red; 3 light-blue
+        Game 7: 1 purple
+        Game 9: 4 purple, 1 light-blue, 1 red
+        "};
+        let games = Games::parse(input).unwrap();
+        let bag = Bag::new([("purple", 3), ("light-blue", 3), ("red", 1)]);
+        let ids = games.possible(&bag).map(|g| g.id).collect::<Vec<_>>();
+        assert_eq!(ids, [1, 7]);
+        // there are no purple cubes in the bag of part 1
+        assert_eq!(solve_part1(input), Ok(0));
+        // game 7 has no red and light-blue cubes, so its power is zero
+        assert_eq!(solve_part2(input), Ok(6 + 4));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Games::parse("Game 1: 3 blue, red").is_err());
+        assert!(Games::parse("Game x: 3 blue").is_err());
+        assert!(Games::parse("1: 3 blue").is_err());
+        assert!(Bag::parse("3 dark blue").is_err());
+        assert!(Bag::parse("3").is_err());
+        assert!(solve_part1("Game 1: 3 blue, red").is_err());
+        assert!(solve_part2("Game 1: 3 blue, red").is_err());
     }
 }
 
@@ -189,12 +309,12 @@ mod benches {
     #[divan::bench]
     fn part1() {
         let answer = solve_part1(black_box(INPUT));
-        assert_eq!(answer, 2239);
+        assert_eq!(answer, Ok(2239));
     }
 
     #[divan::bench]
     fn part2() {
         let answer = solve_part2(black_box(INPUT));
-        assert_eq!(answer, 83435);
+        assert_eq!(answer, Ok(83435));
     }
 }
@@ -1,388 +1,243 @@
-use std::str::FromStr;
+use std::ops::Range;
 
+use crate::fastparse;
+use crate::grid::Pos;
+use crate::parse;
+
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 3;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day3.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
-    let answer_part1 = solve_part1(input);
+    let answer_part1 = solve_part1(input).unwrap();
     println!("day{DAY}::part1 answer: {}", answer_part1);
 
-    let answer_part2 = solve_part2(input);
+    let answer_part2 = solve_part2(input).unwrap();
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn is_symbol(c: char) -> bool {
-    !c.is_ascii_digit() && c != '.'
+/// Number written in the schematic, the digits are in `cols` of `row`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    pub value: u64,
+    pub row: usize,
+    pub cols: Range<usize>,
 }
 
-fn solve_part1(input: &str) -> u64 {
-    let mut lines = input.lines();
-
-    let first_line = lines.next().unwrap();
-    let dummy_line = ".".repeat(first_line.len());
-
-    let mut line_buf = [&dummy_line, first_line, lines.next().unwrap_or(&dummy_line)];
-
-    let mut end = false;
-    let mut sum = 0;
-    loop {
-        //print!("{line_num}: ");
-        let mut is_important = false;
-        let mut number_start = None;
-
-        let iter = line_buf[0]
-            .chars()
-            .zip(line_buf[1].chars())
-            .zip(line_buf[2].chars())
-            .enumerate();
-
-        for (pos, ((c0, c1), c2)) in iter {
-            match (c0, c1, c2) {
-                (above, middle, below) if middle.is_ascii_digit() && number_start.is_none() => {
-                    // Number started
-                    number_start = Some(pos);
-                    if !is_important && (is_symbol(above) || is_symbol(below)) {
-                        is_important = true;
-                    }
-                }
-                (above, middle, below) if middle.is_ascii_digit() && number_start.is_some() => {
-                    // Number continuing
-                    if !is_important && (is_symbol(above) || is_symbol(below)) {
-                        is_important = true;
-                    }
-                }
-                (above, middle, below) if !middle.is_ascii_digit() && number_start.is_some() => {
-                    // Number ended
-                    let was_symbol = is_symbol(above) || is_symbol(below) || is_symbol(middle);
-                    if !is_important && was_symbol {
-                        is_important = true;
-                    }
-
-                    if is_important {
-                        let start = number_start.unwrap();
-                        let end = pos;
-                        let num = &line_buf[1][start..end];
-                        //print!("{num}, ");
-
-                        let num = u64::from_str(num).unwrap();
-                        sum += num;
-                    }
+/// Any character of the schematic which is neither a digit nor a `.`, the columns of the positions
+/// are counted in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    pub ch: char,
+    pub pos: Pos,
+}
 
-                    if !was_symbol {
-                        is_important = false;
-                    }
+/// Lists of indices for each item, all stored in one `Vec`
+#[derive(Debug, Clone, Default)]
+struct Adjacency {
+    /// The list of item `i` is `targets[offsets[i]..offsets[i + 1]]`
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+}
 
-                    number_start = None;
-                }
-                (c0, c1, c2) if is_symbol(c0) || is_symbol(c1) || is_symbol(c2) => {
-                    is_important = true;
-                }
-                _ => {
-                    // ignore
-                    is_important = false;
-                }
-            }
+impl Adjacency {
+    /// Groups the `(item, target)` pairs by item, the targets keep their order
+    fn from_pairs(items: usize, pairs: &[(usize, usize)]) -> Self {
+        let mut offsets = vec![0; items + 1];
+        for &(item, _) in pairs {
+            offsets[item + 1] += 1;
         }
-
-        if is_important && number_start.is_some() {
-            let start = number_start.unwrap();
-            let num = &line_buf[1][start..];
-            //print!("{num}, ");
-
-            let num = u64::from_str(num).unwrap();
-            sum += num;
+        for i in 0..items {
+            offsets[i + 1] += offsets[i];
         }
-
-        if let Some(line) = lines.next() {
-            line_buf[0] = line;
-            line_buf.rotate_left(1);
-        } else if !end {
-            line_buf[0] = &dummy_line;
-            line_buf.rotate_left(1);
-            end = true;
-        } else {
-            break;
+        let mut next = offsets.clone();
+        let mut targets = vec![0; pairs.len()];
+        for &(item, target) in pairs {
+            targets[next[item]] = target;
+            next[item] += 1;
         }
+        Adjacency { offsets, targets }
     }
 
-    sum
-}
-
-#[derive(Debug, Clone, Copy)]
-enum Position {
-    NW,
-    N,
-    NE,
-    E,
-    SE,
-    S,
-    SW,
-    W,
-    Unknown,
+    fn get(&self, item: usize) -> &[usize] {
+        &self.targets[self.offsets[item]..self.offsets[item + 1]]
+    }
 }
 
+/// The numbers and symbols of an engine schematic and which of them are adjacent
+///
+/// The lines don't need to be equally long, there is nothing beyond the end of a line.
 #[derive(Debug, Clone)]
-struct GearNumberPositions {
-    buf: [Position; 2],
-    count: usize,
+pub struct Schematic {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    symbols_of_number: Adjacency,
+    numbers_of_symbol: Adjacency,
 }
 
-impl GearNumberPositions {
-    fn new() -> Self {
-        Self {
-            buf: [Position::Unknown; 2],
-            count: 0,
+impl Schematic {
+    pub fn parse(input: &str) -> parse::Result<Self> {
+        let mut numbers = Vec::new();
+        let mut symbols = Vec::new();
+        // symbols of every row, they are found in order so each row is a range of `symbols`
+        let mut row_symbols = Vec::new();
+        for (row, line) in fastparse::lines(input).enumerate() {
+            let start = symbols.len();
+            let bytes = line.as_bytes();
+            let mut col = 0;
+            while col < bytes.len() {
+                match bytes[col] {
+                    b'.' => col += 1,
+                    b'0'..=b'9' => {
+                        let len = fastparse::digits(&bytes[col..]);
+                        numbers.push(Number {
+                            value: fastparse::number(&line[col..col + len])?,
+                            row,
+                            cols: col..col + len,
+                        });
+                        col += len;
+                    }
+                    b => {
+                        let ch = if b.is_ascii() {
+                            b as char
+                        } else {
+                            line[col..].chars().next().unwrap()
+                        };
+                        symbols.push(Symbol {
+                            ch,
+                            pos: (row, col),
+                        });
+                        col += ch.len_utf8();
+                    }
+                }
+            }
+            row_symbols.push(start..symbols.len());
         }
-    }
 
-    fn push(&mut self, pos: Position) -> Result<(), ()> {
-        if self.count == 2 {
-            return Err(());
+        // (number, symbol) pairs, ordered by number
+        let mut pairs = Vec::new();
+        for (i, number) in numbers.iter().enumerate() {
+            let rows = number.row.saturating_sub(1)..(number.row + 2).min(row_symbols.len());
+            let cols = number.cols.start.saturating_sub(1)..=number.cols.end;
+            for row in rows {
+                let range = row_symbols[row].clone();
+                let first = range.start
+                    + symbols[range.clone()].partition_point(|s| s.pos.1 < *cols.start());
+                pairs.extend(
+                    (first..range.end)
+                        .take_while(|&s| cols.contains(&symbols[s].pos.1))
+                        .map(|s| (i, s)),
+                );
+            }
         }
-
-        self.buf[self.count] = pos;
-        self.count += 1;
-        Ok(())
+        let symbols_of_number = Adjacency::from_pairs(numbers.len(), &pairs);
+        let flipped = pairs.iter().map(|&(n, s)| (s, n)).collect::<Vec<_>>();
+        let numbers_of_symbol = Adjacency::from_pairs(symbols.len(), &flipped);
+
+        Ok(Schematic {
+            numbers,
+            symbols,
+            symbols_of_number,
+            numbers_of_symbol,
+        })
     }
-}
-
-fn solve_part2(input: &str) -> u64 {
-    let mut lines = input.lines();
 
-    let first_line = lines.next().unwrap();
-    let line_len = first_line.len();
-    let dummy_line = ".".repeat(first_line.len());
-
-    let mut line_buf = [&dummy_line, first_line, lines.next().unwrap_or(&dummy_line)];
-
-    let mut end = false;
-    let mut sum = 0;
-    loop {
-        let mut chars = line_buf[1].char_indices().peekable();
-        let mut num_start: Option<usize> = None;
-        while let Some((pos, c)) = chars.next() {
-            match c {
-                '*' => {
-                    let mut positions = GearNumberPositions::new();
+    /// The numbers from left to right and top to bottom
+    pub fn numbers(&self) -> &[Number] {
+        &self.numbers
+    }
 
-                    // west
-                    if num_start.is_some() {
-                        positions.push(Position::W).unwrap();
-                    }
+    /// The symbols from left to right and top to bottom
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
 
-                    // nw, n, ne
-                    let is_at_line_end = line_len == pos - 1;
-                    let mut above_adjacent = line_buf[0]
-                        [pos - 1..pos + 1 + !is_at_line_end as usize]
-                        .chars()
-                        .map(|c| c.is_ascii_digit());
-                    let is_nw_number = above_adjacent.next().unwrap();
-                    let is_n_number = above_adjacent.next().unwrap();
-                    if is_n_number {
-                        // if n is number, then nw and ne are part of the same number, don't check atm
-                        positions.push(Position::N).unwrap();
-                    } else {
-                        if is_nw_number && positions.push(Position::NW).is_err() {
-                            // more than two numbers are adjacent to *
-                            continue;
-                        }
-                        //dbg!(is_nw_number);
-                        if let Some(ne) = above_adjacent.next() {
-                            // dbg!(ne);
-                            if ne && positions.push(Position::NE).is_err() {
-                                // more than two numbers are adjacent to *
-                                continue;
-                            }
-                        }
-                    }
+    /// Indices of the symbols next to the number with index `number`, diagonals count too
+    pub fn adjacent_symbols(&self, number: usize) -> &[usize] {
+        self.symbols_of_number.get(number)
+    }
 
-                    // east
-                    let is_next_number = chars.peek().map(|(_, c)| c.is_ascii_digit());
-                    if let Some(is_next_number) = is_next_number {
-                        if is_next_number && positions.push(Position::E).is_err() {
-                            // more than two numbers are adjacent to *
-                            continue;
-                        }
-                    }
+    /// Indices of the numbers next to the symbol with index `symbol`, diagonals count too
+    pub fn adjacent_numbers(&self, symbol: usize) -> &[usize] {
+        self.numbers_of_symbol.get(symbol)
+    }
 
-                    // sw, s, se
-                    let mut below_adjacent = line_buf[2]
-                        [pos - 1..pos + 1 + !is_at_line_end as usize]
-                        .chars()
-                        .map(|c| c.is_ascii_digit());
-                    let is_sw_number = below_adjacent.next().unwrap();
-                    let is_s_number = below_adjacent.next().unwrap();
-                    if is_s_number {
-                        // if n is number, then nw and ne are part of the same number, don't check atm
-                        if positions.push(Position::S).is_err() {
-                            continue;
-                        }
-                    } else {
-                        if is_sw_number && positions.push(Position::SW).is_err() {
-                            // more than two numbers are adjacent to *
-                            continue;
-                        }
-                        if let Some(se) = below_adjacent.next() {
-                            if se && positions.push(Position::SE).is_err() {
-                                // more than two numbers are adjacent to *
-                                continue;
-                            }
-                        }
-                    }
+    /// Numbers which are next to a symbol
+    pub fn part_numbers(&self) -> impl Iterator<Item = &Number> {
+        (0..self.numbers.len())
+            .filter(|&n| !self.adjacent_symbols(n).is_empty())
+            .map(|n| &self.numbers[n])
+    }
 
-                    if positions.count == 2 {
-                        // exactly two numbers are adjacent to *
-                        let mut prod = 1;
-                        for num_pos in &positions.buf {
-                            match num_pos {
-                                Position::NW => {
-                                    let end = pos;
-                                    let line = &line_buf[0][..end];
-                                    let num = parse_num_from_back(line);
-                                    prod *= num;
-                                }
-                                Position::SW => {
-                                    let end = pos;
-                                    let line = &line_buf[2][..end];
-                                    let num = parse_num_from_back(line);
-                                    prod *= num;
-                                }
-                                Position::NE => {
-                                    let start = pos + 1;
-                                    let line = &line_buf[0][start..];
-                                    let num = parse_num_from_front(line);
-                                    prod *= num;
-                                }
-                                Position::SE => {
-                                    let start = pos + 1;
-                                    let line = &line_buf[2][start..];
-                                    let num = parse_num_from_front(line);
-                                    prod *= num;
-                                }
-                                Position::E => {
-                                    let mut end = pos + 2;
-                                    while let Some((p, d)) = chars.peek() {
-                                        if !d.is_ascii_digit() {
-                                            end = *p;
-                                            break;
-                                        }
-                                        chars.next();
-                                    }
-
-                                    let num = &line_buf[1][pos + 1..end];
-                                    prod *= u64::from_str(num).unwrap();
-                                }
-                                Position::N => {
-                                    let line = line_buf[0];
-                                    let num = parse_num_from_middle(line, pos);
-                                    prod *= num;
-                                }
-                                Position::S => {
-                                    let line = line_buf[2];
-                                    let num = parse_num_from_middle(line, pos);
-                                    prod *= num;
-                                }
-
-                                Position::W => {
-                                    let num_start = num_start.unwrap();
-                                    let end = pos;
-                                    let num = &line_buf[1][num_start..end];
-                                    prod *= u64::from_str(num).unwrap();
-                                }
-                                Position::Unknown => unreachable!(),
-                            }
-                        }
-
-                        sum += prod;
-                    }
-                }
-                c if c.is_ascii_digit() && num_start.is_none() => {
-                    // number start
-                    num_start = Some(pos);
+    /// Values of the symbols which are gears according to `rule`, `None` if a value overflows a u64
+    pub fn gears<'s>(&'s self, rule: &'s GearRule) -> impl Iterator<Item = Option<u64>> + 's {
+        (0..self.symbols.len())
+            .filter(|&s| rule.symbols.contains(&self.symbols[s].ch))
+            .map(|s| self.adjacent_numbers(s))
+            .filter(|numbers| numbers.len() == rule.numbers)
+            .map(|numbers| {
+                let mut values = numbers.iter().map(|&n| self.numbers[n].value);
+                match rule.combine {
+                    Combine::Product => values.try_fold(1, u64::checked_mul),
+                    Combine::Sum => values.try_fold(0, u64::checked_add),
                 }
-                c if c.is_ascii_digit() && num_start.is_some() => {
-                    //number continuing
-                }
-                _ => {
-                    num_start = None;
-                }
-            }
-        }
-
-        if let Some(line) = lines.next() {
-            line_buf[0] = line;
-            line_buf.rotate_left(1);
-        } else if !end {
-            line_buf[0] = &dummy_line;
-            line_buf.rotate_left(1);
-            end = true;
-        } else {
-            break;
-        }
+            })
     }
-
-    sum
 }
 
-fn parse_num_from_front(input: &str) -> u64 {
-    let mut chars = input.char_indices();
-    let mut end = 1;
-    while let Some((p, d)) = chars.next() {
-        if d.is_ascii_digit() {
-            end = p + 1;
-        } else {
-            break;
-        }
-    }
+/// How the adjacent numbers of a gear are combined into its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combine {
+    Product,
+    Sum,
+}
 
-    let num = &input[..end];
-    u64::from_str(num).unwrap()
+/// Which symbols are gears and what they are worth
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearRule {
+    /// Characters of the symbols which can be gears
+    pub symbols: Vec<char>,
+    /// Number of adjacent numbers a gear has, symbols with fewer or more aren't gears
+    pub numbers: usize,
+    pub combine: Combine,
 }
 
-fn parse_num_from_back(input: &str) -> u64 {
-    let mut chars = input.char_indices();
-    let mut start = input.len() - 1;
-    while let Some((p, d)) = chars.next_back() {
-        if d.is_ascii_digit() {
-            start = p;
-        } else {
-            break;
+impl GearRule {
+    /// The rule of part 2, a `*` next to exactly two numbers is worth their product
+    pub fn gear_ratio() -> Self {
+        GearRule {
+            symbols: vec!['*'],
+            numbers: 2,
+            combine: Combine::Product,
         }
     }
-
-    let num = &input[start..];
-    u64::from_str(num).unwrap()
 }
 
-fn parse_num_from_middle(input: &str, mid: usize) -> u64 {
-    let mut start = mid;
-    let mut end: usize = mid + 1;
-
-    let (front, back) = input.split_at(mid);
-    let mut front = front.char_indices();
-    while let Some((p, d)) = front.next_back() {
-        if d.is_ascii_digit() {
-            start = p;
-        } else {
-            break;
-        }
-    }
+pub(crate) fn solve_part1(input: &str) -> parse::Result<u64> {
+    let schematic = Schematic::parse(input)?;
+    checked_sum(schematic.part_numbers().map(|n| Some(n.value)))
+}
 
-    let mut back = back.char_indices();
-    while let Some((p, d)) = back.next() {
-        if d.is_ascii_digit() {
-            end = mid + p + 1;
-        } else {
-            break;
-        }
-    }
+pub(crate) fn solve_part2(input: &str) -> parse::Result<u64> {
+    let schematic = Schematic::parse(input)?;
+    checked_sum(schematic.gears(&GearRule::gear_ratio()))
+}
 
-    let num = &input[start..end];
-    u64::from_str(num).unwrap()
+/// Sum of `values`, where `None` is a value which already overflowed
+fn checked_sum(mut values: impl Iterator<Item = Option<u64>>) -> parse::Result<u64> {
+    values
+        .try_fold(0u64, |sum, value| sum.checked_add(value?))
+        .ok_or_else(|| parse::ParseError::InvalidNumber {
+            input: String::from("answer"),
+            reason: String::from("the sum overflows u64"),
+        })
 }
 
 #[cfg(test)]
@@ -403,13 +258,13 @@ mod tests {
     #[test]
     fn test_part1() {
         let answer = solve_part1(TEST_INPUT1);
-        assert_eq!(answer, 4361);
+        assert_eq!(answer, Ok(4361));
     }
 
     #[test]
     fn test_part2() {
         let answer = solve_part2(TEST_INPUT1);
-        assert_eq!(answer, 467835);
+        assert_eq!(answer, Ok(467835));
     }
 
     #[test]
@@ -418,7 +273,125 @@ mod tests {
 ....761.169...............=...524........&......152..........*...975.994.........*....122..........858*...........%.......776...........*...
 ...........-..180/.850..............$.....524....-...........940.=......*......199........963..............#........836...*.....34...543.448";
         let answer = solve_part2(input.trim());
-        assert_eq!(answer, 543 * 448);
+        assert_eq!(answer, Ok(543 * 448));
+    }
+
+    #[test]
+    fn test_part2_consecutive_gears() {
+        let input = indoc::indoc! {"
+        .......
+        .12*3*4
+        "};
+        let answer = solve_part2(input);
+        assert_eq!(answer, Ok(12 * 3 + 3 * 4));
+    }
+
+    #[test]
+    fn test_part2_edges() {
+        let input = indoc::indoc! {"
+        *12..
+        3...4
+        ...5*
+        "};
+        assert_eq!(solve_part1(input), Ok(12 + 3 + 4 + 5));
+        assert_eq!(solve_part2(input), Ok(12 * 3 + 4 * 5));
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let schematic = Schematic::parse(TEST_INPUT1).unwrap();
+        assert_eq!(schematic.numbers().len(), 10);
+        assert_eq!(
+            schematic.numbers()[0],
+            Number {
+                value: 467,
+                row: 0,
+                cols: 0..3
+            }
+        );
+        assert_eq!(
+            schematic.symbols()[0],
+            Symbol {
+                ch: '*',
+                pos: (1, 3)
+            }
+        );
+        assert_eq!(schematic.adjacent_numbers(0), [0, 2]);
+        assert_eq!(schematic.adjacent_symbols(0), [0]);
+        // 114 and 58 are not part numbers
+        assert!(schematic.adjacent_symbols(1).is_empty());
+        assert!(schematic.adjacent_symbols(5).is_empty());
+        assert_eq!(schematic.part_numbers().count(), 8);
+    }
+
+    #[test]
+    fn test_gear_rules() {
+        let schematic = Schematic::parse(TEST_INPUT1).unwrap();
+        let rule = GearRule {
+            symbols: vec!['*', '#'],
+            numbers: 2,
+            combine: Combine::Product,
+        };
+        assert_eq!(
+            schematic.gears(&rule).map(Option::unwrap).sum::<u64>(),
+            467835
+        );
+
+        let rule = GearRule {
+            symbols: vec!['*', '#'],
+            numbers: 1,
+            combine: Combine::Sum,
+        };
+        // the `*` next to 617 and the `#` next to 633
+        assert_eq!(
+            schematic.gears(&rule).collect::<Vec<_>>(),
+            [Some(633), Some(617)]
+        );
+
+        let rule = GearRule {
+            symbols: vec!['+', '$'],
+            numbers: 1,
+            combine: Combine::Product,
+        };
+        assert_eq!(
+            schematic.gears(&rule).map(Option::unwrap).sum::<u64>(),
+            592 + 664
+        );
+    }
+
+    #[test]
+    fn test_ragged_lines() {
+        let input = indoc::indoc! {"
+        12.....
+        ..*.
+        3.45#....9
+        *
+        "};
+        let schematic = Schematic::parse(input).unwrap();
+        let parts = schematic
+            .part_numbers()
+            .map(|n| n.value)
+            .collect::<Vec<_>>();
+        assert_eq!(parts, [12, 3, 45]);
+        assert_eq!(solve_part2(input), Ok(12 * 45));
+        assert_eq!(solve_part1("1\n.#"), Ok(1));
+        assert_eq!(solve_part1(""), Ok(0));
+    }
+
+    #[test]
+    fn test_invalid() {
+        // too large for a u64
+        for len in 21..40 {
+            assert!(Schematic::parse(&"1".repeat(len)).is_err());
+            assert!(Schematic::parse(&"9".repeat(len - 1)).is_err());
+        }
+        let long = "1".repeat(40);
+        // the answers overflow
+        assert!(solve_part2("4294967296*4294967296").is_err());
+        let input = format!("{}*.\n{}*.", u64::MAX - 1, u64::MAX - 1);
+        assert!(solve_part1(&input).is_err());
+        assert!(solve_part1(&format!("{long}*")).is_err());
+        assert!(solve_part2(&format!("{long}*2")).is_err());
     }
 }
 
@@ -430,12 +403,12 @@ mod benches {
     #[divan::bench]
     fn part1() {
         let answer = solve_part1(black_box(INPUT));
-        assert_eq!(answer, 535351);
+        assert_eq!(answer, Ok(535351));
     }
 
     #[divan::bench]
     fn part2() {
         let answer = solve_part2(black_box(INPUT));
-        assert_eq!(answer, 87287096);
+        assert_eq!(answer, Ok(87287096));
     }
 }
@@ -1,57 +1,43 @@
+use crate::fastparse;
+use crate::parse;
+
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 4;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day4.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
-    let answer_part1 = solve_part1(input);
+    let answer_part1 = solve_part1(input).unwrap();
     println!("day{DAY}::part1 answer: {}", answer_part1);
 
-    let answer_part2 = solve_part2(input);
+    let answer_part2 = solve_part2(input).unwrap();
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u64 {
+pub(crate) fn solve_part1(input: &str) -> parse::Result<u64> {
     let mut winning_numbers = Vec::new();
 
     let mut sum = 0;
-    for line in input.lines() {
-        let (_, numbers_str) = line.split_once(": ").unwrap();
-        let (winning_numbers_str, numbers_str) = numbers_str.split_once(" | ").unwrap();
-
-        winning_numbers.extend(
-            winning_numbers_str
-                .split(' ')
-                .filter(|s| !s.is_empty())
-                .map(|n| n.parse::<u32>().unwrap()),
-        );
-
-        let mut won_numbers = numbers_str.split(' ').filter(|n| {
-            if n.is_empty() {
-                false
-            } else {
-                let num = n.parse::<u32>().unwrap();
-                winning_numbers.contains(&num)
-            }
-        });
-
-        let prod = won_numbers.next().is_some() as u64;
-        sum += won_numbers.fold(prod, |p, _| p * 2);
-
-        winning_numbers.clear();
+    for line in fastparse::lines(input) {
+        let matches = count_matches(line, &mut winning_numbers)?;
+        sum += matches.checked_sub(1).map_or(0, |m| 1 << m);
     }
 
-    sum
+    Ok(sum)
 }
 
-fn solve_part2(input: &str) -> u64 {
+pub(crate) fn solve_part2(input: &str) -> parse::Result<u64> {
     let mut winning_numbers = Vec::new();
     let mut card_multipliers = Vec::new();
     let mut total_number_of_cards = 0;
 
-    for (card_nr, line) in input.lines().enumerate() {
+    for (card_nr, line) in fastparse::lines(input).enumerate() {
         let current_card_multiplier = match card_multipliers.get_mut(card_nr) {
             Some(m) => {
                 *m += 1;
@@ -66,37 +52,42 @@ fn solve_part2(input: &str) -> u64 {
         // The number of current card's won't change anymore, add to total
         total_number_of_cards += current_card_multiplier;
 
-        let (_, numbers_str) = line.split_once(": ").unwrap();
-        let (winning_numbers_str, numbers_str) = numbers_str.split_once(" | ").unwrap();
-
-        winning_numbers.extend(
-            winning_numbers_str
-                .split(' ')
-                .filter(|s| !s.is_empty())
-                .map(|n| n.parse::<u32>().unwrap()),
-        );
-
-        let won_numbers = numbers_str.split(' ').filter(|n| {
-            if n.is_empty() {
-                false
-            } else {
-                let num = n.parse::<u32>().unwrap();
-                winning_numbers.contains(&num)
-            }
-        });
-
-        for (_, won_card_nr) in won_numbers.zip(card_nr + 1..) {
+        let matches = count_matches(line, &mut winning_numbers)?;
+        for won_card_nr in card_nr + 1..card_nr + 1 + matches {
             match card_multipliers.get_mut(won_card_nr) {
                 Some(m) => *m += current_card_multiplier,
                 None => card_multipliers.push(current_card_multiplier),
             };
         }
+    }
 
-        winning_numbers.clear();
+    Ok(total_number_of_cards)
+}
+
+/// Counts the numbers we have that are winning numbers of the card on `line`
+///
+/// `winning_numbers` is only a buffer, which is reused between the cards.
+fn count_matches(line: &str, winning_numbers: &mut Vec<u32>) -> parse::Result<usize> {
+    let (winning_numbers_str, numbers_str) = parse_card(line)?;
+
+    winning_numbers.clear();
+    for number in fastparse::numbers(winning_numbers_str) {
+        winning_numbers.push(number?);
     }
 
-    total_number_of_cards
+    let mut matches = 0;
+    for number in fastparse::numbers::<u32>(numbers_str) {
+        matches += winning_numbers.contains(&number?) as usize;
+    }
+    Ok(matches)
 }
+
+/// Splits a `Card 1: 41 48 | 83 86` line into the winning numbers and the numbers we have
+fn parse_card(line: &str) -> parse::Result<(&str, &str)> {
+    let (_, numbers) = parse::header(line)?;
+    parse::split_once(numbers, " | ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,13 +104,14 @@ mod tests {
     #[test]
     fn test_part1() {
         let answer = solve_part1(TEST_INPUT1);
-        assert_eq!(answer, 13);
+        assert_eq!(answer, Ok(13));
+        assert!(solve_part1("Card 1: 41 x | 83").is_err());
     }
 
     #[test]
     fn test_part2() {
         let answer = solve_part2(TEST_INPUT1);
-        assert_eq!(answer, 30);
+        assert_eq!(answer, Ok(30));
     }
 }
 
@@ -131,12 +123,12 @@ mod benches {
     #[divan::bench]
     fn part1() {
         let answer = solve_part1(black_box(INPUT));
-        assert_eq!(answer, 21485);
+        assert_eq!(answer, Ok(21485));
     }
 
     #[divan::bench]
     fn part2() {
         let answer = solve_part2(black_box(INPUT));
-        assert_eq!(answer, 11024379);
+        assert_eq!(answer, Ok(11024379));
     }
 }
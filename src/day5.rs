@@ -1,25 +1,30 @@
 use std::ops::Range;
 
-use rangemap::RangeMap;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use crate::fastparse;
+use crate::interval::{OffsetMap, RangeSet};
+use crate::parse;
 
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 5;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day5.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
-    let answer_part1 = solve_part1(input);
+    let answer_part1 = solve_part1(input).unwrap();
     println!("day{DAY}::part1 answer: {}", answer_part1);
 
-    let answer_part2 = solve_part2(input);
+    let answer_part2 = solve_part2(input).unwrap();
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u64 {
-    let (seeds, data) = parse_part1(input);
+pub(crate) fn solve_part1(input: &str) -> parse::Result<u64> {
+    let (seeds, data) = parse_part1(input)?;
 
     let mut min_location = u64::MAX;
     for seed in seeds {
@@ -28,130 +33,157 @@ fn solve_part1(input: &str) -> u64 {
         //print!("{} ", location);
     }
 
-    min_location
+    Ok(min_location)
 }
 
-fn solve_part2(input: &str) -> u64 {
-    let (seeds, data) = parse_part2(input);
+pub(crate) fn solve_part2(input: &str) -> parse::Result<u64> {
+    let (seeds, data) = parse_part2(input)?;
 
-    seeds
-        .into_par_iter()
-        .flat_map(|r| r.into_par_iter())
-        .map(|seed| data.get_location(seed))
+    // Map the seed ranges as a whole, the smallest location is the start of the first range
+    data.get_locations(&seeds.into_iter().collect())
         .min()
-        .unwrap()
+        .ok_or(parse::ParseError::UnexpectedEnd {
+            expected: "seed range",
+        })
 }
 
 #[derive(Debug)]
-struct Almanac {
-    seed_to_soil_map: RangeMap<u64, u64>,
-    soil_to_fertilizer_map: RangeMap<u64, u64>,
-    fertilizer_to_water_map: RangeMap<u64, u64>,
-    water_to_light_map: RangeMap<u64, u64>,
-    light_to_temperature_map: RangeMap<u64, u64>,
-    temperature_to_humidity_map: RangeMap<u64, u64>,
-    humidity_to_location_map: RangeMap<u64, u64>,
+pub struct Almanac {
+    seed_to_soil_map: OffsetMap<u64>,
+    soil_to_fertilizer_map: OffsetMap<u64>,
+    fertilizer_to_water_map: OffsetMap<u64>,
+    water_to_light_map: OffsetMap<u64>,
+    light_to_temperature_map: OffsetMap<u64>,
+    temperature_to_humidity_map: OffsetMap<u64>,
+    humidity_to_location_map: OffsetMap<u64>,
 }
 
 impl Almanac {
-    fn get_location(&self, seed: u64) -> u64 {
-        fn get_mapped_value(map: &RangeMap<u64, u64>, key: u64) -> u64 {
-            let (key_range, next_start) = map
-                .get_key_value(&key)
-                .map(|(a, b)| (a.clone(), *b))
-                .unwrap_or((key..key + 1, key));
-
-            next_start + (key - key_range.start)
-        }
-
-        let soil = get_mapped_value(&self.seed_to_soil_map, seed);
-        let fertilizer = get_mapped_value(&self.soil_to_fertilizer_map, soil);
-        let water = get_mapped_value(&self.fertilizer_to_water_map, fertilizer);
-        let light = get_mapped_value(&self.water_to_light_map, water);
-        let temperature = get_mapped_value(&self.light_to_temperature_map, light);
-        let humidity = get_mapped_value(&self.temperature_to_humidity_map, temperature);
-
-        get_mapped_value(&self.humidity_to_location_map, humidity)
+    /// Names of the categories a seed is mapped through, in order
+    pub const CATEGORIES: [&'static str; 8] = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+
+    pub fn get_location(&self, seed: u64) -> u64 {
+        self.maps().iter().fold(seed, |value, map| map.get(value))
     }
-}
 
-fn parse_part1(input: &str) -> (Vec<u64>, Almanac) {
-    let mut lines = input.lines();
-    (parse_seeds_part1(&mut lines), parse_maps(&mut lines))
-}
+    /// Locations of all the `seeds`
+    pub fn get_locations(&self, seeds: &RangeSet<u64>) -> RangeSet<u64> {
+        let mut values = seeds.clone();
+        for map in self.maps() {
+            values = map.map_set(&values);
+        }
+        values
+    }
 
-fn parse_part2(input: &str) -> (Vec<Range<u64>>, Almanac) {
-    let mut lines = input.lines();
-    (parse_seeds_part2(&mut lines), parse_maps(&mut lines))
-}
+    /// Returns the values of every category for the seed, see [`Almanac::CATEGORIES`]
+    pub fn get_path(&self, seed: u64) -> [u64; 8] {
+        let mut path = [seed; 8];
+        for (i, map) in self.maps().into_iter().enumerate() {
+            path[i + 1] = map.get(path[i]);
+        }
+        path
+    }
 
-fn parse_maps<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Almanac {
-    Almanac {
-        seed_to_soil_map: parse_map(lines),
-        soil_to_fertilizer_map: parse_map(lines),
-        fertilizer_to_water_map: parse_map(lines),
-        water_to_light_map: parse_map(lines),
-        light_to_temperature_map: parse_map(lines),
-        temperature_to_humidity_map: parse_map(lines),
-        humidity_to_location_map: parse_map(lines),
+    fn maps(&self) -> [&OffsetMap<u64>; 7] {
+        [
+            &self.seed_to_soil_map,
+            &self.soil_to_fertilizer_map,
+            &self.fertilizer_to_water_map,
+            &self.water_to_light_map,
+            &self.light_to_temperature_map,
+            &self.temperature_to_humidity_map,
+            &self.humidity_to_location_map,
+        ]
     }
 }
 
-fn parse_seeds_part1<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Vec<u64> {
-    let line = lines.next().expect("expected non empty input");
-    assert!(
-        line.starts_with("seeds: "),
-        "expected line to start with 'seeds: '"
-    );
-    let line = line.trim_start_matches("seeds: ");
-    let result = line.split(' ').map(|s| s.parse().unwrap()).collect();
-    lines.next(); // eat empty line after seeds
-    result
+pub fn parse_part1(input: &str) -> parse::Result<(Vec<u64>, Almanac)> {
+    let mut blocks = parse::blocks(input);
+    let seeds = parse_seeds(blocks.next())?.collect::<parse::Result<_>>()?;
+    Ok((seeds, parse_maps(blocks)?))
 }
 
-fn parse_seeds_part2<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Vec<Range<u64>> {
-    let line = lines.next().expect("expected non empty input");
-    assert!(
-        line.starts_with("seeds: "),
-        "expected line to start with 'seeds: '"
-    );
-    let line = line.trim_start_matches("seeds: ");
-    let mut nums = line.split(' ').map(|s| s.parse::<u64>().unwrap());
+fn parse_part2(input: &str) -> parse::Result<(Vec<Range<u64>>, Almanac)> {
+    let mut blocks = parse::blocks(input);
+    let mut nums = parse_seeds(blocks.next())?;
 
     let mut seeds = Vec::new();
-    loop {
-        match (nums.next(), nums.next()) {
-            (Some(start), Some(len)) => seeds.push(start..start + len),
-            (None, None) => break,
-            _ => panic!("invalid input: {}", line),
-        }
+    while let Some(start) = nums.next() {
+        let start = start?;
+        let len = nums.next().ok_or(parse::ParseError::UnexpectedEnd {
+            expected: "seed range length",
+        })??;
+        let end = start
+            .checked_add(len)
+            .ok_or_else(|| parse::ParseError::InvalidValue {
+                expected: "seed range within u64",
+                input: format!("{start} {len}"),
+            })?;
+        seeds.push(start..end);
     }
 
-    lines.next(); // eat empty line after seeds
-    seeds
+    Ok((seeds, parse_maps(blocks)?))
 }
 
-fn parse_map<'a>(lines: &mut impl Iterator<Item = &'a str>) -> RangeMap<u64, u64> {
-    let mut map = RangeMap::new();
+fn parse_seeds(block: Option<&str>) -> parse::Result<fastparse::Numbers<'_, u64>> {
+    let line = block.ok_or(parse::ParseError::UnexpectedEnd { expected: "seeds" })?;
+    Ok(fastparse::numbers(parse::labeled(line, "seeds")?))
+}
 
-    let line = lines.next().unwrap();
-    assert!(line.ends_with("map:"), "{}", line);
+fn parse_maps<'a>(mut blocks: impl Iterator<Item = &'a str>) -> parse::Result<Almanac> {
+    let mut next_map = || {
+        let block = blocks
+            .next()
+            .ok_or(parse::ParseError::UnexpectedEnd { expected: "map" })?;
+        parse_map(block)
+    };
+
+    Ok(Almanac {
+        seed_to_soil_map: next_map()?,
+        soil_to_fertilizer_map: next_map()?,
+        fertilizer_to_water_map: next_map()?,
+        water_to_light_map: next_map()?,
+        light_to_temperature_map: next_map()?,
+        temperature_to_humidity_map: next_map()?,
+        humidity_to_location_map: next_map()?,
+    })
+}
 
-    while let Some(line) = lines.next() {
-        if line.is_empty() {
-            break;
+fn parse_map(block: &str) -> parse::Result<OffsetMap<u64>> {
+    let mut map = OffsetMap::new();
+
+    let mut lines = fastparse::lines(block);
+    parse::suffix(lines.next().unwrap_or_default(), "map:")?;
+
+    for line in lines {
+        let [dst_start, src_start, len] = parse::exactly(fastparse::numbers::<u64>(line))?;
+        let (Some(src_end), Some(_)) = (src_start.checked_add(len), dst_start.checked_add(len))
+        else {
+            return Err(parse::ParseError::InvalidValue {
+                expected: "map range within u64",
+                input: line.to_string(),
+            });
+        };
+        let source = src_start..src_end;
+        if map.overlaps(&source) {
+            return Err(parse::ParseError::InvalidValue {
+                expected: "non overlapping map range",
+                input: line.to_string(),
+            });
         }
-
-        let mut nums = line.split(' ').map(|s| s.parse::<u64>().unwrap());
-        let dst_start = nums.next().unwrap();
-        let src_start = nums.next().unwrap();
-        let len = nums.next().unwrap();
-        assert!(nums.next().is_none());
-
-        map.insert(src_start..src_start + len, dst_start);
+        map.insert(source, dst_start);
     }
 
-    map
+    Ok(map)
 }
 
 #[cfg(test)]
@@ -196,20 +228,56 @@ mod tests {
 
     #[test]
     fn test_parser() {
-        let answer = parse_part1(TEST_INPUT1);
+        let answer = parse_part1(TEST_INPUT1).unwrap();
         println!("{:#?}", answer);
+
+        // the ranges overflow u64
+        let max = u64::MAX;
+        let input = TEST_INPUT1.replace("79 14", &format!("{max} 14"));
+        assert!(matches!(
+            parse_part2(&input),
+            Err(parse::ParseError::InvalidValue { .. })
+        ));
+        for line in [format!("50 {max} 2"), format!("{max} 50 2")] {
+            let input = TEST_INPUT1.replace("50 98 2", &line);
+            assert!(matches!(
+                parse_part1(&input),
+                Err(parse::ParseError::InvalidValue { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_get_path() {
+        let (_, almanac) = parse_part1(TEST_INPUT1).unwrap();
+        assert_eq!(almanac.get_path(79), [79, 81, 81, 81, 74, 78, 78, 82]);
+        assert_eq!(almanac.get_path(13)[7], almanac.get_location(13));
+    }
+
+    #[test]
+    fn test_get_locations() {
+        let (_, almanac) = parse_part1(TEST_INPUT1).unwrap();
+        let seeds = [79..93, 55..68].into_iter().collect();
+        let locations = almanac.get_locations(&seeds);
+
+        let expected = (79..93)
+            .chain(55..68)
+            .map(|seed| almanac.get_location(seed))
+            .map(|location| location..location + 1)
+            .collect();
+        assert_eq!(locations, expected);
     }
 
     #[test]
     fn test_part1() {
         let answer = solve_part1(TEST_INPUT1);
-        assert_eq!(answer, 35);
+        assert_eq!(answer, Ok(35));
     }
 
     #[test]
     fn test_part2() {
         let answer = solve_part2(TEST_INPUT1);
-        assert_eq!(answer, 46);
+        assert_eq!(answer, Ok(46));
     }
 }
 
@@ -221,12 +289,12 @@ mod benches {
     #[divan::bench]
     fn part1() {
         let answer = solve_part1(black_box(INPUT));
-        assert_eq!(answer, 484023871);
+        assert_eq!(answer, Ok(484023871));
     }
 
-    #[divan::bench(sample_count = 5, sample_size = 1)]
+    #[divan::bench]
     fn part2() {
         let answer = solve_part2(black_box(INPUT));
-        assert_eq!(answer, 46294175);
+        assert_eq!(answer, Ok(46294175));
     }
 }
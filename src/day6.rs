@@ -1,73 +1,96 @@
-use core::num;
+use crate::math;
+use crate::parse;
 
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 6;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day6.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
-    let answer_part1 = solve_part1(input);
+    let answer_part1 = solve_part1(input).unwrap();
     println!("day{DAY}::part1 answer: {}", answer_part1);
 
-    let answer_part2 = solve_part2(input);
+    let answer_part2 = solve_part2(input).unwrap();
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u64 {
-    let races = parse_input_part1(input);
-    races.iter().map(Race::num_ways_to_win).product()
+pub(crate) fn solve_part1(input: &str) -> parse::Result<u64> {
+    let races = parse_input_part1(input)?;
+    Ok(races.iter().map(Race::num_ways_to_win).product())
 }
 
-fn solve_part2(input: &str) -> u64 {
-    let race = parse_input_part2(input);
-    race.num_ways_to_win()
+pub(crate) fn solve_part2(input: &str) -> parse::Result<u64> {
+    let race = parse_input_part2(input)?;
+    Ok(race.num_ways_to_win())
 }
 
-fn parse_input_part1(input: &str) -> Vec<Race> {
-    let (times, dist) = input.split_once('\n').unwrap();
-    let times = times
-        .trim_start_matches("Time:")
-        .split(' ')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(|s| s.trim().parse::<u64>().unwrap());
-    let dist = dist
-        .trim_start_matches("Distance:")
-        .split(' ')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(|s| s.trim().parse::<u64>().unwrap());
-
-    times
-        .zip(dist)
+fn parse_input_part1(input: &str) -> parse::Result<Vec<Race>> {
+    let (times, distances) = parse_lines(input)?;
+    let times = parse::numbers(times).collect::<parse::Result<Vec<_>>>()?;
+    let distances = parse::numbers(distances).collect::<parse::Result<Vec<_>>>()?;
+
+    // every race needs a time and a distance
+    if times.len() != distances.len() {
+        return Err(parse::ParseError::WrongCount {
+            expected: times.len(),
+            found: distances.len(),
+        });
+    }
+
+    Ok(times
+        .into_iter()
+        .zip(distances)
         .map(|(time, distance_record)| Race {
             time,
             distance_record,
         })
-        .collect()
+        .collect())
+}
+
+fn parse_input_part2(input: &str) -> parse::Result<Race> {
+    let (time, distance) = parse_lines(input)?;
+
+    Ok(Race {
+        time: kerned_number(time)?,
+        distance_record: kerned_number(distance)?,
+    })
 }
 
-fn parse_input_part2(input: &str) -> Race {
-    let (times, dist) = input.split_once('\n').unwrap();
-    let times = times
-        .trim_start_matches("Time:")
-        .trim()
-        .replace(' ', "")
-        .parse::<u64>()
-        .unwrap();
-    let dist = dist
-        .trim_start_matches("Distance:")
-        .trim()
-        .replace(' ', "")
-        .parse::<u64>()
-        .unwrap();
-
-    Race {
-        time: times,
-        distance_record: dist,
+/// Parses the digits of `s` as one number, ignoring the spaces between them
+fn kerned_number(s: &str) -> parse::Result<u64> {
+    let invalid = |reason: &str| parse::ParseError::InvalidNumber {
+        input: s.trim().to_string(),
+        reason: reason.to_string(),
+    };
+
+    let mut digits = s.bytes().filter(|&b| b != b' ').peekable();
+    if digits.peek().is_none() {
+        return Err(invalid("cannot parse integer from empty string"));
     }
+    digits.try_fold(0u64, |number, b| {
+        let digit = (b as char)
+            .to_digit(10)
+            .ok_or_else(|| invalid("invalid digit found in string"))?;
+        number
+            .checked_mul(10)
+            .and_then(|n| n.checked_add(digit as u64))
+            .ok_or_else(|| invalid("number too large to fit in target type"))
+    })
+}
+
+/// Returns the values of the `Time:` and `Distance:` lines
+fn parse_lines(input: &str) -> parse::Result<(&str, &str)> {
+    let (times, distances) = parse::split_once(input, "\n")?;
+    Ok((
+        parse::labeled(times, "Time")?,
+        parse::labeled(distances, "Distance")?,
+    ))
 }
 
 struct Race {
@@ -84,45 +107,48 @@ impl Race {
         hold_button_time * (self.time - hold_button_time)
     }
 
-    /// Calculate the minimum and maximum time that the button was held for distance record
-    fn record_hold_times(&self) -> (f64, f64) {
+    /// Calculate the minimum and maximum time that the button was held for distance record,
+    /// rounded outwards to whole milliseconds
+    fn record_hold_times(&self) -> (u64, u64) {
         // distance is quadratic formula in button hold time
         // ht^2 - ht * t + d = 0
-        // hence ht = t/2 +- sqrt( t^2/4 - d )
+        // hence ht = (t +- sqrt(t^2 - 4d)) / 2
+        // If the record cannot be reached, both are t/2.
 
-        let sqrt = (self.time * self.time / 4 - self.distance_record) as f64;
-        let sqrt = sqrt.sqrt();
+        let time = self.time as u128;
+        let discriminant = (time * time).saturating_sub(4 * self.distance_record as u128);
+        let sqrt = math::isqrt_wide(discriminant);
 
-        let half_time = self.time as f64 / 2.0;
-        (half_time - sqrt, half_time + sqrt)
+        (((time - sqrt) / 2) as u64, (time + sqrt).div_ceil(2) as u64)
     }
 
     /// Number of ways to win
     fn num_ways_to_win(&self) -> u64 {
-        let (record_min, record_max) = self.record_hold_times();
-
-        let min_to_win = record_min.ceil() as u64;
-        let max_to_win = record_max.floor() as u64;
-
-        // every step between min and max can win
-        // however min and max themselves can only win if they don't result in
-        // exactly the record distance
-        let mut num_ways = max_to_win - min_to_win + 1;
+        // every step between the record hold times can win,
+        // however the rounded hold times themselves can only win if they result in more than the record distance
+        let (mut min_to_win, mut max_to_win) = self.record_hold_times();
 
-        if self.race_distance(min_to_win) == self.distance_record {
-            num_ways -= 1;
+        while min_to_win <= max_to_win && self.race_distance(min_to_win) <= self.distance_record {
+            min_to_win += 1;
         }
 
-        if self.race_distance(max_to_win) == self.distance_record {
-            num_ways -= 1;
+        // min_to_win wins at this point, so max_to_win stops at it at the latest
+        while min_to_win < max_to_win && self.race_distance(max_to_win) <= self.distance_record {
+            max_to_win -= 1;
         }
 
-        num_ways
+        if min_to_win <= max_to_win {
+            max_to_win - min_to_win + 1
+        } else {
+            0
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     const TEST_INPUT1: &str = indoc::indoc! {"
@@ -132,7 +158,7 @@ mod tests {
 
     #[test]
     fn dbg_print_results() {
-        let races = parse_input_part1(TEST_INPUT1);
+        let races = parse_input_part1(TEST_INPUT1).unwrap();
 
         for race in races {
             println!(
@@ -154,13 +180,54 @@ mod tests {
     #[test]
     fn test_part1() {
         let answer = solve_part1(TEST_INPUT1);
-        assert_eq!(answer, 288);
+        assert_eq!(answer, Ok(288));
+
+        let answer = solve_part1("Time: 7 15 30\nDistance: 9 40\n");
+        assert_eq!(
+            answer,
+            Err(parse::ParseError::WrongCount {
+                expected: 3,
+                found: 2
+            })
+        );
     }
 
     #[test]
     fn test_part2() {
         let answer = solve_part2(TEST_INPUT1);
-        assert_eq!(answer, 71503);
+        assert_eq!(answer, Ok(71503));
+    }
+
+    #[test]
+    fn test_kerned_number() {
+        assert_eq!(kerned_number("  7  15   30"), Ok(71530));
+        assert_eq!(kerned_number("42"), Ok(42));
+        assert!(matches!(
+            kerned_number("7 1x"),
+            Err(parse::ParseError::InvalidNumber { input, .. }) if input == "7 1x"
+        ));
+        assert!(kerned_number("   ").is_err());
+        assert!(kerned_number("99999 99999 99999 99999 9").is_err());
+    }
+
+    fn num_ways_to_win_brute_force(race: &Race) -> u64 {
+        (0..=race.time)
+            .filter(|&t| race.race_distance(t) > race.distance_record)
+            .count() as u64
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_num_ways_to_win(
+            (time, distance_record) in (0..100_000u64).prop_flat_map(|t| {
+                // the best possible distance is t * t / 4, records close to it are the interesting ones
+                let best = t * t / 4;
+                (Just(t), prop_oneof![0..=best, best.saturating_sub(2)..=best])
+            })
+        ) {
+            let race = Race { time, distance_record };
+            prop_assert_eq!(race.num_ways_to_win(), num_ways_to_win_brute_force(&race));
+        }
     }
 }
 
@@ -172,12 +239,12 @@ mod benches {
     #[divan::bench]
     fn part1() {
         let answer = solve_part1(black_box(INPUT));
-        assert_eq!(answer, 800280)
+        assert_eq!(answer, Ok(800280))
     }
 
     #[divan::bench]
     fn part2() {
         let answer = solve_part2(black_box(INPUT));
-        assert_eq!(answer, 45128024);
+        assert_eq!(answer, Ok(45128024));
     }
 }
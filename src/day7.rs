@@ -1,12 +1,16 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeSet;
 
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 7;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day7.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
     let answer_part1 = solve_part1(input);
     println!("day{DAY}::part1 answer: {}", answer_part1);
@@ -15,7 +19,7 @@ fn solve(input: &str) {
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u64 {
+pub(crate) fn solve_part1(input: &str) -> u64 {
     let hands = read_hands_part1(input);
 
     hands
@@ -26,7 +30,7 @@ fn solve_part1(input: &str) -> u64 {
         .sum()
 }
 
-fn solve_part2(input: &str) -> u64 {
+pub(crate) fn solve_part2(input: &str) -> u64 {
     let hands = read_hands_part2(input);
 
     hands
@@ -39,10 +43,10 @@ fn solve_part2(input: &str) -> u64 {
 
 /// Hand which is ordered by it's strength for part1 rules
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct HandPart1<'a> {
-    cards: &'a str,
-    kind: HandKind,
-    bid: u64,
+pub struct HandPart1<'a> {
+    pub cards: &'a str,
+    pub kind: HandKind,
+    pub bid: u64,
 }
 
 impl<'a> PartialOrd for HandPart1<'a> {
@@ -90,10 +94,10 @@ impl<'a> Ord for HandPart1<'a> {
 
 /// Hand which is ordered by it's strength for part2 rules
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct HandPart2<'a> {
-    cards: &'a str,
-    kind: HandKind,
-    bid: u64,
+pub struct HandPart2<'a> {
+    pub cards: &'a str,
+    pub kind: HandKind,
+    pub bid: u64,
 }
 
 impl<'a> PartialOrd for HandPart2<'a> {
@@ -140,7 +144,7 @@ impl<'a> Ord for HandPart2<'a> {
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
-enum HandKind {
+pub enum HandKind {
     FiveOfAKind,
     FourOfAKind,
     FullHouse,
@@ -151,7 +155,7 @@ enum HandKind {
 }
 
 impl HandKind {
-    fn from_hand_part1(hand: &str) -> Self {
+    pub fn from_hand_part1(hand: &str) -> Self {
         // Since we have max 5 cards, don't bother with map as it's faster to search the array
         let mut counts = [('0', 0); 5];
         for (i, char) in hand.chars().enumerate() {
@@ -181,7 +185,7 @@ impl HandKind {
         kind
     }
 
-    fn from_hand_part2(hand: &str) -> Self {
+    pub fn from_hand_part2(hand: &str) -> Self {
         // Since we have max 5 cards, don't bother with map as it's faster to search
         let mut counts = [('0', 0); 5];
         let mut jcount = 0;
@@ -272,7 +276,7 @@ impl HandKind {
     }
 }
 
-fn read_hands_part1(input: &str) -> BTreeSet<HandPart1<'_>> {
+pub fn read_hands_part1(input: &str) -> BTreeSet<HandPart1<'_>> {
     let mut set = BTreeSet::new();
 
     for line in input.lines() {
@@ -287,7 +291,7 @@ fn read_hands_part1(input: &str) -> BTreeSet<HandPart1<'_>> {
     set
 }
 
-fn read_hands_part2(input: &str) -> BTreeSet<HandPart2<'_>> {
+pub fn read_hands_part2(input: &str) -> BTreeSet<HandPart2<'_>> {
     let mut set = BTreeSet::new();
 
     for line in input.lines() {
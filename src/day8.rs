@@ -1,137 +1,367 @@
 use std::collections::HashMap;
+use std::hash::Hash;
 
+use crate::cycle::{self, Cycle};
+use crate::graph::Graph;
+use crate::intern;
+use crate::math;
+use crate::parse;
+
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 8;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day8.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
-    let answer_part1 = solve_part1(input);
+    let answer_part1 = solve_part1(input).unwrap();
     println!("day{DAY}::part1 answer: {}", answer_part1);
 
-    let answer_part2 = solve_part2(input);
+    let answer_part2 = solve_part2(input).unwrap();
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
-fn solve_part1(input: &str) -> u64 {
-    let (mut lr, elements) = parse_part1(input);
+pub(crate) fn solve_part1(input: &str) -> parse::Result<u64> {
+    let network = Network::parse(input)?;
+    let end = network.node("ZZZ")?;
+
+    let mut state = (network.node("AAA")?, 0);
+    // after visiting every state once the walk repeats without ever reaching `ZZZ`
+    let states = network.defined.iter().filter(|&&d| d).count() * network.lr.len();
+    for steps in 1..=states as u64 {
+        state = network.next(&state);
+        if state.0 == end {
+            return Ok(steps);
+        }
+    }
+    Err(unreachable_end())
+}
+
+/// Same as [`solve_part1`] but looks up the nodes by their names in a `HashMap`
+pub(crate) fn solve_part1_hashmap(input: &str) -> parse::Result<u64> {
+    let (mut lr, elements) = parse_part1(input)?;
 
     let mut key = "AAA";
-    let mut steps = 0;
-    loop {
-        key = elements.get(key).unwrap()[lr.next()];
-        steps += 1;
+    for steps in 1..=(elements.len() * lr.len()) as u64 {
+        key = elements.get(key).ok_or_else(|| undefined_node(key))?[lr.next()];
         if key == "ZZZ" {
-            break;
+            return Ok(steps);
         }
     }
-    steps
+    Err(unreachable_end())
 }
 
-fn solve_part2(input: &str) -> u64 {
-    let (lr, elements, nodes) = parse_part2(input);
+pub(crate) fn solve_part2(input: &str) -> parse::Result<u64> {
+    let network = Network::parse(input)?;
+    Ok(solve_part2_core(
+        &network.starts,
+        |state| network.next(state),
+        is_end,
+        find_cycle_hashed,
+    ))
+}
 
-    // Looks like for each starting position an end position is reached in a cycle.
-    let mut periods = nodes.iter().map(|mut node| {
-        let mut steps = 0u64;
-        let mut lr = lr.clone();
+/// Same as [`solve_part2`] but finds the cycles with Brent's algorithm, which doesn't keep the visited nodes
+pub(crate) fn solve_part2_brent(input: &str) -> parse::Result<u64> {
+    let network = Network::parse(input)?;
+    Ok(solve_part2_core(
+        &network.starts,
+        |state| network.next(state),
+        is_end,
+        find_cycle_brent,
+    ))
+}
 
-        loop {
-            steps += 1;
-            node = &elements.get(node).unwrap()[lr.next()];
+/// Same as [`solve_part2`] but looks up the nodes by their names in a `HashMap`
+pub(crate) fn solve_part2_hashmap(input: &str) -> parse::Result<u64> {
+    let (lr, elements, starts) = parse_part2(input)?;
+    Ok(solve_part2_core(
+        &starts,
+        |&(node, i)| (elements[node][lr.get(i)], (i + 1) % lr.len()),
+        |node| node.ends_with('Z'),
+        find_cycle_hashed,
+    ))
+}
+
+/// The state of a ghost is its node and the position in the instructions
+type State<N> = (N, usize);
+
+/// Returns the cycle of the states and the nodes of the first `prefix + period` states
+type FindCycle<N> = fn(State<N>, &dyn Fn(&State<N>) -> State<N>) -> (Cycle, Vec<N>);
+
+fn find_cycle_hashed<N: Copy + Hash + Eq>(
+    start: State<N>,
+    next: &dyn Fn(&State<N>) -> State<N>,
+) -> (Cycle, Vec<N>) {
+    let (cycle, states) = cycle::hashed(start, next);
+    (cycle, states.into_iter().map(|(node, _)| node).collect())
+}
+
+fn find_cycle_brent<N: Copy + Eq>(
+    start: State<N>,
+    next: &dyn Fn(&State<N>) -> State<N>,
+) -> (Cycle, Vec<N>) {
+    let cycle = cycle::brent(start, next);
+
+    let mut state = start;
+    let mut nodes = vec![state.0];
+    for _ in 1..cycle.prefix + cycle.period {
+        state = next(&state);
+        nodes.push(state.0);
+    }
+    (cycle, nodes)
+}
+
+fn solve_part2_core<N: Copy>(
+    starts: &[N],
+    next: impl Fn(&State<N>) -> State<N>,
+    is_end: impl Fn(N) -> bool,
+    find_cycle: FindCycle<N>,
+) -> u64 {
+    // Every ghost ends up in a cycle. If a ghost reaches the end nodes on every interval after its first end,
+    // it is on an end node at the steps `x ≡ first (mod interval)` from then on.
+    let mut congruences = Vec::new();
+    let mut last_first_end = 0;
+    for &node in starts {
+        let (cycle, nodes) = find_cycle((node, 0), &next);
+        let (interval, first) = end_interval(cycle, &nodes, &is_end);
+        congruences.push((first, interval));
+        last_first_end = last_first_end.max(first);
+    }
+
+    let (residue, modulus) =
+        math::crt(congruences).expect("ghosts never reach the end nodes together");
+
+    // The solution of the congruences could be before the last ghost reaches its first end
+    let offset = residue + modulus - last_first_end % modulus;
+    last_first_end + offset % modulus
+}
+
+/// Returns the interval at which the ghost reaches the end nodes and the first step it does so
+///
+/// `nodes` are the nodes of the first `cycle.prefix + cycle.period` steps.
+///
+/// # Panics
+/// If the steps at which the end nodes are reached aren't a common interval apart.
+fn end_interval<N: Copy>(cycle: Cycle, nodes: &[N], is_end: impl Fn(N) -> bool) -> (u64, u64) {
+    let ends = (1..nodes.len() + 1)
+        .filter(|&step| is_end(nodes[cycle.reduce(step)]))
+        .collect::<Vec<_>>();
+
+    let (&first, rest) = ends.split_first().expect("ghost never reaches an end node");
+    let interval = rest.first().map_or(cycle.period, |&second| second - first);
+    let regular = ends.windows(2).all(|w| w[1] - w[0] == interval)
+        && cycle.period.is_multiple_of(interval)
+        // the interval continues into the next repetition of the cycle
+        && is_end(nodes[cycle.reduce(ends[ends.len() - 1] + interval)]);
+    assert!(
+        regular,
+        "ghost doesn't reach the end nodes at a regular interval"
+    );
+
+    (interval as u64, first as u64)
+}
+
+/// The network with the nodes indexed by their [`intern::code`]
+struct Network {
+    lr: LR,
+    /// Left and right node of every node, the nodes which aren't in the input lead to node 0
+    nodes: Vec<[u16; 2]>,
+    /// Which nodes have a line in the input
+    defined: Vec<bool>,
+    starts: Vec<u16>,
+}
 
-            if node.ends_with('Z') {
-                break;
+impl Network {
+    fn parse(input: &str) -> parse::Result<Self> {
+        let (instructions, elements) = parse::split_once(input, "\n")?;
+        let mut network = Network {
+            lr: LR::from_lr_sequence(instructions.trim())?,
+            nodes: vec![[0; 2]; intern::CODES],
+            defined: vec![false; intern::CODES],
+            starts: Vec::new(),
+        };
+
+        let mut targets = Vec::new();
+        for line in elements.lines().filter(|line| !line.is_empty()) {
+            let (key, [left, right]) = parse_element(line)?;
+            let key = node_code(key)?;
+            network.nodes[key as usize] = [node_code(left)?, node_code(right)?];
+            network.defined[key as usize] = true;
+            targets.extend([left, right]);
+            if intern::decode(key)[2] == b'A' {
+                network.starts.push(key);
             }
         }
 
-        steps
-    });
+        // an undefined node would lead to node 0 instead
+        match targets.into_iter().find(|&t| network.node(t).is_err()) {
+            Some(undefined) => Err(undefined_node(undefined)),
+            None => Ok(network),
+        }
+    }
+
+    /// Code of the node `name`, which must have a line in the input
+    fn node(&self, name: &str) -> parse::Result<u16> {
+        let code = node_code(name)?;
+        if self.defined[code as usize] {
+            Ok(code)
+        } else {
+            Err(undefined_node(name))
+        }
+    }
+
+    fn next(&self, &(node, i): &State<u16>) -> State<u16> {
+        (
+            self.nodes[node as usize][self.lr.get(i)],
+            (i + 1) % self.lr.len(),
+        )
+    }
+}
+
+fn is_end(node: u16) -> bool {
+    intern::decode(node)[2] == b'Z'
+}
 
-    let mut lcm = periods.next().unwrap();
-    for p in periods {
-        lcm = num::integer::lcm(lcm, p);
+fn undefined_node(name: &str) -> parse::ParseError {
+    parse::ParseError::InvalidValue {
+        expected: "defined node",
+        input: name.to_string(),
     }
+}
+
+fn unreachable_end() -> parse::ParseError {
+    parse::ParseError::InvalidValue {
+        expected: "node reachable from `AAA`",
+        input: "ZZZ".to_string(),
+    }
+}
 
-    lcm
+fn node_code(name: &str) -> parse::Result<u16> {
+    intern::code(name).ok_or_else(|| parse::ParseError::InvalidValue {
+        expected: "node name",
+        input: name.to_string(),
+    })
 }
 
 #[derive(Debug, Clone)]
 struct LR {
-    iter: std::iter::Cycle<std::vec::IntoIter<usize>>,
+    sequence: Vec<usize>,
+    position: usize,
 }
 
 impl LR {
-    fn from_lr_sequence(input: &str) -> Self {
-        let sequence = input.chars().map(|c| match c {
-            'L' => 0,
-            'R' => 1,
-            _ => unreachable!(),
-        });
-        LR {
-            iter: sequence.collect::<Vec<_>>().into_iter().cycle(),
+    fn from_lr_sequence(input: &str) -> parse::Result<Self> {
+        let sequence = input
+            .chars()
+            .map(|c| match c {
+                'L' => Ok(0),
+                'R' => Ok(1),
+                _ => Err(parse::ParseError::InvalidValue {
+                    expected: "instruction",
+                    input: c.to_string(),
+                }),
+            })
+            .collect::<parse::Result<Vec<_>>>()?;
+        if sequence.is_empty() {
+            return Err(parse::ParseError::UnexpectedEnd {
+                expected: "instructions",
+            });
         }
+
+        Ok(LR {
+            sequence,
+            position: 0,
+        })
     }
 
     fn next(&mut self) -> usize {
-        self.iter.next().unwrap()
+        let lr = self.sequence[self.position];
+        self.position = (self.position + 1) % self.sequence.len();
+        lr
+    }
+
+    /// Instruction at `position`
+    fn get(&self, position: usize) -> usize {
+        self.sequence[position]
+    }
+
+    fn len(&self) -> usize {
+        self.sequence.len()
     }
 }
 
-fn parse_part1(input: &str) -> (LR, HashMap<&str, [&str; 2]>) {
+/// Left and right node of every node by their names
+type Elements<'a> = HashMap<&'a str, [&'a str; 2]>;
+
+fn parse_part1(input: &str) -> parse::Result<(LR, Elements<'_>)> {
     let mut lines = input.lines();
 
-    let lr = LR::from_lr_sequence(lines.next().unwrap());
+    let lr = LR::from_lr_sequence(lines.next().unwrap_or_default())?;
     lines.next(); // eat empty line after LR sequence
 
     let mut elements = HashMap::new();
 
     for line in lines {
-        let (key, dst) = line.split_once(" = ").unwrap();
-        let (left, right) = dst
-            .trim_start_matches('(')
-            .trim_end_matches(')')
-            .split_once(", ")
-            .unwrap();
-        elements.insert(key, [left, right]);
+        let (key, element) = parse_element(line)?;
+        elements.insert(key, element);
     }
 
-    (lr, elements)
+    Ok((lr, elements))
 }
 
-fn parse_part2(input: &str) -> (LR, HashMap<&str, [&str; 2]>, Vec<&str>) {
+fn parse_part2(input: &str) -> parse::Result<(LR, Elements<'_>, Vec<&str>)> {
     let mut lines = input.lines();
 
-    let lr = LR::from_lr_sequence(lines.next().unwrap());
+    let lr = LR::from_lr_sequence(lines.next().unwrap_or_default())?;
     lines.next(); // eat empty line after LR sequence
 
     let mut elements = HashMap::new();
     let mut start_elements = Vec::new();
 
     for line in lines {
-        let (key, dst) = line.split_once(" = ").unwrap();
-        let (left, right) = dst
-            .trim_start_matches('(')
-            .trim_end_matches(')')
-            .split_once(", ")
-            .unwrap();
-        elements.insert(key, [left, right]);
+        let (key, element) = parse_element(line)?;
+        elements.insert(key, element);
 
         if key.ends_with('A') {
             start_elements.push(key);
         }
     }
 
-    (lr, elements, start_elements)
+    Ok((lr, elements, start_elements))
+}
+
+/// The network with an edge for each instruction, labelled `L` or `R`
+pub fn graph(input: &str) -> parse::Result<Graph<&str, char>> {
+    let mut graph = Graph::new();
+    for line in input.lines().skip(2) {
+        let (key, [left, right]) = parse_element(line)?;
+        graph.add_edge(key, left, 'L');
+        graph.add_edge(key, right, 'R');
+    }
+    Ok(graph)
+}
+
+/// Parses a `AAA = (BBB, CCC)` line
+fn parse_element(line: &str) -> parse::Result<(&str, [&str; 2])> {
+    let (key, dst) = parse::assignment(line)?;
+    let (left, right) = parse::pair(dst)?;
+    Ok((key, [left, right]))
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
 
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::generate;
 
     const TEST_INPUT1: &str = indoc::indoc! {"
     RL
@@ -166,39 +396,97 @@ mod tests {
     XXX = (XXX, XXX)
     "};
 
+    /// The ghosts reach their first ends at steps which aren't multiples of their intervals
+    const TEST_INPUT4: &str = indoc::indoc! {"
+    L
+
+    11A = (11Z, 11Z)
+    11Z = (11B, 11B)
+    11B = (11Z, 11Z)
+    22A = (22B, 22B)
+    22B = (22Z, 22Z)
+    22Z = (22C, 22C)
+    22C = (22D, 22D)
+    22D = (22Z, 22Z)
+    "};
+
     #[test]
     fn test_parse() {
-        let (mut lr, _) = parse_part1(TEST_INPUT2);
+        let (mut lr, _) = parse_part1(TEST_INPUT2).unwrap();
 
         for _ in 0..10 {
             println!("{:?}", lr.next());
         }
+
+        let network = Network::parse(TEST_INPUT3).unwrap();
+        assert_eq!(network.starts.len(), 2);
+        assert!(matches!(
+            Network::parse("L\n\naaa = (BBB, CCC)\n"),
+            Err(parse::ParseError::InvalidValue { input, .. }) if input == "aaa"
+        ));
+        assert!(matches!(
+            Network::parse("L\n\nAAA = (BBB, AAA)\n"),
+            Err(parse::ParseError::InvalidValue { input, .. }) if input == "BBB"
+        ));
+
+        // the nodes of part 1 are only needed there
+        let network = Network::parse("L\n\nBBB = (BBB, BBB)\n").unwrap();
+        assert!(network.node("BBB").is_ok());
+        assert!(matches!(
+            network.node("AAA"),
+            Err(parse::ParseError::InvalidValue { input, .. }) if input == "AAA"
+        ));
+        assert!(network.node("ZZZ").is_err());
+
+        assert!(matches!(
+            Network::parse("LX\n\nAAA = (AAA, AAA)\n"),
+            Err(parse::ParseError::InvalidValue { input, .. }) if input == "X"
+        ));
+        assert!(Network::parse("\n\nAAA = (AAA, AAA)\n").is_err());
+        assert!(parse_part1("").is_err());
     }
 
     #[test]
     fn test_part1() {
         let answer = solve_part1(TEST_INPUT1);
-        assert_eq!(answer, 2);
+        assert_eq!(answer, Ok(2));
 
         let answer = solve_part1(TEST_INPUT2);
-        assert_eq!(answer, 6);
+        assert_eq!(answer, Ok(6));
+
+        // `ZZZ` can't be reached from `AAA`
+        let input = "LR\n\nAAA = (AAA, BBB)\nBBB = (AAA, AAA)\nZZZ = (AAA, ZZZ)\n";
+        assert!(solve_part1(input).is_err());
+
+        for input in [TEST_INPUT1, TEST_INPUT2, input] {
+            assert_eq!(solve_part1_hashmap(input), solve_part1(input));
+        }
     }
 
     #[test]
     fn test_part2() {
         let answer = solve_part2(TEST_INPUT1);
-        assert_eq!(answer, 2);
+        assert_eq!(answer, Ok(2));
 
         let answer = solve_part2(TEST_INPUT2);
-        assert_eq!(answer, 6);
+        assert_eq!(answer, Ok(6));
 
         let answer = solve_part2(TEST_INPUT3);
-        assert_eq!(answer, 6);
+        assert_eq!(answer, Ok(6));
+
+        let answer = solve_part2(TEST_INPUT4);
+        assert_eq!(answer, Ok(5));
+        assert_eq!(answer, Ok(solve_part2_brute_force(TEST_INPUT4)));
+
+        for input in [TEST_INPUT1, TEST_INPUT2, TEST_INPUT3, TEST_INPUT4] {
+            assert_eq!(solve_part2_brent(input), solve_part2(input));
+            assert_eq!(solve_part2_hashmap(input), solve_part2(input));
+        }
     }
 
     #[test]
     fn periods() {
-        let (mut lr, elements, mut nodes) = parse_part2(INPUT);
+        let (mut lr, elements, mut nodes) = parse_part2(INPUT).unwrap();
 
         // Looks like for each starting position an end position is reached in a cycle.
         let orig_nodes = nodes.clone();
@@ -221,6 +509,70 @@ mod tests {
 
         println!("{:?}", periods);
     }
+
+    #[test]
+    fn test_graph() {
+        let graph = graph(TEST_INPUT3).unwrap();
+        assert_eq!(graph.len(), 8);
+        assert_eq!(graph.edge_count(), 16);
+
+        // The ghosts walk in separate parts of the network, which only share the dead end
+        let ghosts = graph
+            .strongly_connected_components()
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| {
+                component
+                    .into_iter()
+                    .map(|id| *graph.node(id))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ghosts, [vec!["11B", "11Z"], vec!["22B", "22C", "22Z"]]);
+    }
+
+    #[test]
+    fn ghost_networks() {
+        let graph = graph(INPUT).unwrap();
+
+        // Every ghost has its own part of the network with a single end node
+        for component in graph.components() {
+            let starts = component
+                .iter()
+                .filter(|&&id| graph.node(id).ends_with('A'));
+            let ends = component
+                .iter()
+                .filter(|&&id| graph.node(id).ends_with('Z'));
+            assert_eq!((starts.count(), ends.count()), (1, 1));
+        }
+    }
+
+    /// Moves all the ghosts at the same time until they are all at an end node
+    fn solve_part2_brute_force(input: &str) -> u64 {
+        let (mut lr, elements, mut nodes) = parse_part2(input).unwrap();
+
+        let mut steps = 0;
+        while !nodes.iter().all(|node| node.ends_with('Z')) {
+            let lr_index = lr.next();
+            for node in nodes.iter_mut() {
+                *node = elements.get(node).unwrap()[lr_index];
+            }
+            steps += 1;
+        }
+        steps
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_part2_against_brute_force(
+            seed in any::<u64>(),
+            instructions in "[LR]{1,10}",
+            periods in prop::collection::vec(2..12usize, 1..5),
+        ) {
+            let input = generate::ghost_network(&mut generate::Rng::new(seed), &instructions, &periods);
+            prop_assert_eq!(solve_part2(&input), Ok(solve_part2_brute_force(&input)));
+        }
+    }
 }
 
 #[cfg(feature = "divan")]
@@ -231,12 +583,12 @@ mod benches {
     #[divan::bench]
     fn part1() {
         let answer = solve_part1(black_box(INPUT));
-        assert_eq!(answer, 15_989);
+        assert_eq!(answer, Ok(15_989));
     }
 
     #[divan::bench]
     fn part2() {
         let answer = solve_part2(black_box(INPUT));
-        assert_eq!(answer, 13_830_919_117_339);
+        assert_eq!(answer, Ok(13_830_919_117_339));
     }
 }
@@ -1,15 +1,23 @@
+use crate::fastparse;
+use crate::math;
+use crate::parse;
+
+#[cfg(not(target_arch = "wasm32"))]
 const DAY: u8 = 9;
+#[cfg(not(target_arch = "wasm32"))]
 pub const INPUT: &str = include_str!("../inputs/day9.txt");
 
+#[cfg(not(target_arch = "wasm32"))]
 pub fn run() {
     solve(INPUT);
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 fn solve(input: &str) {
-    let answer_part1 = solve_part1(input);
+    let answer_part1 = solve_part1(input).unwrap();
     println!("day{DAY}::part1 answer: {}", answer_part1);
 
-    let answer_part2 = solve_part2(input);
+    let answer_part2 = solve_part2(input).unwrap();
     println!("day{DAY}::part2 answer: {}", answer_part2);
 }
 
@@ -19,11 +27,10 @@ fn solve(input: &str) {
 //        2   2   2   2
 //          0   0   0
 
-fn solve_part1(input: &str) -> i64 {
-    input
-        .lines()
+pub(crate) fn solve_part1(input: &str) -> parse::Result<i64> {
+    fastparse::lines(input)
         .map(parse_line)
-        .map(|d| calc_p1(&d))
+        .map(|values| Ok(math::extrapolate_next(&values?)))
         .sum()
 }
 
@@ -33,38 +40,31 @@ fn solve_part1(input: &str) -> i64 {
 //       2   2   2   2
 //         0   0   0
 
-fn solve_part2(input: &str) -> i64 {
-    input
-        .lines()
+pub(crate) fn solve_part2(input: &str) -> parse::Result<i64> {
+    fastparse::lines(input)
         .map(parse_line)
-        .map(|d| calc_p2(&d))
+        .map(|values| Ok(math::extrapolate_prev(&values?)))
         .sum()
 }
 
-fn calc_p1(diffs: &[i64]) -> i64 {
-    let next_diffs = calc_diffs(diffs);
-    if next_diffs.iter().all(|v| v == &0) {
-        *diffs.last().unwrap()
-    } else {
-        diffs.last().unwrap() + calc_p1(&next_diffs)
-    }
-}
-
-fn calc_p2(diffs: &[i64]) -> i64 {
-    let next_diffs = calc_diffs(diffs);
-    if next_diffs.iter().all(|v| v == &0) {
-        *diffs.first().unwrap()
-    } else {
-        diffs.first().unwrap() - calc_p2(&next_diffs)
-    }
+/// Same as [`solve_part1`] but evaluates the interpolating polynomial after the last value
+pub(crate) fn solve_part1_lagrange(input: &str) -> parse::Result<i64> {
+    fastparse::lines(input)
+        .map(parse_line)
+        .map(|values| values.map(|v| math::lagrange(&v, v.len() as i64)))
+        .sum()
 }
 
-fn calc_diffs(diffs: &[i64]) -> Vec<i64> {
-    diffs.windows(2).map(|w| w[1] - w[0]).collect()
+/// Same as [`solve_part2`] but evaluates the interpolating polynomial before the first value
+pub(crate) fn solve_part2_lagrange(input: &str) -> parse::Result<i64> {
+    fastparse::lines(input)
+        .map(parse_line)
+        .map(|values| Ok(math::lagrange(&values?, -1)))
+        .sum()
 }
 
-fn parse_line(line: &str) -> Vec<i64> {
-    line.split(' ').map(|s| s.parse().unwrap()).collect()
+fn parse_line(line: &str) -> parse::Result<Vec<i64>> {
+    fastparse::numbers(line).collect()
 }
 
 #[cfg(test)]
@@ -80,13 +80,16 @@ mod tests {
     #[test]
     fn test_part1() {
         let answer = solve_part1(TEST_INPUT1);
-        assert_eq!(answer, 114);
+        assert_eq!(answer, Ok(114));
+        assert_eq!(solve_part1_lagrange(TEST_INPUT1), Ok(114));
+        assert!(solve_part1("0 3 x").is_err());
     }
 
     #[test]
     fn test_part2() {
         let answer = solve_part2(TEST_INPUT1);
-        assert_eq!(answer, 2);
+        assert_eq!(answer, Ok(2));
+        assert_eq!(solve_part2_lagrange(TEST_INPUT1), Ok(2));
     }
 }
 
@@ -98,12 +101,12 @@ mod benches {
     #[divan::bench]
     fn part1() {
         let answer = solve_part1(black_box(INPUT));
-        assert_eq!(answer, 1887980197);
+        assert_eq!(answer, Ok(1887980197));
     }
 
     #[divan::bench]
     fn part2() {
         let answer = solve_part2(black_box(INPUT));
-        assert_eq!(answer, 990);
+        assert_eq!(answer, Ok(990));
     }
 }
@@ -0,0 +1,405 @@
+//! Registry of all the days that have a solution.
+//!
+//! Allows to pick a day and a part at runtime, for example from the command line or over http.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::cell::Cell;
+#[cfg(not(target_arch = "wasm32"))]
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Once;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+use crate::*;
+
+/// Returns the answer or why the input couldn't be parsed
+pub type Solver = fn(&str) -> Result<String, String>;
+
+/// Values returned by the `solve_part` functions
+pub trait Answer {
+    fn into_answer(self) -> Result<String, String>;
+}
+
+macro_rules! impl_answer {
+    ($($t:ty),*) => {
+        $(
+            impl Answer for $t {
+                fn into_answer(self) -> Result<String, String> {
+                    Ok(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_answer!(u32, u64, i64, usize, String);
+
+impl<T: Answer> Answer for parse::Result<T> {
+    fn into_answer(self) -> Result<String, String> {
+        self.map_err(|e| e.to_string())?.into_answer()
+    }
+}
+
+/// Name of the implementation registered from `solve_part1` and `solve_part2`
+pub const DEFAULT_IMPL: &str = "default";
+
+/// One named implementation of a part
+#[derive(Debug, Clone, Copy)]
+pub struct Impl {
+    pub name: &'static str,
+    pub solver: Solver,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Day {
+    pub day: u8,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub input: &'static str,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub run: fn(),
+    /// Implementations of both parts, the first one is always the [`DEFAULT_IMPL`]
+    pub parts: [&'static [Impl]; 2],
+    /// Known correct answers for [`Day::input`], `None` if the day isn't solved yet
+    pub answers: Option<[&'static str; 2]>,
+}
+
+impl Day {
+    pub fn is_solved(&self) -> bool {
+        self.answers.is_some()
+    }
+
+    pub fn answer(&self, part: u8) -> Option<&'static str> {
+        let index = (part as usize).checked_sub(1)?;
+        self.answers.and_then(|a| a.get(index).copied())
+    }
+
+    /// Returns the default implementation of the `part`
+    pub fn part(&self, part: u8) -> Option<Solver> {
+        self.impls(part).map(|impls| impls[0].solver)
+    }
+
+    /// Returns all implementations of the `part`
+    pub fn impls(&self, part: u8) -> Option<&'static [Impl]> {
+        let index = (part as usize).checked_sub(1)?;
+        self.parts.get(index).copied()
+    }
+
+    /// Returns the implementation of the `part` with given `name`
+    pub fn get_impl(&self, part: u8, name: &str) -> Option<Solver> {
+        self.impls(part)?
+            .iter()
+            .find(|i| i.name == name)
+            .map(|i| i.solver)
+    }
+
+    /// Runs the solver for `part` on `input` and measures how long it took.
+    ///
+    /// Parse errors and panics of the solver on invalid input are returned as an error.
+    /// Returns `None` if `part` doesn't exist.
+    ///
+    /// Not available on wasm, since panics cannot be caught and there is no [`Instant`] there.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn solve(&self, part: u8, input: &str) -> Option<Solution> {
+        self.part(part).map(|solver| solve_with(solver, input))
+    }
+
+    /// Runs all implementations of the `part` on `input`, see [`Day::solve`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn solve_all(&self, part: u8, input: &str) -> Option<Vec<(&'static str, Solution)>> {
+        let impls = self.impls(part)?;
+        Some(
+            impls
+                .iter()
+                .map(|i| (i.name, solve_with(i.solver, input)))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn solve_with(solver: Solver, input: &str) -> Solution {
+    let start = Instant::now();
+    let answer = panic::catch_unwind(AssertUnwindSafe(|| solver(input)));
+    let elapsed = start.elapsed();
+
+    Solution {
+        answer: answer
+            .map_err(|payload| panic_message(payload.as_ref()))
+            .and_then(|answer| answer),
+        elapsed,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    /// The answer, the parse error of the input or the message of the panic that happened while
+    /// solving
+    pub answer: Result<String, String>,
+    pub elapsed: Duration,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        String::from("unknown panic")
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    /// Set while [`catch_quietly`] runs on this thread
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Like [`panic::catch_unwind`] but the message of a panic in `f` is not printed
+///
+/// The panic hook is only wrapped, panics of other threads and panics outside of `f` go to the
+/// hook which was installed before as usual.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn catch_quietly<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    static WRAP_HOOK: Once = Once::new();
+    WRAP_HOOK.call_once(|| {
+        let prev_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.get() {
+                prev_hook(info);
+            }
+        }));
+    });
+
+    struct Reset(bool);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            QUIET.set(self.0);
+        }
+    }
+    let _reset = Reset(QUIET.replace(true));
+
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| panic_message(payload.as_ref()))
+}
+
+/// Registers the days.
+///
+/// `N => dayN ["answer1", "answer2"]` registers `dayN::solve_part1` and `dayN::solve_part2` as the default
+/// implementations. Alternative implementations are listed after the answers as
+/// `{ part1: ["name" => dayN::fn_name], part2: [...] }`.
+macro_rules! days {
+    (@answers) => { None };
+    (@answers $p1:literal, $p2:literal) => { Some([$p1, $p2]) };
+    ($(
+        $num:literal => $day:ident $([$p1:literal, $p2:literal])?
+        $({
+            $(part1: [$($name1:literal => $impl1:path),*$(,)?])?$(,)?
+            $(part2: [$($name2:literal => $impl2:path),*$(,)?])?$(,)?
+        })?
+    ),*$(,)?) => {
+        &[$(
+            Day {
+                day: $num,
+                #[cfg(not(target_arch = "wasm32"))]
+                input: $day::INPUT,
+                #[cfg(not(target_arch = "wasm32"))]
+                run: $day::run,
+                parts: [
+                    &[
+                        Impl {
+                            name: DEFAULT_IMPL,
+                            solver: |input| $day::solve_part1(input).into_answer(),
+                        },
+                        $($($(Impl {
+                            name: $name1,
+                            solver: |input| $impl1(input).into_answer(),
+                        },)*)?)?
+                    ],
+                    &[
+                        Impl {
+                            name: DEFAULT_IMPL,
+                            solver: |input| $day::solve_part2(input).into_answer(),
+                        },
+                        $($($(Impl {
+                            name: $name2,
+                            solver: |input| $impl2(input).into_answer(),
+                        },)*)?)?
+                    ],
+                ],
+                answers: days!(@answers $($p1, $p2)?),
+            },
+        )*]
+    };
+}
+
+pub static DAYS: &[Day] = days!(
+    1 => day1 ["56049", "54530"] {
+        part2: ["handwritten" => day1::solve_part2_handwritten],
+    },
+    2 => day2 ["2239", "83435"],
+    3 => day3 ["535351", "87287096"],
+    4 => day4 ["21485", "11024379"],
+    5 => day5 ["484023871", "46294175"],
+    6 => day6 ["800280", "45128024"],
+    7 => day7 ["250058342", "250506580"],
+    8 => day8 ["15989", "13830919117339"] {
+        part1: ["hashmap" => day8::solve_part1_hashmap],
+        part2: ["brent" => day8::solve_part2_brent, "hashmap" => day8::solve_part2_hashmap],
+    },
+    9 => day9 ["1887980197", "990"] {
+        part1: ["lagrange" => day9::solve_part1_lagrange],
+        part2: ["lagrange" => day9::solve_part2_lagrange],
+    },
+    10 => day10 ["6907", "541"] {
+        part1: ["bfs" => day10::solve_part1_bfs],
+        part2: ["7j" => day10::solve_part2_7j, "pick" => day10::solve_part2_pick],
+    },
+    11 => day11 ["9608724", "904633799472"] {
+        part1: ["reverse" => day11::solve_part1_reverse, "sorted" => day11::solve_part1_sorted],
+        part2: ["reverse" => day11::solve_part2_reverse, "sorted" => day11::solve_part2_sorted],
+    },
+    12 => day12,
+    13 => day13 ["31956", "37617"],
+    14 => day14 ["108144", "108404"] {
+        part2: ["brent" => day14::solve_part2_brent, "bytes" => day14::solve_part2_bytes],
+    },
+    15 => day15 ["513214", "258826"],
+    16 => day16 ["7939", "8318"],
+    19 => day19 ["480738", "131550418841958"] {
+        part1: ["hashmap" => day19::solve_part1_hashmap],
+        part2: ["hashmap" => day19::solve_part2_hashmap],
+    },
+);
+
+pub fn get(day: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let day = get(9).unwrap();
+
+        let solution = day.solve(1, "0 3 6 9 12 15").unwrap();
+        assert_eq!(solution.answer, Ok(String::from("18")));
+
+        let solution = day.solve(2, "0 3 6 9 x 15").unwrap();
+        assert_eq!(
+            solution.answer,
+            Err(String::from(
+                "invalid number `x`: invalid digit found in string"
+            ))
+        );
+
+        // day 12 isn't solved yet and panics
+        let solution = get(12).unwrap().solve(1, "").unwrap();
+        assert!(solution.answer.is_err());
+
+        assert!(day.solve(3, "0 3 6 9 12 15").is_none());
+    }
+
+    #[test]
+    fn test_catch_quietly() {
+        assert_eq!(catch_quietly(|| 1), Ok(1));
+        assert_eq!(
+            catch_quietly(|| -> u32 { panic!("invalid input") }),
+            Err(String::from("invalid input"))
+        );
+        // nested calls only make the outer one quiet again once they are done
+        assert!(catch_quietly(|| {
+            let _ = catch_quietly(|| panic!("inner"));
+            QUIET.get()
+        })
+        .unwrap());
+        assert!(!QUIET.get());
+    }
+
+    #[test]
+    fn test_get_impl() {
+        let day = get(11).unwrap();
+
+        assert_eq!(day.impls(1).unwrap().len(), 3);
+        assert!(day.get_impl(2, "sorted").is_some());
+        assert!(day.get_impl(2, "unknown").is_none());
+        assert!(day.get_impl(3, DEFAULT_IMPL).is_none());
+    }
+
+    #[test]
+    fn test_alternatives_agree() {
+        for day in DAYS {
+            for part in [1, 2] {
+                if day.impls(part).unwrap().len() == 1 {
+                    continue;
+                }
+
+                let known = day.answer(part).map(String::from);
+                for (name, solution) in day.solve_all(part, day.input).unwrap() {
+                    assert_eq!(
+                        solution.answer.ok(),
+                        known,
+                        "day{}::part{part}::{name}",
+                        day.day
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "divan")]
+mod benches {
+    use std::fmt;
+
+    use divan::{black_box, Bencher};
+
+    use super::*;
+
+    /// Benchmark argument for one implementation of a part
+    struct BenchImpl {
+        day: &'static Day,
+        part: u8,
+        implementation: &'static Impl,
+    }
+
+    impl fmt::Display for BenchImpl {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "day{}::part{}::{}",
+                self.day.day, self.part, self.implementation.name
+            )
+        }
+    }
+
+    /// All implementations of the parts which have more than one of them
+    fn alternatives() -> impl Iterator<Item = BenchImpl> {
+        DAYS.iter().flat_map(|day| {
+            (1..=2)
+                .filter(|&part| day.impls(part).unwrap().len() > 1)
+                .flat_map(move |part| {
+                    day.impls(part)
+                        .unwrap()
+                        .iter()
+                        .map(move |implementation| BenchImpl {
+                            day,
+                            part,
+                            implementation,
+                        })
+                })
+        })
+    }
+
+    #[divan::bench(args = alternatives())]
+    fn impls(bencher: Bencher, bench: &BenchImpl) {
+        let known = bench.day.answer(bench.part).unwrap();
+        bencher.bench(|| {
+            let answer = (bench.implementation.solver)(black_box(bench.day.input));
+            assert_eq!(answer.as_deref(), Ok(known));
+        });
+    }
+}
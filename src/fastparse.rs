@@ -0,0 +1,500 @@
+//! Integer parsing and scanning directly on the bytes of the input.
+//!
+//! The digits are found eight bytes at a time with SWAR (SIMD within a register): a `u64` is loaded
+//! from the input and all of its bytes are classified with a handful of integer operations. Runs of
+//! up to eight digits are converted with three multiplications instead of a loop. Lines are split
+//! with [`memchr`], which uses the SIMD instructions of the target.
+//!
+//! [`numbers`] and [`number`] are drop-in replacements for [`parse::numbers`] and
+//! [`parse::number`] for integers, with the same errors for malformed input. The gain comes from
+//! scanning whole lines with [`numbers`], where the words can be loaded past the end of short
+//! numbers, a lone short number is parsed about as fast as with [`str::parse`].
+
+use std::marker::PhantomData;
+
+use crate::parse::{self, ParseError};
+
+/// `byte` in every byte of a word
+const fn splat(byte: u8) -> u64 {
+    0x0101_0101_0101_0101 * byte as u64
+}
+
+const ZEROS: u64 = splat(b'0');
+const HIGH_BITS: u64 = splat(0x80);
+
+const EMPTY: &str = "cannot parse integer from empty string";
+const INVALID_DIGIT: &str = "invalid digit found in string";
+const TOO_LARGE: &str = "number too large to fit in target type";
+const TOO_SMALL: &str = "number too small to fit in target type";
+
+/// Integer types the numbers can be parsed into
+pub trait Integer: Copy {
+    /// Whether a leading `-` is allowed
+    const SIGNED: bool;
+
+    /// The number `-magnitude` or `magnitude`, `None` if it doesn't fit
+    fn from_magnitude(negative: bool, magnitude: u64) -> Option<Self>;
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const SIGNED: bool = false;
+
+                fn from_magnitude(negative: bool, magnitude: u64) -> Option<Self> {
+                    if negative {
+                        return None;
+                    }
+                    magnitude.try_into().ok()
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_signed {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const SIGNED: bool = true;
+
+                fn from_magnitude(negative: bool, magnitude: u64) -> Option<Self> {
+                    let value = if negative {
+                        0i64.checked_sub_unsigned(magnitude)?
+                    } else {
+                        magnitude.try_into().ok()?
+                    };
+                    value.try_into().ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8, u16, u32, u64, usize);
+impl_signed!(i8, i16, i32, i64, isize);
+
+/// Value of an ASCII digit
+pub fn digit(byte: u8) -> Option<u8> {
+    let value = byte.wrapping_sub(b'0');
+    (value < 10).then_some(value)
+}
+
+/// Loads 8 bytes, the first one into the lowest byte
+fn load(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+/// Sets the highest bit of every byte of `word` which is not an ASCII digit and clears the rest
+fn non_digits(word: u64) -> u64 {
+    // zero where the high nibble is 3 and where the low nibble is below 10
+    let high = (word & splat(0xf0)) ^ ZEROS;
+    let low = ((word & splat(0x0f)) + splat(0x06)) & splat(0xf0);
+    let x = high | low;
+    // moves "the byte is not zero" into its highest bit, without carries between the bytes
+    (((x & splat(0x7f)) + splat(0x7f)) | x) & HIGH_BITS
+}
+
+/// Parses 8 ASCII digits, the most significant one is in the lowest byte
+fn parse8(word: u64) -> u64 {
+    let word = word - ZEROS;
+    // pairs of digits in every second byte, then groups of four in every second u16
+    let word = (word.wrapping_mul(10) + (word >> 8)) & 0x00ff_00ff_00ff_00ff;
+    let word = (word.wrapping_mul(1 + (100 << 16)) >> 16) & 0x0000_ffff_0000_ffff;
+    word.wrapping_mul(1 + (10000 << 32)) >> 32
+}
+
+/// Number of ASCII digits at the start of `bytes`
+pub fn digits(bytes: &[u8]) -> usize {
+    let mut i = 0;
+    while i + 8 <= bytes.len() {
+        let mask = non_digits(load(&bytes[i..]));
+        if mask != 0 {
+            return i + mask.trailing_zeros() as usize / 8;
+        }
+        i += 8;
+    }
+    i + bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count()
+}
+
+/// Index of the first ASCII digit in `bytes`
+pub fn first_digit(bytes: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    while i + 8 <= bytes.len() {
+        let mask = !non_digits(load(&bytes[i..])) & HIGH_BITS;
+        if mask != 0 {
+            return Some(i + mask.trailing_zeros() as usize / 8);
+        }
+        i += 8;
+    }
+    bytes[i..]
+        .iter()
+        .position(u8::is_ascii_digit)
+        .map(|pos| i + pos)
+}
+
+/// Index of the last ASCII digit in `bytes`
+pub fn last_digit(bytes: &[u8]) -> Option<usize> {
+    let mut end = bytes.len();
+    while end >= 8 {
+        let mask = !non_digits(load(&bytes[end - 8..])) & HIGH_BITS;
+        if mask != 0 {
+            return Some(end - 8 + (63 - mask.leading_zeros() as usize) / 8);
+        }
+        end -= 8;
+    }
+    bytes[..end].iter().rposition(u8::is_ascii_digit)
+}
+
+/// Value of the first `len` bytes of `bytes`, which must be digits, `None` if it overflows
+///
+/// The bytes after them are read to load whole words, but they don't change the result.
+fn parse_digits(bytes: &[u8], len: usize) -> Option<u64> {
+    debug_assert!(bytes[..len].iter().all(u8::is_ascii_digit));
+
+    let mut value = 0u64;
+    let mut i = 0;
+    while i + 8 <= len {
+        value = value
+            .checked_mul(100_000_000)?
+            .checked_add(parse8(load(&bytes[i..])))?;
+        i += 8;
+    }
+
+    let rest = len - i;
+    let tail = match rest {
+        0 => return Some(value),
+        1..=3 => bytes[i..len]
+            .iter()
+            .fold(0, |n, &b| n * 10 + (b - b'0') as u64),
+        _ => {
+            let word = if i + 8 <= bytes.len() {
+                load(&bytes[i..])
+            } else {
+                let mut buffer = [b'0'; 8];
+                buffer[..rest].copy_from_slice(&bytes[i..len]);
+                u64::from_le_bytes(buffer)
+            };
+            // move the digits to the top and pad them with leading zeros, `rest` is at least 4 so
+            // neither shift is by 64 bits
+            let shift = (8 - rest as u32) * 8;
+            parse8(word << shift | ZEROS >> (64 - shift))
+        }
+    };
+    value.checked_mul(10u64.pow(rest as u32))?.checked_add(tail)
+}
+
+/// Parses the digits at the start of `bytes`
+///
+/// Returns the value, `None` if it overflows, and the number of digits. Numbers of up to seven
+/// digits followed by at least one other byte are parsed from a single word.
+fn leading_digits(bytes: &[u8]) -> (Option<u64>, usize) {
+    if bytes.len() < 8 {
+        let mut value = 0;
+        for (i, &b) in bytes.iter().enumerate() {
+            match digit(b) {
+                Some(d) => value = value * 10 + d as u64,
+                None => return (Some(value), i),
+            }
+        }
+        return (Some(value), bytes.len());
+    }
+
+    let word = load(bytes);
+    let mask = non_digits(word);
+    if mask == 0 {
+        let len = digits(bytes);
+        return (parse_digits(bytes, len), len);
+    }
+    let len = mask.trailing_zeros() / 8;
+    let value = match len {
+        0 => 0,
+        // the most common lengths are cheaper without the multiplications of `parse8`
+        1 => (word & 0xff) - b'0' as u64,
+        2 => ((word & 0xff) - b'0' as u64) * 10 + ((word >> 8) & 0xff) - b'0' as u64,
+        // move the digits to the top and pad them with leading zeros
+        _ => parse8(word << (64 - 8 * len) | ZEROS >> (8 * len)),
+    };
+    (Some(value), len as usize)
+}
+
+/// Parses the integer at the start of `bytes`, which ends at whitespace or at the end of `bytes`
+///
+/// Returns the integer or the reason it is invalid, and the length of the token.
+fn token<T: Integer>(bytes: &[u8]) -> (Result<T, &'static str>, usize) {
+    let (negative, sign) = match bytes.first() {
+        Some(b'-') if T::SIGNED => (true, 1),
+        Some(b'+') => (false, 1),
+        _ => (false, 0),
+    };
+    let (magnitude, len) = leading_digits(&bytes[sign..]);
+    let end = sign + len;
+
+    if len == 0 || bytes.get(end).is_some_and(|b| !b.is_ascii_whitespace()) {
+        let token_len = bytes[end..]
+            .iter()
+            .position(u8::is_ascii_whitespace)
+            .map_or(bytes.len(), |pos| end + pos);
+        return (Err(INVALID_DIGIT), token_len);
+    }
+
+    let value = magnitude.and_then(|m| T::from_magnitude(negative, m));
+    (
+        value.ok_or(if negative { TOO_SMALL } else { TOO_LARGE }),
+        end,
+    )
+}
+
+fn invalid_number(input: &str, reason: &str) -> ParseError {
+    ParseError::InvalidNumber {
+        input: input.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Parses an integer, surrounding ASCII whitespace is ignored
+pub fn number<T: Integer>(s: &str) -> parse::Result<T> {
+    let s = s.trim_ascii();
+    if s.is_empty() {
+        return Err(invalid_number(s, EMPTY));
+    }
+    match token(s.as_bytes()) {
+        (Ok(value), len) if len == s.len() => Ok(value),
+        (Ok(_), _) => Err(invalid_number(s, INVALID_DIGIT)),
+        (Err(reason), _) => Err(invalid_number(s, reason)),
+    }
+}
+
+/// Iterator over the whitespace separated integers in `s`, any amount of whitespace is allowed
+pub fn numbers<T>(s: &str) -> Numbers<'_, T> {
+    Numbers {
+        rest: s,
+        _marker: PhantomData,
+    }
+}
+
+pub struct Numbers<'a, T> {
+    rest: &'a str,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Integer> Iterator for Numbers<'_, T> {
+    type Item = parse::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.rest.bytes().position(|b| !b.is_ascii_whitespace())?;
+        let rest = &self.rest[start..];
+        let (value, len) = token(rest.as_bytes());
+        self.rest = &rest[len..];
+        Some(value.map_err(|reason| invalid_number(&rest[..len], reason)))
+    }
+}
+
+/// Iterator over the lines of `s`, like [`str::lines`] the line endings are `\n` or `\r\n`
+pub fn lines(s: &str) -> Lines<'_> {
+    Lines { rest: s }
+}
+
+pub struct Lines<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        match memchr::memchr(b'\n', self.rest.as_bytes()) {
+            Some(end) => {
+                let line = &self.rest[..end];
+                self.rest = &self.rest[end + 1..];
+                Some(line.strip_suffix('\r').unwrap_or(line))
+            }
+            None => Some(std::mem::take(&mut self.rest)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(digit(b'7'), Some(7));
+        assert_eq!(digit(b'a'), None);
+        assert_eq!(digits(b"12345678901 x"), 11);
+        assert_eq!(digits(b"x1"), 0);
+        assert_eq!(first_digit(b"abcdefghij7k"), Some(10));
+        assert_eq!(last_digit(b"a1bcdefghijk"), Some(1));
+        assert_eq!(first_digit(b"treb7uchet"), Some(4));
+        assert_eq!(last_digit(b"treb7uchet"), Some(4));
+        assert_eq!(first_digit(b"no digits here"), None);
+        assert_eq!(last_digit(b""), None);
+        // bytes which are close to the digits
+        assert_eq!(first_digit(b"/:\xb0\xb9\x10\x19\xff\x00"), None);
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number::<u64>(" 12345678901234567 "), Ok(12345678901234567));
+        assert_eq!(number::<i64>("-17"), Ok(-17));
+        assert_eq!(number::<i64>("+17"), Ok(17));
+        assert_eq!(number::<i8>("-128"), Ok(-128));
+        assert_eq!(number::<u64>("18446744073709551615"), Ok(u64::MAX));
+        assert_eq!(number::<i64>("-9223372036854775808"), Ok(i64::MIN));
+
+        let reason = |s| match number::<u8>(s) {
+            Err(ParseError::InvalidNumber { reason, .. }) => reason,
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(reason(""), "cannot parse integer from empty string");
+        assert_eq!(reason("12x"), "invalid digit found in string");
+        assert_eq!(reason("-1"), "invalid digit found in string");
+        assert_eq!(reason("1 2"), "invalid digit found in string");
+        assert_eq!(reason("256"), "number too large to fit in target type");
+        assert!(
+            matches!(number::<i8>("-129"), Err(ParseError::InvalidNumber { reason, .. }) if reason == TOO_SMALL)
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        let nums = numbers::<i64>("  0 -2 41\t1000000007\n 3")
+            .collect::<parse::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(nums, [0, -2, 41, 1000000007, 3]);
+
+        let mut nums = numbers::<u32>("1 x2 3");
+        assert_eq!(nums.next(), Some(Ok(1)));
+        assert!(matches!(
+            nums.next(),
+            Some(Err(ParseError::InvalidNumber { input, .. })) if input == "x2"
+        ));
+        assert_eq!(nums.next(), Some(Ok(3)));
+        assert_eq!(nums.next(), None);
+    }
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(
+            lines("a\r\nb\n\nc").collect::<Vec<_>>(),
+            ["a", "b", "", "c"]
+        );
+        assert_eq!(lines("a\n").count(), 1);
+        assert_eq!(lines("").count(), 0);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_number_against_std(s in "[-+]?[0-9]{0,22}|[-+0-9a]{0,6}") {
+            let expected = s.parse::<i64>().ok();
+            prop_assert_eq!(number::<i64>(&s).ok(), expected);
+            let expected = s.parse::<u32>().ok();
+            prop_assert_eq!(number::<u32>(&s).ok(), expected);
+        }
+
+        #[test]
+        fn test_numbers_against_std(nums in prop::collection::vec(any::<i64>(), 0..20), sep in "[ \t\n]{1,3}") {
+            let input = nums.iter().map(i64::to_string).collect::<Vec<_>>().join(&sep);
+            let parsed = numbers::<i64>(&input).collect::<parse::Result<Vec<_>>>().unwrap();
+            prop_assert_eq!(parsed, nums);
+        }
+
+        #[test]
+        fn test_scanning_against_std(s in "[ -~]{0,40}") {
+            let bytes = s.as_bytes();
+            prop_assert_eq!(first_digit(bytes), bytes.iter().position(u8::is_ascii_digit));
+            prop_assert_eq!(last_digit(bytes), bytes.iter().rposition(u8::is_ascii_digit));
+            prop_assert_eq!(digits(bytes), bytes.iter().take_while(|b| b.is_ascii_digit()).count());
+        }
+
+        #[test]
+        fn test_lines_against_std(s in "[a\r\n]{0,20}") {
+            prop_assert_eq!(lines(&s).collect::<Vec<_>>(), s.lines().collect::<Vec<_>>());
+        }
+    }
+}
+
+#[cfg(feature = "divan")]
+mod benches {
+    use divan::{black_box, Bencher};
+
+    use super::*;
+
+    /// The integers in the inputs of the days with the most numbers
+    fn tokens() -> Vec<&'static str> {
+        [crate::day4::INPUT, crate::day5::INPUT, crate::day9::INPUT]
+            .iter()
+            .flat_map(|input| input.split_ascii_whitespace())
+            .filter(|token| token.parse::<i64>().is_ok())
+            .collect()
+    }
+
+    #[divan::bench]
+    fn number_std(bencher: Bencher) {
+        bencher.with_inputs(tokens).bench_refs(|tokens| {
+            tokens
+                .iter()
+                .map(|t| parse::number::<i64>(black_box(t)).unwrap())
+                .sum::<i64>()
+        });
+    }
+
+    #[divan::bench]
+    fn number_fast(bencher: Bencher) {
+        bencher.with_inputs(tokens).bench_refs(|tokens| {
+            tokens
+                .iter()
+                .map(|t| number::<i64>(black_box(t)).unwrap())
+                .sum::<i64>()
+        });
+    }
+
+    #[divan::bench]
+    fn numbers_std() -> i64 {
+        crate::day9::INPUT
+            .lines()
+            .flat_map(|line| parse::numbers::<i64>(black_box(line)))
+            .map(Result::unwrap)
+            .sum()
+    }
+
+    #[divan::bench]
+    fn numbers_fast() -> i64 {
+        lines(crate::day9::INPUT)
+            .flat_map(|line| numbers::<i64>(black_box(line)))
+            .map(Result::unwrap)
+            .sum()
+    }
+
+    #[divan::bench]
+    fn digits_chars() -> u32 {
+        crate::day1::INPUT
+            .lines()
+            .map(|line| {
+                let line = black_box(line);
+                let first = line.bytes().find_map(|c| (c as char).to_digit(10));
+                let last = line.bytes().rev().find_map(|c| (c as char).to_digit(10));
+                first.unwrap() * 10 + last.unwrap()
+            })
+            .sum()
+    }
+
+    #[divan::bench]
+    fn digits_swar() -> u32 {
+        lines(crate::day1::INPUT)
+            .map(|line| {
+                let bytes = black_box(line).as_bytes();
+                let first = bytes[first_digit(bytes).unwrap()] - b'0';
+                let last = bytes[last_digit(bytes).unwrap()] - b'0';
+                (first * 10 + last) as u32
+            })
+            .sum()
+    }
+}
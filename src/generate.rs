@@ -0,0 +1,758 @@
+//! Generators for synthetic puzzle inputs of arbitrary size.
+//!
+//! The generated inputs have the same structure as the real ones, so the solutions can be run on them.
+//! `size` is roughly the number of lines, or the side length for the days with a grid.
+//! The same `seed` always generates the same input.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::ops::Range;
+
+use crate::day5::Almanac;
+
+/// Generates an input for `day`, returns `None` if there is no such day.
+pub fn generate(day: u8, size: usize, seed: u64) -> Option<String> {
+    let generator = match day {
+        1 => day1,
+        2 => day2,
+        3 => day3,
+        4 => day4,
+        5 => day5,
+        6 => day6,
+        7 => day7,
+        8 => day8,
+        9 => day9,
+        10 => day10,
+        11 => day11,
+        12 => day12,
+        13 => day13,
+        14 => day14,
+        15 => day15,
+        16 => day16,
+        19 => day19,
+        _ => return None,
+    };
+
+    Some(generator(&mut Rng::new(seed), size.max(1)))
+}
+
+/// SplitMix64, small and good enough for generating inputs
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random number in `range`, which must not be empty
+    fn range(&mut self, range: Range<u64>) -> u64 {
+        range.start + self.next_u64() % (range.end - range.start)
+    }
+
+    fn index(&mut self, len: usize) -> usize {
+        self.range(0..len as u64) as usize
+    }
+
+    fn one_in(&mut self, n: u64) -> bool {
+        self.next_u64().is_multiple_of(n)
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.index(items.len())]
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+
+    /// Random string of `len` chars picked from `chars`
+    fn string(&mut self, chars: &[u8], len: usize) -> String {
+        (0..len).map(|_| self.pick(chars) as char).collect()
+    }
+}
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+fn grid(rng: &mut Rng, size: usize, mut cell: impl FnMut(&mut Rng) -> char) -> String {
+    let mut out = String::with_capacity((size + 1) * size);
+    for _ in 0..size {
+        out.extend((0..size).map(|_| cell(rng)));
+        out.push('\n');
+    }
+    out
+}
+
+/// Calibration lines with digits and spelled out digits, every line has at least one digit
+fn day1(rng: &mut Rng, size: usize) -> String {
+    const WORDS: [&str; 9] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+
+    let mut out = String::new();
+    for _ in 0..size {
+        let mut segments = Vec::new();
+        for _ in 0..rng.range(1..8) {
+            match rng.range(0..3) {
+                0 => segments.push(rng.pick(&WORDS).to_string()),
+                1 => segments.push(rng.range(1..10).to_string()),
+                _ => {
+                    let len = rng.range(1..6) as usize;
+                    segments.push(rng.string(LOWERCASE, len));
+                }
+            }
+        }
+        let at = rng.index(segments.len() + 1);
+        segments.insert(at, rng.range(1..10).to_string());
+
+        out += &segments.concat();
+        out.push('\n');
+    }
+    out
+}
+
+/// Games with random subsets of red, green and blue cubes
+fn day2(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for id in 1..=size {
+        let subsets = (0..rng.range(1..7))
+            .map(|_| {
+                let mut colors = ["red", "green", "blue"];
+                rng.shuffle(&mut colors);
+                let count = rng.range(1..4) as usize;
+                colors[..count]
+                    .iter()
+                    .map(|color| format!("{} {color}", rng.range(1..21)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        writeln!(out, "Game {id}: {subsets}").unwrap();
+    }
+    out
+}
+
+/// Engine schematic with numbers and symbols
+fn day3(rng: &mut Rng, size: usize) -> String {
+    const SYMBOLS: &[u8] = b"*#+$@/=%&-";
+
+    let mut out = String::new();
+    for _ in 0..size {
+        let mut line = String::with_capacity(size);
+        while line.len() < size {
+            let digits = rng.range(1..4) as usize;
+            if rng.one_in(5) && line.len() + digits <= size {
+                write!(
+                    line,
+                    "{}",
+                    rng.range(10u64.pow(digits as u32 - 1)..10u64.pow(digits as u32))
+                )
+                .unwrap();
+                // numbers must be separated
+                if line.len() < size {
+                    line.push(if rng.one_in(4) {
+                        rng.pick(SYMBOLS) as char
+                    } else {
+                        '.'
+                    });
+                }
+            } else if rng.one_in(12) {
+                line.push(rng.pick(SYMBOLS) as char);
+            } else {
+                line.push('.');
+            }
+        }
+        out += &line;
+        out.push('\n');
+    }
+    out
+}
+
+/// Scratchcards with 10 winning numbers and 25 numbers.
+///
+/// The cards win less than one new card on average, with more the number of cards in part 2 grows exponentially.
+fn day4(rng: &mut Rng, size: usize) -> String {
+    let format_numbers = |numbers: &[u64]| {
+        numbers
+            .iter()
+            .map(|n| format!("{n:>2}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut all_numbers = (1..100).collect::<Vec<u64>>();
+    let mut out = String::new();
+    for id in 1..=size {
+        rng.shuffle(&mut all_numbers);
+        let (winning, others) = all_numbers.split_at(10);
+        let matches = if rng.one_in(5) { rng.range(1..5) } else { 0 } as usize;
+        let mut numbers = [&winning[..matches], &others[..25 - matches]].concat();
+        rng.shuffle(&mut numbers);
+
+        let winning = format_numbers(winning);
+        let numbers = format_numbers(&numbers);
+        writeln!(out, "Card {id:>3}: {winning} | {numbers}").unwrap();
+    }
+    out
+}
+
+/// Almanac with `size` non-overlapping ranges in every map.
+/// The seed ranges are kept small so that they can be checked seed by seed.
+fn day5(rng: &mut Rng, size: usize) -> String {
+    let max = day5_max(size as u64);
+
+    let seeds = (0..10)
+        .map(|_| {
+            let len = rng.range(1..(size as u64).saturating_mul(100).min(max / 2) + 1);
+            format!("{} {len}", rng.range(0..max - len))
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut out = format!("seeds: {seeds}\n");
+    // every map range gets a segment of its own so that they don't overlap
+    let segment = max / size as u64;
+    for names in Almanac::CATEGORIES.windows(2) {
+        write!(out, "\n{}-to-{} map:\n", names[0], names[1]).unwrap();
+
+        let mut segments = (0..size as u64).collect::<Vec<_>>();
+        rng.shuffle(&mut segments);
+        for i in segments {
+            let len = rng.range(1..segment + 1);
+            let src = i * segment + rng.range(0..segment - len + 1);
+            let dst = rng.range(0..max - len);
+            writeln!(out, "{dst} {src} {len}").unwrap();
+        }
+    }
+    out
+}
+
+/// Upper bound of the day 5 values, large enough for segments of at least 2^16 values up to
+/// 2^48 ranges and at least one value for any number of ranges
+fn day5_max(size: u64) -> u64 {
+    size.saturating_mul(1 << 16).max(1 << 32)
+}
+
+/// At most 4 races, part 2 concatenates the numbers which would overflow otherwise
+fn day6(rng: &mut Rng, size: usize) -> String {
+    let races = (0..size.min(4))
+        .map(|_| {
+            // Keeping the times at 2 digits with a leading digit of at least 2 and the distances at most 3 digits
+            // makes sure that concatenated record can be beaten too
+            let time = rng.range(20..100);
+            let distance = rng.range(1..(time * time / 4).min(1000));
+            (time, distance)
+        })
+        .collect::<Vec<_>>();
+
+    let mut times = String::from("Time:");
+    let mut distances = String::from("Distance:");
+    for (time, distance) in races {
+        write!(times, " {time:>6}").unwrap();
+        write!(distances, " {distance:>3}").unwrap();
+    }
+    format!("{times}\n{distances}\n")
+}
+
+/// Unique hands with bids
+fn day7(rng: &mut Rng, size: usize) -> String {
+    let mut seen = HashSet::new();
+    let mut out = String::new();
+    // there are only 13^5 different hands
+    while seen.len() < size.min(13usize.pow(5)) {
+        let hand = rng.string(b"AKQJT98765432", 5);
+        if seen.insert(hand.clone()) {
+            writeln!(out, "{hand} {}", rng.range(1..1001)).unwrap();
+        }
+    }
+    out
+}
+
+/// Network with up to 6 ghosts, their periods are distinct primes
+fn day8(rng: &mut Rng, size: usize) -> String {
+    let ghosts = (size / 50).clamp(1, 6);
+    let mut periods = Vec::new();
+    // use at most half of the names, so new ones are still quick to find at random
+    let max_period = STEP_NAMES / 4 / ghosts;
+    let mut period = (size / (2 * ghosts)).clamp(2, max_period);
+    for _ in 0..ghosts {
+        while !is_prime(period) {
+            period += 1;
+        }
+        periods.push(period);
+        period += 1;
+    }
+
+    let instructions = rng.string(b"LR", size);
+    ghost_network(rng, &instructions, &periods)
+}
+
+/// Characters of the node names, the example inputs of day 8 use digits too
+const NAME_CHARS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+/// Last characters of the nodes between the start and the end nodes
+const STEP_LAST_CHARS: &[u8] = b"0123456789BCDEFGHIJKLMNOPQRSTUVWXY";
+/// Number of names for the nodes between the start and the end nodes
+const STEP_NAMES: usize = NAME_CHARS.len() * NAME_CHARS.len() * STEP_LAST_CHARS.len();
+
+/// Network where every start node `..A` loops through an end node `..Z` with given period, which must be at least 2.
+/// The first ghost starts from `AAA` and ends at `ZZZ`.
+///
+/// Like in the real input, after the start node there are two nodes for every step. Which one we go to depends on
+/// the instructions, but both of them lead to the next step, so the instructions don't change the periods.
+///
+/// # Panics
+/// If the periods need more than the [`STEP_NAMES`] names for the steps.
+pub(crate) fn ghost_network(rng: &mut Rng, instructions: &str, periods: &[usize]) -> String {
+    let steps = periods.iter().map(|period| 2 * (period - 1)).sum::<usize>();
+    assert!(
+        steps <= STEP_NAMES,
+        "{steps} nodes don't fit into the {STEP_NAMES} names"
+    );
+
+    let mut names = HashSet::from([String::from("AAA"), String::from("ZZZ")]);
+    let mut new_name = |rng: &mut Rng, last: &[u8]| loop {
+        let name = rng.string(NAME_CHARS, 2) + &rng.string(last, 1);
+        if names.insert(name.clone()) {
+            return name;
+        }
+    };
+
+    let mut nodes = Vec::new();
+    for (ghost, &period) in periods.iter().enumerate() {
+        let (start, end) = if ghost == 0 {
+            (String::from("AAA"), String::from("ZZZ"))
+        } else {
+            (new_name(rng, b"A"), new_name(rng, b"Z"))
+        };
+        let steps = (1..period)
+            .map(|_| {
+                [
+                    new_name(rng, STEP_LAST_CHARS),
+                    new_name(rng, STEP_LAST_CHARS),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let mut node = |rng: &mut Rng, name: &str, mut next: [&str; 2]| {
+            rng.shuffle(&mut next);
+            nodes.push(format!("{name} = ({}, {})", next[0], next[1]));
+        };
+        let first = [steps[0][0].as_str(), steps[0][1].as_str()];
+        node(rng, &start, first);
+        node(rng, &end, first);
+        for (i, step) in steps.iter().enumerate() {
+            let next = match steps.get(i + 1) {
+                Some(next) => [next[0].as_str(), next[1].as_str()],
+                None => [end.as_str(), end.as_str()],
+            };
+            node(rng, &step[0], next);
+            node(rng, &step[1], next);
+        }
+    }
+    rng.shuffle(&mut nodes);
+
+    format!("{instructions}\n\n{}\n", nodes.join("\n"))
+}
+
+fn is_prime(n: usize) -> bool {
+    n >= 2
+        && (2..)
+            .take_while(|i| i * i <= n)
+            .all(|i| !n.is_multiple_of(i))
+}
+
+/// Sequences of 21 values of random polynomials
+fn day9(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        let coefficients = (0..rng.range(1..7))
+            .map(|_| rng.range(0..11) as i64 - 5)
+            .collect::<Vec<_>>();
+        let offset = rng.range(0..41) as i64 - 20;
+
+        let values = (0..21)
+            .map(|x| {
+                let value = coefficients.iter().rev().fold(0, |acc, c| acc * x + c);
+                (value + offset).to_string()
+            })
+            .collect::<Vec<_>>();
+        out += &values.join(" ");
+        out.push('\n');
+    }
+    out
+}
+
+/// Square grid with a single loop and random pipes around it, see [`pipe_loop`]
+fn day10(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(3);
+    let cells = (size - 1) / 2;
+
+    let first_col = rng.index(cells);
+    let last_col = first_col + rng.index(cells - first_col);
+    let mut cols = Vec::new();
+    let (mut top, mut bottom) = (0, cells - 1);
+    for _ in first_col..=last_col {
+        let new_top = rng.index(bottom + 1);
+        bottom = new_top.max(top) + rng.index(cells - new_top.max(top));
+        top = new_top;
+        cols.push(top..bottom + 1);
+    }
+
+    pipe_loop(size, first_col, &cols, || rng.pick(b"|-LJ7F...") as char)
+}
+
+/// Draws a `size` x `size` grid with a single loop, the tiles which are not on the loop are filled with `junk`.
+///
+/// The loop is the boundary of a column convex polyomino scaled by 2. The columns of the polyomino start from
+/// `first_col` and `cols` are the rows covered by each column. Neighbouring columns must overlap, hence the
+/// boundary never touches itself. The polyomino must fit into `(size - 1) / 2` x `(size - 1) / 2` cells.
+pub(crate) fn pipe_loop(
+    size: usize,
+    first_col: usize,
+    cols: &[Range<usize>],
+    mut junk: impl FnMut() -> char,
+) -> String {
+    const UP: u8 = 1;
+    const DOWN: u8 = 2;
+    const LEFT: u8 = 4;
+    const RIGHT: u8 = 8;
+
+    let last_col = first_col + cols.len() - 1;
+    let is_inside =
+        |x: usize, y: usize| x >= first_col && x <= last_col && cols[x - first_col].contains(&y);
+
+    let mut connections = vec![vec![0u8; size]; size];
+    let mut connect = |(x, y): (usize, usize), horizontal: bool| {
+        let (x, y) = (x * 2, y * 2);
+        if horizontal {
+            connections[y][x] |= RIGHT;
+            connections[y][x + 1] |= LEFT | RIGHT;
+            connections[y][x + 2] |= LEFT;
+        } else {
+            connections[y][x] |= DOWN;
+            connections[y + 1][x] |= UP | DOWN;
+            connections[y + 2][x] |= UP;
+        }
+    };
+    for x in first_col..=last_col {
+        for y in cols[x - first_col].clone() {
+            if y == 0 || !is_inside(x, y - 1) {
+                connect((x, y), true);
+            }
+            if !is_inside(x, y + 1) {
+                connect((x, y + 1), true);
+            }
+            if x == 0 || !is_inside(x - 1, y) {
+                connect((x, y), false);
+            }
+            if !is_inside(x + 1, y) {
+                connect((x + 1, y), false);
+            }
+        }
+    }
+
+    let mut grid = connections
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&c| match c {
+                    0 => junk(),
+                    c if c == UP | DOWN => '|',
+                    c if c == LEFT | RIGHT => '-',
+                    c if c == UP | RIGHT => 'L',
+                    c if c == UP | LEFT => 'J',
+                    c if c == DOWN | LEFT => '7',
+                    c if c == DOWN | RIGHT => 'F',
+                    _ => unreachable!("the loop touches itself"),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // Any corner of the polyomino is on the loop
+    let (x, y) = (first_col * 2, cols[0].start * 2);
+    grid[y][x] = 'S';
+    // Random pipes next to the start could look like they are connected to it
+    for (nx, ny) in [
+        (x.wrapping_sub(1), y),
+        (x + 1, y),
+        (x, y.wrapping_sub(1)),
+        (x, y + 1),
+    ] {
+        if let Some(0) = connections.get(ny).and_then(|row| row.get(nx)) {
+            grid[ny][nx] = '.';
+        }
+    }
+
+    grid.into_iter()
+        .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+        .collect()
+}
+
+/// Galaxy field where roughly every tenth row and column is empty
+fn day11(rng: &mut Rng, size: usize) -> String {
+    let empty_rows = (0..size).map(|_| rng.one_in(10)).collect::<Vec<_>>();
+    let empty_cols = (0..size).map(|_| rng.one_in(10)).collect::<Vec<_>>();
+
+    let mut out = String::new();
+    for &empty_row in &empty_rows {
+        for &empty_col in &empty_cols {
+            let is_galaxy = !empty_row && !empty_col && rng.one_in(20);
+            out.push(if is_galaxy { '#' } else { '.' });
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Spring rows with damaged springs replaced by `?`
+fn day12(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        let len = rng.range(5..21) as usize;
+        let mut springs = rng.string(b"#..", len).into_bytes();
+        let at = rng.index(springs.len());
+        springs[at] = b'#';
+
+        let groups = springs
+            .split(|&c| c == b'.')
+            .filter(|group| !group.is_empty())
+            .map(|group| group.len().to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        for c in springs.iter_mut() {
+            if rng.one_in(2) {
+                *c = b'?';
+            }
+        }
+
+        writeln!(out, "{} {groups}", String::from_utf8(springs).unwrap()).unwrap();
+    }
+    out
+}
+
+/// Patterns with a perfect reflection in one direction and a reflection with a single smudge in the other
+fn day13(rng: &mut Rng, size: usize) -> String {
+    // Maps the index to the mirrored index on the other side of the reflection if there is one
+    fn mirror(i: usize, reflection: usize, len: usize) -> usize {
+        let span = reflection.min(len - reflection);
+        if i >= reflection && i < reflection + span {
+            2 * reflection - 1 - i
+        } else {
+            i
+        }
+    }
+
+    let mut patterns = Vec::new();
+    for _ in 0..size {
+        // odd number of rows leaves at least one row outside the perfect reflection
+        let rows = rng.range(2..9) as usize * 2 + 1;
+        let cols = rng.range(5..18) as usize;
+        let row_reflection = rng.range(1..rows as u64) as usize;
+        let col_reflection = rng.range(1..cols as u64) as usize;
+
+        let base = (0..rows)
+            .map(|_| rng.string(b"#.", cols).into_bytes())
+            .collect::<Vec<_>>();
+        let mut pattern = (0..rows)
+            .map(|r| {
+                (0..cols)
+                    .map(|c| base[mirror(r, row_reflection, rows)][mirror(c, col_reflection, cols)])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // Smudge a cell on a row which isn't reflected and on a column which is
+        let row_span = row_reflection.min(rows - row_reflection);
+        let unreflected_rows = (0..rows)
+            .filter(|r| !(row_reflection - row_span..row_reflection + row_span).contains(r))
+            .collect::<Vec<_>>();
+        let col_span = col_reflection.min(cols - col_reflection);
+        let r = rng.pick(&unreflected_rows);
+        let c = col_reflection - col_span + rng.index(2 * col_span);
+        pattern[r][c] = if pattern[r][c] == b'#' { b'.' } else { b'#' };
+
+        if rng.one_in(2) {
+            pattern = (0..cols)
+                .map(|c| pattern.iter().map(|row| row[c]).collect())
+                .collect();
+        }
+
+        patterns.push(
+            pattern
+                .into_iter()
+                .map(|row| String::from_utf8(row).unwrap() + "\n")
+                .collect::<String>(),
+        );
+    }
+    patterns.join("\n")
+}
+
+/// Square grid of rolling and square rocks
+fn day14(rng: &mut Rng, size: usize) -> String {
+    grid(rng, size, |rng| rng.pick(b"O#...") as char)
+}
+
+/// Initialization sequence, the labels are reused so that operations hit existing lenses.
+/// Like the real input there is no trailing new line.
+fn day15(rng: &mut Rng, size: usize) -> String {
+    let labels = (0..size / 2 + 1)
+        .map(|_| {
+            let len = rng.range(2..7) as usize;
+            rng.string(LOWERCASE, len)
+        })
+        .collect::<Vec<_>>();
+
+    (0..size)
+        .map(|_| {
+            let label = &labels[rng.index(labels.len())];
+            if rng.one_in(3) {
+                format!("{label}-")
+            } else {
+                format!("{label}={}", rng.range(1..10))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Square grid of mirrors and splitters
+fn day16(rng: &mut Rng, size: usize) -> String {
+    grid(rng, size, |rng| {
+        if rng.one_in(10) {
+            rng.pick(b"/\\|-") as char
+        } else {
+            '.'
+        }
+    })
+}
+
+/// `size` workflows which form a tree starting from `in`, followed by `size` parts
+fn day19(rng: &mut Rng, size: usize) -> String {
+    let mut names = HashSet::from([String::from("in")]);
+    let mut pending = vec![String::from("in")];
+    let mut workflows = Vec::new();
+
+    while let Some(name) = pending.pop() {
+        let mut target = |rng: &mut Rng| {
+            if names.len() < size && rng.one_in(2) {
+                loop {
+                    let len = rng.range(2..4) as usize;
+                    let name = rng.string(LOWERCASE, len);
+                    if names.insert(name.clone()) {
+                        pending.push(name.clone());
+                        return name;
+                    }
+                }
+            } else {
+                rng.pick(&["A", "R"]).to_string()
+            }
+        };
+
+        let mut rules = (0..rng.range(1..5))
+            .map(|_| {
+                let category = rng.pick(b"xmas") as char;
+                let op = rng.pick(b"<>") as char;
+                let value = rng.range(1..4001);
+                format!("{category}{op}{value}:{}", target(rng))
+            })
+            .collect::<Vec<_>>();
+        rules.push(target(rng));
+
+        workflows.push(format!("{name}{{{}}}", rules.join(",")));
+    }
+    rng.shuffle(&mut workflows);
+
+    let mut out = workflows.join("\n") + "\n\n";
+    for _ in 0..size {
+        let [x, m, a, s] = [(); 4].map(|_| rng.range(1..4001));
+        writeln!(out, "{{x={x},m={m},a={a},s={s}}}").unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days;
+
+    #[test]
+    fn test_same_seed() {
+        assert_eq!(generate(7, 50, 1), generate(7, 50, 1));
+        assert_ne!(generate(7, 50, 1), generate(7, 50, 2));
+        assert_eq!(generate(17, 50, 1), None);
+    }
+
+    #[test]
+    fn test_solvable() {
+        for day in days::DAYS.iter().filter(|d| d.is_solved()) {
+            for seed in 0..5 {
+                let input = generate(day.day, 30, seed).unwrap();
+                for part in [1, 2] {
+                    let solution = day.solve(part, &input).unwrap();
+                    assert!(
+                        solution.answer.is_ok(),
+                        "day{}::part{part} seed {seed}: {:?}\n{input}",
+                        day.day,
+                        solution.answer
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_day3_edges() {
+        // gears on the first and last column are generated too
+        let gear_on_edge = (0..20).any(|seed| {
+            let input = day3(&mut Rng::new(seed), 30);
+            input
+                .lines()
+                .any(|line| line.starts_with('*') || line.ends_with('*'))
+        });
+        assert!(gear_on_edge);
+    }
+
+    #[test]
+    fn test_day5_sizes() {
+        for size in [1, 100, 1 << 20, 1 << 33, 1 << 48, 1 << 60, u64::MAX] {
+            let max = day5_max(size);
+            let segment = max / size;
+            assert!(segment >= 1, "size {size}");
+            assert!(segment >= 1 << 16 || max == u64::MAX, "size {size}");
+            assert!((size - 1)
+                .checked_mul(segment)
+                .is_some_and(|start| start < max));
+            assert!(size.saturating_mul(100).min(max / 2) < max);
+        }
+        let input = day5(&mut Rng::new(0), 3);
+        assert_eq!(input.lines().filter(|l| l.contains("map:")).count(), 7);
+    }
+
+    #[test]
+    fn test_day8_periods() {
+        let input = day8(&mut Rng::new(0), 200);
+        // 4 ghosts with periods starting from 25 rounded up to distinct primes, `AAA` is the first one
+        assert_eq!(crate::day8::solve_part1(&input), Ok(29));
+        assert_eq!(crate::day8::solve_part2(&input), Ok(29 * 31 * 37 * 41));
+
+        // the periods are clamped to the names there are for the steps
+        let input = day8(&mut Rng::new(0), 100_000);
+        let nodes = input.lines().skip(2).count();
+        assert!(nodes <= STEP_NAMES, "{nodes}");
+        // the first period is `STEP_NAMES / 4 / 6` rounded up to a prime
+        assert_eq!(crate::day8::solve_part1(&input), Ok(1847));
+    }
+}
@@ -0,0 +1,517 @@
+//! Directed graphs with interned nodes.
+//!
+//! Every node is stored once and referred to by its [`NodeId`], which is its index in insertion
+//! order. The edges are adjacency lists with a label per edge, undirected graphs add both
+//! directions. [`Graph::to_dot`] exports the graph for Graphviz, [`dot`] does it for the days
+//! whose input is a graph.
+
+use std::borrow::Borrow;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Write};
+use std::hash::Hash;
+
+use crate::parse;
+
+/// Index of a node in its [`Graph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph<N, E = ()> {
+    nodes: Vec<N>,
+    ids: HashMap<N, NodeId>,
+    edges: Vec<Vec<(NodeId, E)>>,
+}
+
+impl<N: Hash + Eq + Clone, E> Default for Graph<N, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Hash + Eq + Clone, E> Graph<N, E> {
+    pub fn new() -> Self {
+        Graph {
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Returns the id of `node`, it's added if it isn't in the graph yet
+    pub fn add_node(&mut self, node: N) -> NodeId {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+
+        let id = NodeId(self.nodes.len().try_into().expect("too many nodes"));
+        self.nodes.push(node.clone());
+        self.ids.insert(node, id);
+        self.edges.push(Vec::new());
+        id
+    }
+
+    /// Adds an edge from `from` to `to`, the nodes are added if needed
+    pub fn add_edge(&mut self, from: N, to: N, label: E) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        self.add_edge_ids(from, to, label);
+    }
+
+    pub fn id<Q>(&self, node: &Q) -> Option<NodeId>
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.ids.get(node).copied()
+    }
+}
+
+impl<N, E> Graph<N, E> {
+    /// # Panics
+    /// If either id is not from this graph.
+    pub fn add_edge_ids(&mut self, from: NodeId, to: NodeId, label: E) {
+        assert!(
+            to.index() < self.nodes.len(),
+            "node {to} is not in the graph"
+        );
+        self.edges[from.index()].push((to, label));
+    }
+
+    pub fn node(&self, id: NodeId) -> &N {
+        &self.nodes[id.index()]
+    }
+
+    /// Number of nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.iter().map(Vec::len).sum()
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.nodes.len() as u32).map(NodeId)
+    }
+
+    /// Outgoing edges of `id` in insertion order
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, E)] {
+        &self.edges[id.index()]
+    }
+
+    pub fn neighbours(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.edges(id).iter().map(|&(to, _)| to)
+    }
+
+    /// Breadth first traversal from `start`, yields the reachable nodes with their distance
+    pub fn bfs(&self, start: NodeId) -> Bfs<'_, N, E> {
+        let mut visited = vec![false; self.len()];
+        visited[start.index()] = true;
+        Bfs {
+            graph: self,
+            visited,
+            queue: VecDeque::from([(start, 0)]),
+        }
+    }
+
+    /// Depth first traversal from `start`, yields the reachable nodes in preorder
+    pub fn dfs(&self, start: NodeId) -> Dfs<'_, N, E> {
+        Dfs {
+            graph: self,
+            visited: vec![false; self.len()],
+            stack: vec![start],
+        }
+    }
+
+    /// Weakly connected components, the direction of the edges is ignored
+    ///
+    /// The nodes of a component are sorted and the components are sorted by their first node.
+    pub fn components(&self) -> Vec<Vec<NodeId>> {
+        fn find(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+
+        let mut parents = (0..self.len()).collect::<Vec<_>>();
+        for from in self.ids() {
+            for to in self.neighbours(from) {
+                let a = find(&mut parents, from.index());
+                let b = find(&mut parents, to.index());
+                // the smaller index is the root, so the components come out in order
+                parents[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut components: Vec<Vec<NodeId>> = Vec::new();
+        let mut component_of_root = HashMap::new();
+        for id in self.ids() {
+            let root = find(&mut parents, id.index());
+            let component = *component_of_root.entry(root).or_insert_with(|| {
+                components.push(Vec::new());
+                components.len() - 1
+            });
+            components[component].push(id);
+        }
+        components
+    }
+
+    /// Strongly connected components with Tarjan's algorithm
+    ///
+    /// The components are in reverse topological order, every edge between two components goes
+    /// from a later one to an earlier one.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        const UNVISITED: usize = usize::MAX;
+
+        let mut index = vec![UNVISITED; self.len()];
+        let mut lowlink = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+        let mut components = Vec::new();
+
+        for root in self.ids() {
+            if index[root.index()] != UNVISITED {
+                continue;
+            }
+
+            // (node, position of the next edge to follow), instead of recursion
+            let mut calls = vec![(root, 0)];
+            index[root.index()] = next_index;
+            lowlink[root.index()] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root.index()] = true;
+
+            while let Some(&(v, edge)) = calls.last() {
+                if let Some(&(w, _)) = self.edges(v).get(edge) {
+                    calls.last_mut().unwrap().1 += 1;
+                    if index[w.index()] == UNVISITED {
+                        index[w.index()] = next_index;
+                        lowlink[w.index()] = next_index;
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack[w.index()] = true;
+                        calls.push((w, 0));
+                    } else if on_stack[w.index()] {
+                        lowlink[v.index()] = lowlink[v.index()].min(index[w.index()]);
+                    }
+                    continue;
+                }
+
+                calls.pop();
+                if let Some(&(parent, _)) = calls.last() {
+                    lowlink[parent.index()] = lowlink[parent.index()].min(lowlink[v.index()]);
+                }
+
+                if lowlink[v.index()] == index[v.index()] {
+                    let mut component = Vec::new();
+                    loop {
+                        let w = stack.pop().unwrap();
+                        on_stack[w.index()] = false;
+                        component.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    component.sort();
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+
+    /// Orders the nodes so that every edge goes forward, `None` if the graph has a cycle
+    ///
+    /// Of the nodes which could come next the one with the smallest id is picked.
+    pub fn topological_sort(&self) -> Option<Vec<NodeId>> {
+        let mut in_degree = vec![0; self.len()];
+        for to in self.ids().flat_map(|id| self.neighbours(id)) {
+            in_degree[to.index()] += 1;
+        }
+
+        let mut ready = std::collections::BinaryHeap::new();
+        ready.extend(
+            self.ids()
+                .filter(|id| in_degree[id.index()] == 0)
+                .map(std::cmp::Reverse),
+        );
+
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(std::cmp::Reverse(id)) = ready.pop() {
+            order.push(id);
+            for to in self.neighbours(id) {
+                in_degree[to.index()] -= 1;
+                if in_degree[to.index()] == 0 {
+                    ready.push(std::cmp::Reverse(to));
+                }
+            }
+        }
+
+        (order.len() == self.len()).then_some(order)
+    }
+
+    /// Graphviz DOT source of the graph, `edge_label` gives the optional label of each edge
+    pub fn to_dot(
+        &self,
+        node_label: impl Fn(&N) -> String,
+        edge_label: impl Fn(&E) -> Option<String>,
+    ) -> String {
+        let mut dot = String::from("digraph {\n");
+        for id in self.ids() {
+            let label = escape(&node_label(self.node(id)));
+            writeln!(dot, "    {id} [label=\"{label}\"];").unwrap();
+        }
+        for from in self.ids() {
+            for (to, edge) in self.edges(from) {
+                match edge_label(edge) {
+                    Some(label) => {
+                        writeln!(dot, "    {from} -> {to} [label=\"{}\"];", escape(&label))
+                    }
+                    None => writeln!(dot, "    {from} -> {to};"),
+                }
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub struct Bfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    visited: Vec<bool>,
+    queue: VecDeque<(NodeId, usize)>,
+}
+
+impl<N, E> Iterator for Bfs<'_, N, E> {
+    type Item = (NodeId, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, distance) = self.queue.pop_front()?;
+        for to in self.graph.neighbours(id) {
+            if !std::mem::replace(&mut self.visited[to.index()], true) {
+                self.queue.push_back((to, distance + 1));
+            }
+        }
+        Some((id, distance))
+    }
+}
+
+pub struct Dfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    visited: Vec<bool>,
+    stack: Vec<NodeId>,
+}
+
+impl<N, E> Iterator for Dfs<'_, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let id = self.stack.pop()?;
+            if std::mem::replace(&mut self.visited[id.index()], true) {
+                continue;
+            }
+
+            // reversed so that the first edge is followed first
+            let unvisited = self
+                .graph
+                .neighbours(id)
+                .filter(|to| !self.visited[to.index()])
+                .collect::<Vec<_>>();
+            self.stack.extend(unvisited.into_iter().rev());
+            return Some(id);
+        }
+    }
+}
+
+/// Graphviz DOT source of the graph in the `input` of `day`, `None` if the day has no graph
+pub fn dot(day: u8, input: &str) -> Option<parse::Result<String>> {
+    Some(match day {
+        8 => crate::day8::graph(input)
+            .map(|graph| graph.to_dot(|node| node.to_string(), |lr| Some(lr.to_string()))),
+        10 => Ok(crate::day10::graph(input).to_dot(|(row, col)| format!("{row},{col}"), |_| None)),
+        19 => crate::day19::graph(input).map(|graph| {
+            graph.to_dot(
+                |node| node.to_string(),
+                |condition| condition.map(str::to_string),
+            )
+        }),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn graph(edges: &[(u32, u32)]) -> Graph<u32> {
+        let mut graph = Graph::new();
+        for &(from, to) in edges {
+            graph.add_edge(from, to, ());
+        }
+        graph
+    }
+
+    fn nodes(graph: &Graph<u32>, ids: impl IntoIterator<Item = NodeId>) -> Vec<u32> {
+        ids.into_iter().map(|id| *graph.node(id)).collect()
+    }
+
+    #[test]
+    fn test_interning() {
+        let mut graph = Graph::<&str, char>::new();
+        graph.add_edge("AAA", "BBB", 'L');
+        graph.add_edge("AAA", "CCC", 'R');
+        graph.add_edge("BBB", "AAA", 'L');
+
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph.edge_count(), 3);
+        let aaa = graph.id("AAA").unwrap();
+        assert_eq!(graph.add_node("AAA"), aaa);
+        assert_eq!(graph.id("DDD"), None);
+        assert_eq!(
+            graph
+                .edges(aaa)
+                .iter()
+                .map(|&(to, lr)| (*graph.node(to), lr))
+                .collect::<Vec<_>>(),
+            [("BBB", 'L'), ("CCC", 'R')]
+        );
+    }
+
+    #[test]
+    fn test_traversal() {
+        //   1 - 2 - 4
+        //    \     /
+        //     3 - 5    6
+        let graph = graph(&[(1, 2), (1, 3), (2, 4), (3, 5), (5, 4), (6, 6)]);
+        let start = graph.id(&1).unwrap();
+
+        let bfs = graph
+            .bfs(start)
+            .map(|(id, distance)| (*graph.node(id), distance))
+            .collect::<Vec<_>>();
+        assert_eq!(bfs, [(1, 0), (2, 1), (3, 1), (4, 2), (5, 2)]);
+
+        assert_eq!(nodes(&graph, graph.dfs(start)), [1, 2, 4, 3, 5]);
+    }
+
+    #[test]
+    fn test_components() {
+        let graph = graph(&[(1, 2), (3, 2), (4, 5), (6, 6), (5, 4), (2, 1), (7, 3)]);
+
+        let components = graph
+            .components()
+            .into_iter()
+            .map(|c| nodes(&graph, c))
+            .collect::<Vec<_>>();
+        assert_eq!(components, [vec![1, 2, 3, 7], vec![4, 5], vec![6]]);
+
+        let strong = graph
+            .strongly_connected_components()
+            .into_iter()
+            .map(|c| nodes(&graph, c))
+            .collect::<Vec<_>>();
+        assert_eq!(strong, [vec![1, 2], vec![3], vec![4, 5], vec![6], vec![7]]);
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let dag = graph(&[(5, 3), (3, 1), (5, 1), (4, 1), (2, 4)]);
+        assert_eq!(
+            nodes(&dag, dag.topological_sort().unwrap()),
+            [5, 3, 2, 4, 1]
+        );
+
+        let cyclic = graph(&[(1, 2), (2, 3), (3, 1)]);
+        assert_eq!(cyclic.topological_sort(), None);
+    }
+
+    #[test]
+    fn test_dot() {
+        let mut graph = Graph::<&str, &str>::new();
+        graph.add_edge("in", "px", "s<1351");
+        graph.add_edge("in", "\"A\"", "");
+
+        let dot = graph.to_dot(
+            |node| node.to_string(),
+            |&label| (!label.is_empty()).then(|| label.to_string()),
+        );
+        assert_eq!(
+            dot,
+            indoc::indoc! {r#"
+            digraph {
+                0 [label="in"];
+                1 [label="px"];
+                2 [label="\"A\""];
+                0 -> 1 [label="s<1351"];
+                0 -> 2;
+            }
+            "#}
+        );
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_strongly_connected_components(
+            edges in prop::collection::vec((0..20u32, 0..20u32), 0..60)
+        ) {
+            let graph = graph(&edges);
+            let components = graph.strongly_connected_components();
+
+            let mut component_of = vec![usize::MAX; graph.len()];
+            for (i, component) in components.iter().enumerate() {
+                for id in component {
+                    prop_assert_eq!(component_of[id.index()], usize::MAX);
+                    component_of[id.index()] = i;
+                }
+            }
+
+            let reachable = |from: NodeId, to: NodeId| graph.dfs(from).any(|id| id == to);
+            for a in graph.ids() {
+                prop_assert!(component_of[a.index()] != usize::MAX);
+                for b in graph.neighbours(a) {
+                    // edges never go to a later component
+                    prop_assert!(component_of[b.index()] <= component_of[a.index()]);
+                }
+                for b in graph.ids() {
+                    let same = component_of[a.index()] == component_of[b.index()];
+                    prop_assert_eq!(same, reachable(a, b) && reachable(b, a));
+                }
+            }
+
+            // the condensation is a DAG, so without cycles a topological order exists
+            let acyclic = components.len() == graph.len()
+                && graph.ids().all(|id| graph.neighbours(id).all(|to| to != id));
+            prop_assert_eq!(graph.topological_sort().is_some(), acyclic);
+        }
+    }
+}
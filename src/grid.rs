@@ -0,0 +1,567 @@
+//! Rectangular 2D grids parsed from the puzzle inputs.
+//!
+//! Positions are `(row, col)` pairs with `(0, 0)` in the top left corner and rows growing down.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::parse;
+
+pub type Pos = (usize, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    /// The four directions which move along a row or a column, clockwise from up
+    pub const ORTHOGONAL: [Direction; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    /// All eight directions, clockwise from up left
+    pub const ALL: [Direction; 8] = [
+        Self::UpLeft,
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+    ];
+
+    /// Change of `(row, col)` when moving one step in this direction
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Self::Up => (-1, 0),
+            Self::Down => (1, 0),
+            Self::Left => (0, -1),
+            Self::Right => (0, 1),
+            Self::UpLeft => (-1, -1),
+            Self::UpRight => (-1, 1),
+            Self::DownLeft => (1, -1),
+            Self::DownRight => (1, 1),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::UpLeft => Self::DownRight,
+            Self::UpRight => Self::DownLeft,
+            Self::DownLeft => Self::UpRight,
+            Self::DownRight => Self::UpLeft,
+        }
+    }
+
+    /// Direction after turning 90 degrees clockwise
+    pub fn turn_right(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+            Self::UpLeft => Self::UpRight,
+            Self::UpRight => Self::DownRight,
+            Self::DownRight => Self::DownLeft,
+            Self::DownLeft => Self::UpLeft,
+        }
+    }
+
+    /// Direction after turning 90 degrees counter clockwise
+    pub fn turn_left(self) -> Self {
+        self.turn_right().opposite()
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Self::Up | Self::Down)
+    }
+
+    pub fn is_horizontal(self) -> bool {
+        matches!(self, Self::Left | Self::Right)
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Set of directions stored as bits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DirectionSet(u8);
+
+impl DirectionSet {
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Adds `dir` to the set, returns `false` if it was already in it
+    pub fn insert(&mut self, dir: Direction) -> bool {
+        let is_new = !self.contains(dir);
+        self.0 |= dir.bit();
+        is_new
+    }
+
+    pub fn contains(&self, dir: Direction) -> bool {
+        self.0 & dir.bit() != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid from row major `data`
+    pub fn new(rows: usize, cols: usize, data: Vec<T>) -> Self {
+        assert_eq!(data.len(), rows * cols, "grid data has wrong length");
+        Self { data, rows, cols }
+    }
+
+    pub fn filled(rows: usize, cols: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(rows, cols, vec![value; rows * cols])
+    }
+
+    /// Parses a grid where every char of the input is mapped to one cell by `f`.
+    ///
+    /// # Panics
+    ///
+    /// If the lines are not of equal length.
+    pub fn parse(input: &str, mut f: impl FnMut(char) -> T) -> Self {
+        let cols = input.lines().next().map_or(0, |l| l.chars().count());
+        let mut data = Vec::with_capacity(input.len());
+        let mut rows = 0;
+        for line in input.lines() {
+            let len = data.len();
+            data.extend(line.chars().map(&mut f));
+            assert_eq!(data.len() - len, cols, "line {} has wrong length", rows + 1);
+            rows += 1;
+        }
+
+        Self::new(rows, cols, data)
+    }
+
+    /// Same as [`Grid::parse`] but returns the errors of `f` and an error for lines of different lengths
+    pub fn try_parse(
+        input: &str,
+        mut f: impl FnMut(char) -> parse::Result<T>,
+    ) -> parse::Result<Self> {
+        let cols = input.lines().next().map_or(0, |l| l.chars().count());
+        let mut data = Vec::with_capacity(input.len());
+        let mut rows = 0;
+        for line in input.lines() {
+            let len = data.len();
+            for ch in line.chars() {
+                data.push(f(ch)?);
+            }
+            if data.len() - len != cols {
+                return Err(parse::ParseError::WrongWidth {
+                    line: rows + 1,
+                    expected: cols,
+                    found: data.len() - len,
+                });
+            }
+            rows += 1;
+        }
+
+        Ok(Self::new(rows, cols, data))
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn get(&self, (row, col): Pos) -> Option<&T> {
+        (row < self.rows && col < self.cols).then(|| &self.data[row * self.cols + col])
+    }
+
+    pub fn get_mut(&mut self, (row, col): Pos) -> Option<&mut T> {
+        (row < self.rows && col < self.cols).then(|| &mut self.data[row * self.cols + col])
+    }
+
+    /// Position one step from `pos` in `dir` if it is inside the grid
+    pub fn step(&self, (row, col): Pos, dir: Direction) -> Option<Pos> {
+        let (drow, dcol) = dir.offset();
+        let row = row.checked_add_signed(drow).filter(|&r| r < self.rows)?;
+        let col = col.checked_add_signed(dcol).filter(|&c| c < self.cols)?;
+        Some((row, col))
+    }
+
+    /// Up, right, down and left neighbours of `pos` inside the grid
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = (Direction, Pos)> + '_ {
+        Direction::ORTHOGONAL
+            .into_iter()
+            .filter_map(move |dir| Some((dir, self.step(pos, dir)?)))
+    }
+
+    /// All eight neighbours of `pos` inside the grid
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = (Direction, Pos)> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |dir| Some((dir, self.step(pos, dir)?)))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        &mut self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    pub fn col(&self, col: usize) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        assert!(col < self.cols, "column {col} is out of bounds");
+        self.data[col..].iter().step_by(self.cols)
+    }
+
+    pub fn iter_rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + '_ {
+        self.data.chunks_exact(self.cols.max(1))
+    }
+
+    pub fn iter_cols(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = impl DoubleEndedIterator<Item = &T> + '_> + '_ {
+        (0..self.cols).map(|c| self.col(c))
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.data.iter_mut()
+    }
+
+    /// All positions in row major order
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |r| (0..cols).map(move |c| (r, c)))
+    }
+
+    /// Position of the first cell in row major order which matches `pred`
+    pub fn position(&self, pred: impl FnMut(&T) -> bool) -> Option<Pos> {
+        let i = self.data.iter().position(pred)?;
+        Some((i / self.cols, i % self.cols))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.rows, self.cols, self.data.iter().map(f).collect())
+    }
+
+    /// Grid where rows are the columns of this grid
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        let data = self.iter_cols().flatten().cloned().collect();
+        Self::new(self.cols, self.rows, data)
+    }
+
+    pub fn rotate_clockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        let data = self.iter_cols().flat_map(Iterator::rev).cloned().collect();
+        Self::new(self.cols, self.rows, data)
+    }
+
+    pub fn rotate_counter_clockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        let data = self.iter_cols().rev().flatten().cloned().collect();
+        Self::new(self.cols, self.rows, data)
+    }
+
+    pub fn cursor(&self, pos: Pos) -> Cursor<'_, T> {
+        Cursor { grid: self, pos }
+    }
+
+    pub fn cursor_mut(&mut self, pos: Pos) -> CursorMut<'_, T> {
+        CursorMut { grid: self, pos }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): Pos) -> &T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "({row}, {col}) is out of bounds"
+        );
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, (row, col): Pos) -> &mut T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "({row}, {col}) is out of bounds"
+        );
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.iter_rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Position in a grid which can be moved around, it never leaves the grid
+#[derive(Debug, Clone)]
+pub struct Cursor<'a, T> {
+    grid: &'a Grid<T>,
+    pos: Pos,
+}
+
+impl<'a, T> Cursor<'a, T> {
+    pub fn pos(&self) -> Pos {
+        self.pos
+    }
+
+    pub fn current(&self) -> &'a T {
+        &self.grid[self.pos]
+    }
+
+    /// Cell one step in `dir` from the cursor
+    pub fn peek(&self, dir: Direction) -> Option<&'a T> {
+        let grid = self.grid;
+        grid.step(self.pos, dir).map(|pos| &grid[pos])
+    }
+
+    /// Moves one step in `dir`, returns `false` and stays put if that would leave the grid
+    pub fn step(&mut self, dir: Direction) -> bool {
+        match self.grid.step(self.pos, dir) {
+            Some(pos) => {
+                self.pos = pos;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves to `pos`, returns `false` and stays put if it is outside the grid
+    pub fn set_pos(&mut self, pos: Pos) -> bool {
+        let inside = self.grid.get(pos).is_some();
+        if inside {
+            self.pos = pos;
+        }
+        inside
+    }
+}
+
+/// [`Cursor`] which can modify the cells
+#[derive(Debug)]
+pub struct CursorMut<'a, T> {
+    grid: &'a mut Grid<T>,
+    pos: Pos,
+}
+
+impl<T> CursorMut<'_, T> {
+    pub fn pos(&self) -> Pos {
+        self.pos
+    }
+
+    pub fn current(&self) -> &T {
+        &self.grid[self.pos]
+    }
+
+    pub fn current_mut(&mut self) -> &mut T {
+        &mut self.grid[self.pos]
+    }
+
+    pub fn peek(&self, dir: Direction) -> Option<&T> {
+        self.grid.step(self.pos, dir).map(|pos| &self.grid[pos])
+    }
+
+    pub fn step(&mut self, dir: Direction) -> bool {
+        match self.grid.step(self.pos, dir) {
+            Some(pos) => {
+                self.pos = pos;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_pos(&mut self, pos: Pos) -> bool {
+        let inside = self.grid.get(pos).is_some();
+        if inside {
+            self.pos = pos;
+        }
+        inside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = indoc::indoc! {"
+    abc
+    def
+    "};
+
+    fn grid() -> Grid<char> {
+        Grid::parse(TEST_INPUT, |c| c)
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = grid();
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid[(1, 0)], 'd');
+        assert_eq!(grid.get((0, 3)), None);
+        assert_eq!(grid.position(|&c| c == 'f'), Some((1, 2)));
+        assert_eq!(grid.to_string(), TEST_INPUT);
+    }
+
+    #[test]
+    #[should_panic(expected = "line 2 has wrong length")]
+    fn test_parse_ragged() {
+        Grid::parse("abc\nde\n", |c| c);
+    }
+
+    #[test]
+    fn test_try_parse() {
+        assert_eq!(Grid::try_parse(TEST_INPUT, Ok), Ok(grid()));
+        assert_eq!(
+            Grid::try_parse("abc\nde\n", Ok),
+            Err(parse::ParseError::WrongWidth {
+                line: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+        let digit = |c: char| {
+            c.to_digit(10).ok_or(parse::ParseError::InvalidValue {
+                expected: "digit",
+                input: c.to_string(),
+            })
+        };
+        assert!(Grid::try_parse("12\n3x\n", digit).is_err());
+    }
+
+    #[test]
+    fn test_rows_and_cols() {
+        let grid = grid();
+        assert_eq!(grid.row(1), ['d', 'e', 'f']);
+        assert_eq!(grid.col(1).collect::<String>(), "be");
+        assert_eq!(grid.col(2).rev().collect::<String>(), "fc");
+        assert_eq!(grid.iter_rows().count(), 2);
+        let cols = grid
+            .iter_cols()
+            .map(|c| c.collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(cols, ["ad", "be", "cf"]);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = grid();
+        let n4 = grid.neighbours4((0, 1)).collect::<Vec<_>>();
+        assert_eq!(
+            n4,
+            [
+                (Direction::Right, (0, 2)),
+                (Direction::Down, (1, 1)),
+                (Direction::Left, (0, 0)),
+            ]
+        );
+
+        let n8 = grid
+            .neighbours8((1, 0))
+            .map(|(_, p)| grid[p])
+            .collect::<String>();
+        assert_eq!(n8, "abe");
+        assert_eq!(grid.neighbours8((0, 1)).count(), 5);
+    }
+
+    #[test]
+    fn test_transform() {
+        let grid = grid();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_counter_clockwise().to_string(), "cf\nbe\nad\n");
+        assert_eq!(grid.transpose().transpose(), grid);
+        assert_eq!((0..4).fold(grid.clone(), |g, _| g.rotate_clockwise()), grid);
+        assert_eq!(grid.rotate_clockwise().rotate_counter_clockwise(), grid);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let grid = grid();
+        let mut cursor = grid.cursor((0, 0));
+        assert_eq!(cursor.peek(Direction::Left), None);
+        assert_eq!(cursor.peek(Direction::DownRight), Some(&'e'));
+        assert!(!cursor.step(Direction::Up));
+        assert!(cursor.step(Direction::Right));
+        assert!(cursor.step(Direction::Down));
+        assert_eq!((cursor.pos(), cursor.current()), ((1, 1), &'e'));
+        assert!(!cursor.set_pos((2, 0)));
+        assert_eq!(cursor.pos(), (1, 1));
+
+        let mut grid = grid;
+        let mut cursor = grid.cursor_mut((1, 2));
+        *cursor.current_mut() = 'x';
+        assert!(cursor.step(Direction::UpLeft));
+        *cursor.current_mut() = 'y';
+        assert_eq!(grid.to_string(), "ayc\ndex\n");
+    }
+
+    #[test]
+    fn test_direction() {
+        for dir in Direction::ALL {
+            assert_eq!(dir.opposite().opposite(), dir);
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            let (dr, dc) = dir.offset();
+            assert_eq!(dir.opposite().offset(), (-dr, -dc));
+        }
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+
+        let mut set = DirectionSet::empty();
+        assert!(set.is_empty());
+        assert!(set.insert(Direction::Left));
+        assert!(!set.insert(Direction::Left));
+        assert!(set.contains(Direction::Left));
+        assert!(!set.contains(Direction::Right));
+    }
+}
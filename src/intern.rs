@@ -0,0 +1,171 @@
+//! Dense ids for the names of nodes, workflows and the like in the inputs.
+//!
+//! [`Interner`] numbers arbitrary names in the order they are first seen, so the ids can index a
+//! `Vec` instead of hashing the name on every lookup. Three character names like `AAA` or `11Z`
+//! don't even need that, [`code`] turns them into an index of an array with [`CODES`] entries.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Integer type of the ids
+pub trait Id: Copy + Eq + Hash + Debug {
+    /// # Panics
+    /// If `index` doesn't fit the type.
+    fn from_index(index: usize) -> Self;
+
+    fn index(self) -> usize;
+}
+
+impl Id for u16 {
+    fn from_index(index: usize) -> Self {
+        index.try_into().expect("too many names for u16 ids")
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl Id for u32 {
+    fn from_index(index: usize) -> Self {
+        index.try_into().expect("too many names for u32 ids")
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Gives every distinct name the next free id, starting from 0
+#[derive(Debug, Clone)]
+pub struct Interner<'a, I = u32> {
+    ids: HashMap<&'a str, I>,
+    names: Vec<&'a str>,
+}
+
+impl<I: Id> Default for Interner<'_, I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, I: Id> Interner<'a, I> {
+    pub fn new() -> Self {
+        Interner {
+            ids: HashMap::new(),
+            names: Vec::new(),
+        }
+    }
+
+    /// Returns the id of `name`, a new one if it wasn't seen before
+    pub fn intern(&mut self, name: &'a str) -> I {
+        *self.ids.entry(name).or_insert_with(|| {
+            self.names.push(name);
+            I::from_index(self.names.len() - 1)
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<I> {
+        self.ids.get(name).copied()
+    }
+
+    /// # Panics
+    /// If the id wasn't returned by this interner.
+    pub fn name(&self, id: I) -> &'a str {
+        self.names[id.index()]
+    }
+
+    /// Names in the order of their ids
+    pub fn names(&self) -> &[&'a str] {
+        &self.names
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Number of three character names, every [`code`] is below it
+pub const CODES: usize = 36 * 36 * 36;
+
+/// Encodes a name of three digits or uppercase letters, `None` for any other name
+///
+/// The characters are digits of a base 36 number, so the codes of names ending with the same
+/// character are equal modulo 36.
+pub fn code(name: &str) -> Option<u16> {
+    let &[a, b, c] = name.as_bytes() else {
+        return None;
+    };
+    Some((digit(a)? * 36 + digit(b)?) * 36 + digit(c)?)
+}
+
+/// The name of a [`code`]
+///
+/// # Panics
+/// If `code` is not below [`CODES`].
+pub fn decode(code: u16) -> [u8; 3] {
+    assert!(
+        (code as usize) < CODES,
+        "{code} is not a three character code"
+    );
+    let chars = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let code = code as usize;
+    [
+        chars[code / (36 * 36)],
+        chars[code / 36 % 36],
+        chars[code % 36],
+    ]
+}
+
+fn digit(c: u8) -> Option<u16> {
+    match c {
+        b'0'..=b'9' => Some((c - b'0') as u16),
+        b'A'..=b'Z' => Some((c - b'A') as u16 + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_interner() {
+        let mut names = Interner::<u16>::new();
+        assert_eq!(names.intern("in"), 0);
+        assert_eq!(names.intern("px"), 1);
+        assert_eq!(names.intern("in"), 0);
+        assert_eq!(names.get("px"), Some(1));
+        assert_eq!(names.get("qkq"), None);
+        assert_eq!(names.name(1), "px");
+        assert_eq!(names.names(), ["in", "px"]);
+        assert_eq!(names.len(), 2);
+    }
+
+    #[test]
+    fn test_code() {
+        assert_eq!(code("000"), Some(0));
+        assert_eq!(code("ZZZ"), Some(CODES as u16 - 1));
+        assert_eq!(code("11A").map(|c| c % 36), code("AAA").map(|c| c % 36));
+        assert_eq!(code("aaa"), None);
+        assert_eq!(code("AAAA"), None);
+        assert_eq!(code("AA"), None);
+        assert_eq!(&decode(code("11Z").unwrap()), b"11Z");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_decode(name in "[0-9A-Z]{3}") {
+            let code = code(&name).unwrap();
+            prop_assert!((code as usize) < CODES);
+            prop_assert_eq!(&decode(code), name.as_bytes());
+        }
+    }
+}
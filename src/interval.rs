@@ -0,0 +1,449 @@
+//! Arithmetic on half-open ranges, for the days which handle whole ranges of values at once.
+//!
+//! Empty ranges are never returned, operations which would produce one return `None` instead.
+
+use std::ops::{Add, Range, Sub};
+
+fn non_empty<T: Ord>(range: Range<T>) -> Option<Range<T>> {
+    (range.start < range.end).then_some(range)
+}
+
+/// Number of values in `range`, `0` for empty ranges
+pub fn len<T>(range: &Range<T>) -> u64
+where
+    T: Ord + Copy + Sub<Output = T> + Into<u64>,
+{
+    if range.start < range.end {
+        (range.end - range.start).into()
+    } else {
+        0
+    }
+}
+
+/// Splits `range` into the values below `at` and the values from `at` on
+pub fn split<T: Ord + Copy>(range: &Range<T>, at: T) -> (Option<Range<T>>, Option<Range<T>>) {
+    let at = at.clamp(range.start, range.end.max(range.start));
+    (non_empty(range.start..at), non_empty(at..range.end))
+}
+
+/// Values which are in both `a` and `b`
+pub fn intersect<T: Ord + Copy>(a: &Range<T>, b: &Range<T>) -> Option<Range<T>> {
+    non_empty(a.start.max(b.start)..a.end.min(b.end))
+}
+
+/// Values of `a` which are not in `b`, the part below `b` and the part above it
+pub fn subtract<T: Ord + Copy>(a: &Range<T>, b: &Range<T>) -> (Option<Range<T>>, Option<Range<T>>) {
+    if b.start >= b.end {
+        return (non_empty(a.clone()), None);
+    }
+
+    (
+        non_empty(a.start..a.end.min(b.start)),
+        non_empty(a.start.max(b.end)..a.end),
+    )
+}
+
+/// Single range with the values of both `a` and `b`, `None` if there is a gap between them
+pub fn union<T: Ord + Copy>(a: &Range<T>, b: &Range<T>) -> Option<Range<T>> {
+    match (non_empty(a.clone()), non_empty(b.clone())) {
+        (None, None) => None,
+        (Some(r), None) | (None, Some(r)) => Some(r),
+        (Some(a), Some(b)) => {
+            (a.start <= b.end && b.start <= a.end).then(|| a.start.min(b.start)..a.end.max(b.end))
+        }
+    }
+}
+
+/// Set of values stored as sorted ranges which don't overlap or touch
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RangeSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for RangeSet<T> {
+    fn default() -> Self {
+        Self { ranges: Vec::new() }
+    }
+}
+
+impl<T: Ord + Copy> RangeSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Smallest value in the set
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= value);
+        self.ranges.get(i).is_some_and(|r| r.contains(&value))
+    }
+
+    /// Adds the values of `range`, merging it with the ranges it overlaps or touches
+    pub fn insert(&mut self, range: Range<T>) {
+        let Some(mut range) = non_empty(range) else {
+            return;
+        };
+
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        if first < last {
+            range.start = range.start.min(self.ranges[first].start);
+            range.end = range.end.max(self.ranges[last - 1].end);
+        }
+        self.ranges.splice(first..last, [range]);
+    }
+
+    /// Removes the values of `range`
+    pub fn remove(&mut self, range: Range<T>) {
+        let Some(range) = non_empty(range) else {
+            return;
+        };
+
+        let first = self.ranges.partition_point(|r| r.end <= range.start);
+        let last = self.ranges.partition_point(|r| r.start < range.end);
+        if first == last {
+            return;
+        }
+
+        let (below, _) = subtract(&self.ranges[first], &range);
+        let (_, above) = subtract(&self.ranges[last - 1], &range);
+        self.ranges
+            .splice(first..last, below.into_iter().chain(above));
+    }
+
+    /// Total number of values in the set
+    pub fn count(&self) -> u64
+    where
+        T: Sub<Output = T> + Into<u64>,
+    {
+        self.ranges.iter().map(len).sum()
+    }
+}
+
+impl<T: Ord + Copy> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+/// Piecewise map which moves the values of its source ranges to new starting points
+///
+/// Values outside of every source range are mapped to themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OffsetMap<T> {
+    /// Source ranges with their destination start, sorted by the source start
+    entries: Vec<(Range<T>, T)>,
+}
+
+impl<T> Default for OffsetMap<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<T> OffsetMap<T>
+where
+    T: Ord + Copy + Add<Output = T> + Sub<Output = T>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps the values of `source` to `destination_start..`
+    ///
+    /// # Panics
+    /// If `source` overlaps an already inserted source range.
+    pub fn insert(&mut self, source: Range<T>, destination_start: T) {
+        let Some(source) = non_empty(source) else {
+            return;
+        };
+
+        assert!(
+            !self.overlaps(&source),
+            "source ranges of an offset map can't overlap"
+        );
+        let i = self.entries.partition_point(|(r, _)| r.end <= source.start);
+        self.entries.insert(i, (source, destination_start));
+    }
+
+    /// Whether `source` overlaps any of the source ranges
+    pub fn overlaps(&self, source: &Range<T>) -> bool {
+        let i = self.entries.partition_point(|(r, _)| r.end <= source.start);
+        self.entries
+            .get(i)
+            .is_some_and(|(r, _)| intersect(r, source).is_some())
+    }
+
+    pub fn get(&self, value: T) -> T {
+        let i = self.entries.partition_point(|(r, _)| r.end <= value);
+        match self.entries.get(i) {
+            Some((source, destination)) if source.contains(&value) => {
+                *destination + (value - source.start)
+            }
+            _ => value,
+        }
+    }
+
+    /// Maps every value of `range`, the mapped values are added to `out`
+    pub fn map_range_into(&self, range: &Range<T>, out: &mut RangeSet<T>) {
+        let mut rest = range.start;
+        let first = self.entries.partition_point(|(r, _)| r.end <= range.start);
+
+        for (source, destination) in &self.entries[first..] {
+            if source.start >= range.end {
+                break;
+            }
+
+            // values between the source ranges map to themselves
+            if let Some(gap) = non_empty(rest..source.start.min(range.end)) {
+                out.insert(gap);
+            }
+
+            if let Some(overlap) = intersect(source, range) {
+                let start = *destination + (overlap.start - source.start);
+                out.insert(start..start + (overlap.end - overlap.start));
+                rest = overlap.end;
+            }
+        }
+
+        if let Some(tail) = non_empty(rest..range.end) {
+            out.insert(tail);
+        }
+    }
+
+    /// Maps every value of `range`
+    pub fn map_range(&self, range: &Range<T>) -> RangeSet<T> {
+        let mut out = RangeSet::new();
+        self.map_range_into(range, &mut out);
+        out
+    }
+
+    /// Maps every value of `set`
+    pub fn map_set(&self, set: &RangeSet<T>) -> RangeSet<T> {
+        let mut out = RangeSet::new();
+        for range in set.ranges() {
+            self.map_range_into(range, &mut out);
+        }
+        out
+    }
+}
+
+/// Axis aligned box in `N` dimensions, the product of one range per axis
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cuboid<T, const N: usize> {
+    pub ranges: [Range<T>; N],
+}
+
+impl<T: Ord + Copy, const N: usize> Cuboid<T, N> {
+    pub fn new(ranges: [Range<T>; N]) -> Self {
+        Self { ranges }
+    }
+
+    /// Cuboid with the same `range` on every axis
+    pub fn cube(range: Range<T>) -> Self {
+        Self::new(std::array::from_fn(|_| range.clone()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.iter().any(|r| r.start >= r.end)
+    }
+
+    pub fn contains(&self, point: &[T; N]) -> bool {
+        self.ranges.iter().zip(point).all(|(r, v)| r.contains(v))
+    }
+
+    /// Number of points in the cuboid
+    pub fn volume(&self) -> u64
+    where
+        T: Sub<Output = T> + Into<u64>,
+    {
+        self.ranges.iter().map(len).product()
+    }
+
+    /// Splits the cuboid along `axis` into the part below `at` and the part from `at` on
+    pub fn split(&self, axis: usize, at: T) -> (Option<Self>, Option<Self>) {
+        if self.is_empty() {
+            return (None, None);
+        }
+
+        let (below, above) = split(&self.ranges[axis], at);
+        let with_range = |range: Range<T>| {
+            let mut cuboid = self.clone();
+            cuboid.ranges[axis] = range;
+            cuboid
+        };
+        (below.map(with_range), above.map(with_range))
+    }
+
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let mut ranges = self.ranges.clone();
+        for (range, other) in ranges.iter_mut().zip(&other.ranges) {
+            *range = intersect(range, other)?;
+        }
+        Some(Self { ranges })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split(&(2..5), 0), (None, Some(2..5)));
+        assert_eq!(split(&(2..5), 2), (None, Some(2..5)));
+        assert_eq!(split(&(2..5), 3), (Some(2..3), Some(3..5)));
+        assert_eq!(split(&(2..5), 4), (Some(2..4), Some(4..5)));
+        assert_eq!(split(&(2..5), 5), (Some(2..5), None));
+        assert_eq!(split(&(2..5), 9), (Some(2..5), None));
+        assert_eq!(split(&(3..3), 3), (None, None));
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 5..2;
+        assert_eq!(split(&reversed, 3), (None, None));
+    }
+
+    #[test]
+    fn test_intersect() {
+        assert_eq!(intersect(&(0..5), &(5..9)), None);
+        assert_eq!(intersect(&(0..5), &(4..9)), Some(4..5));
+        assert_eq!(intersect(&(0..9), &(3..4)), Some(3..4));
+        assert_eq!(intersect(&(3..4), &(0..9)), Some(3..4));
+        assert_eq!(intersect(&(0..5), &(0..5)), Some(0..5));
+        assert_eq!(intersect(&(0..5), &(2..2)), None);
+    }
+
+    #[test]
+    fn test_subtract() {
+        assert_eq!(subtract(&(0..5), &(5..9)), (Some(0..5), None));
+        assert_eq!(subtract(&(5..9), &(0..5)), (None, Some(5..9)));
+        assert_eq!(subtract(&(0..9), &(3..5)), (Some(0..3), Some(5..9)));
+        assert_eq!(subtract(&(0..9), &(0..9)), (None, None));
+        assert_eq!(subtract(&(0..9), &(0..1)), (None, Some(1..9)));
+        assert_eq!(subtract(&(0..9), &(8..12)), (Some(0..8), None));
+        assert_eq!(subtract(&(0..9), &(4..4)), (Some(0..9), None));
+    }
+
+    #[test]
+    fn test_union() {
+        assert_eq!(union(&(0..5), &(5..9)), Some(0..9));
+        assert_eq!(union(&(5..9), &(0..5)), Some(0..9));
+        assert_eq!(union(&(0..5), &(6..9)), None);
+        assert_eq!(union(&(0..9), &(2..3)), Some(0..9));
+        assert_eq!(union(&(4..4), &(6..9)), Some(6..9));
+        assert_eq!(union(&(4..4), &(6..6)), None);
+    }
+
+    #[test]
+    fn test_range_set() {
+        let mut set = [10..20, 30..40, 0..1]
+            .into_iter()
+            .collect::<RangeSet<u32>>();
+        assert_eq!(set.ranges(), [0..1, 10..20, 30..40]);
+
+        // touching ranges are merged
+        set.insert(20..25);
+        set.insert(1..2);
+        assert_eq!(set.ranges(), [0..2, 10..25, 30..40]);
+
+        set.insert(5..30);
+        assert_eq!(set.ranges(), [0..2, 5..40]);
+        assert_eq!(set.count(), 37);
+        assert!(set.contains(0) && set.contains(39));
+        assert!(!set.contains(2) && !set.contains(4) && !set.contains(40));
+
+        set.remove(10..12);
+        set.remove(0..1);
+        set.remove(39..100);
+        set.remove(2..5);
+        assert_eq!(set.ranges(), [1..2, 5..10, 12..39]);
+
+        set.remove(1..39);
+        assert!(set.is_empty());
+        assert_eq!(set.min(), None);
+    }
+
+    #[test]
+    fn test_offset_map() {
+        let mut map = OffsetMap::new();
+        map.insert(98..100, 50u64);
+        map.insert(50..98, 52);
+
+        assert_eq!(map.get(0), 0);
+        assert_eq!(map.get(49), 49);
+        assert_eq!(map.get(50), 52);
+        assert_eq!(map.get(97), 99);
+        assert_eq!(map.get(98), 50);
+        assert_eq!(map.get(99), 51);
+        assert_eq!(map.get(100), 100);
+
+        assert_eq!(map.map_range(&(0..10)).ranges(), vec![0..10]);
+        assert_eq!(map.map_range(&(45..55)).ranges(), [45..50, 52..57]);
+        // 96..98 is mapped next to the unmapped 100..102
+        assert_eq!(map.map_range(&(96..102)).ranges(), [50..52, 98..102]);
+
+        // every mapped value of a range is in the mapped set and nothing else
+        for start in 40..105 {
+            for end in start..106 {
+                let mapped = map.map_range(&(start..end));
+                let expected = (start..end).map(|v| map.get(v)).map(|v| v..v + 1);
+                assert_eq!(mapped, expected.collect(), "{start}..{end}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic = "can't overlap"]
+    fn test_offset_map_overlap() {
+        let mut map = OffsetMap::new();
+        map.insert(10..20, 0u32);
+        assert!(map.overlaps(&(19..30)));
+        assert!(!map.overlaps(&(20..30)));
+        assert!(!map.overlaps(&(5..10)));
+        map.insert(19..30, 0);
+    }
+
+    #[test]
+    fn test_cuboid() {
+        let cuboid = Cuboid::cube(1u16..11);
+        assert_eq!(cuboid.volume(), 10_000);
+        assert!(cuboid.contains(&[1, 5, 10, 7]));
+        assert!(!cuboid.contains(&[1, 5, 11, 7]));
+
+        let (below, above) = cuboid.split(2, 4);
+        let (below, above) = (below.unwrap(), above.unwrap());
+        assert_eq!(below.ranges[2], 1..4);
+        assert_eq!(above.ranges[2], 4..11);
+        assert_eq!(below.volume() + above.volume(), cuboid.volume());
+
+        assert_eq!(cuboid.split(0, 1), (None, Some(cuboid.clone())));
+        assert_eq!(cuboid.split(0, 11), (Some(cuboid.clone()), None));
+
+        let other = Cuboid::new([0..2, 5..20, 3..4, 10..11]);
+        let overlap = cuboid.intersect(&other).unwrap();
+        assert_eq!(overlap.ranges, [1..2, 5..11, 3..4, 10..11]);
+        assert_eq!(overlap.volume(), 6);
+        assert_eq!(cuboid.intersect(&Cuboid::cube(11..12)), None);
+
+        let empty = Cuboid::new([1u16..1, 0..5]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.volume(), 0);
+        assert_eq!(empty.split(1, 2), (None, None));
+    }
+}
@@ -0,0 +1,274 @@
+//! Viewer for the private leaderboard json which can be downloaded from
+//! `https://adventofcode.com/{year}/leaderboard/private/view/{id}.json`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::{anyhow, Context};
+use serde_json::Value;
+
+pub struct Leaderboard {
+    pub event: String,
+    pub owner_id: u64,
+    pub members: Vec<Member>,
+}
+
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    pub stars: u32,
+    pub local_score: u64,
+    pub last_star_ts: u64,
+    /// Timestamps when the stars of part 1 and 2 were got for each day
+    pub days: BTreeMap<u8, [Option<u64>; 2]>,
+}
+
+impl Leaderboard {
+    pub fn parse(json: &str) -> anyhow::Result<Self> {
+        let value = serde_json::from_str::<Value>(json).context("leaderboard is not valid json")?;
+        let event = match &value["event"] {
+            Value::String(s) => s.clone(),
+            v => v.to_string(),
+        };
+        let owner_id = as_u64(&value["owner_id"]).context("missing `owner_id`")?;
+        let members = value["members"]
+            .as_object()
+            .context("missing `members`")?
+            .values()
+            .map(Member::parse)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            event,
+            owner_id,
+            members,
+        })
+    }
+
+    /// Members ordered by local score, ties are broken by who got their last star first
+    pub fn ranking(&self) -> Vec<&Member> {
+        let mut members = self.members.iter().collect::<Vec<_>>();
+        members.sort_by_key(|m| {
+            (
+                std::cmp::Reverse(m.local_score),
+                m.last_star_ts == 0,
+                m.last_star_ts,
+                m.id,
+            )
+        });
+        members
+    }
+}
+
+impl Member {
+    fn parse(value: &Value) -> anyhow::Result<Self> {
+        let id = as_u64(&value["id"]).context("member without an `id`")?;
+        let field = |name: &str| {
+            as_u64(&value[name]).with_context(|| format!("member {id} has no `{name}`"))
+        };
+
+        let mut days = BTreeMap::new();
+        if let Some(levels) = value["completion_day_level"].as_object() {
+            for (day, parts) in levels {
+                let day = day
+                    .parse::<u8>()
+                    .map_err(|_| anyhow!("member {id} has invalid day `{day}`"))?;
+                let ts = |part: &str| as_u64(&parts[part]["get_star_ts"]);
+                days.insert(day, [ts("1"), ts("2")]);
+            }
+        }
+
+        let last_star_ts = as_u64(&value["last_star_ts"])
+            .or_else(|| days.values().flatten().flatten().copied().max())
+            .unwrap_or(0);
+
+        Ok(Self {
+            id,
+            name: value["name"].as_str().map(str::to_string),
+            stars: field("stars")? as u32,
+            local_score: field("local_score")?,
+            last_star_ts,
+            days,
+        })
+    }
+
+    /// Name as shown on the website
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    /// Seconds between getting the first and the second star of the day
+    pub fn delta(&self, day: u8) -> Option<u64> {
+        match self.days.get(&day)? {
+            [Some(part1), Some(part2)] => Some(part2.saturating_sub(*part1)),
+            _ => None,
+        }
+    }
+}
+
+/// Timestamps are sometimes numbers and sometimes strings
+fn as_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+impl fmt::Display for Leaderboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranking = self.ranking();
+        writeln!(
+            f,
+            "Private leaderboard #{} of {}, {} members",
+            self.owner_id,
+            self.event,
+            ranking.len()
+        )?;
+        writeln!(f)?;
+        writeln!(f, "Rank  Score  Stars  Name")?;
+        for (i, member) in ranking.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:>5}  {:>5}  {}",
+                i + 1,
+                member.local_score,
+                member.stars,
+                member.display_name()
+            )?;
+        }
+
+        for member in ranking {
+            writeln!(f)?;
+            if member.days.is_empty() {
+                writeln!(f, "{}: no stars", member.display_name())?;
+                continue;
+            }
+
+            writeln!(f, "{}:", member.display_name())?;
+            writeln!(f, "  Day  Part 1 (UTC)         Part 2 (UTC)         Delta")?;
+            for (&day, parts) in &member.days {
+                let [part1, part2] = parts.map(|ts| ts.map_or("-".to_string(), format_timestamp));
+                let delta = member.delta(day).map_or("-".to_string(), format_duration);
+                writeln!(f, "  {day:>3}  {part1:<19}  {part2:<19}  {delta}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats unix timestamp as `YYYY-MM-DD hh:mm:ss` in UTC
+pub fn format_timestamp(ts: u64) -> String {
+    let (days, secs) = (ts / 86400, ts % 86400);
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Formats duration in seconds as `hh:mm:ss`, hours can be larger than 24
+pub fn format_duration(secs: u64) -> String {
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) in the proleptic Gregorian calendar.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = include_str!("../tests/fixtures/leaderboard.json");
+
+    #[test]
+    fn test_parse() {
+        let board = Leaderboard::parse(TEST_INPUT).unwrap();
+        assert_eq!(board.event, "2023");
+        assert_eq!(board.owner_id, 1);
+        assert_eq!(board.members.len(), 3);
+
+        let alice = board.members.iter().find(|m| m.id == 1).unwrap();
+        assert_eq!(alice.stars, 3);
+        assert_eq!(alice.days[&1], [Some(1701407052), Some(1701407440)]);
+        assert_eq!(alice.days[&2], [Some(1701500400), None]);
+        assert_eq!(alice.delta(1), Some(388));
+        assert_eq!(alice.delta(2), None);
+        assert_eq!(alice.delta(3), None);
+
+        let anonymous = board.members.iter().find(|m| m.id == 3).unwrap();
+        assert_eq!(anonymous.display_name(), "(anonymous user #3)");
+        assert!(anonymous.days.is_empty());
+
+        assert!(Leaderboard::parse("{}").is_err());
+        assert!(Leaderboard::parse("<html>").is_err());
+    }
+
+    #[test]
+    fn test_ranking() {
+        let mut board = Leaderboard::parse(TEST_INPUT).unwrap();
+        let ids = |board: &Leaderboard| board.ranking().iter().map(|m| m.id).collect::<Vec<_>>();
+        assert_eq!(ids(&board), [2, 1, 3]);
+
+        // ties go to whoever got their last star first
+        board.members.iter_mut().for_each(|m| m.local_score = 0);
+        assert_eq!(ids(&board), [2, 1, 3]);
+        let alice = board.members.iter_mut().find(|m| m.id == 1).unwrap();
+        alice.last_star_ts = 1701400000;
+        assert_eq!(ids(&board), [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(1701407052), "2023-12-01 05:04:12");
+        assert_eq!(format_timestamp(1709210096), "2024-02-29 12:34:56");
+        assert_eq!(format_duration(388), "00:06:28");
+        assert_eq!(format_duration(2 * 86400 + 3661), "49:01:01");
+    }
+
+    #[test]
+    fn test_display() {
+        let board = Leaderboard::parse(TEST_INPUT).unwrap();
+        let expected = indoc::indoc! {"
+            Private leaderboard #1 of 2023, 3 members
+
+            Rank  Score  Stars  Name
+               1     10      4  bob
+               2      8      3  alice
+               3      0      0  (anonymous user #3)
+
+            bob:
+              Day  Part 1 (UTC)         Part 2 (UTC)         Delta
+                1  2023-12-01 06:00:00  2023-12-01 08:00:00  02:00:00
+                2  2023-12-02 05:05:00  2023-12-02 05:15:00  00:10:00
+
+            alice:
+              Day  Part 1 (UTC)         Part 2 (UTC)         Delta
+                1  2023-12-01 05:04:12  2023-12-01 05:10:40  00:06:28
+                2  2023-12-02 07:00:00  -                    -
+
+            (anonymous user #3): no stars
+        "};
+        assert_eq!(board.to_string(), expected);
+    }
+}
@@ -1,3 +1,10 @@
+pub mod aho_corasick;
+pub mod bitgrid;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod client;
+pub mod cycle;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod dashboard;
 pub mod day1;
 pub mod day10;
 pub mod day11;
@@ -15,3 +22,27 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+pub mod days;
+pub mod fastparse;
+pub mod generate;
+pub mod graph;
+pub mod grid;
+pub mod intern;
+pub mod interval;
+pub mod leaderboard;
+pub mod math;
+pub mod parse;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod puzzle;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod repl;
+pub mod search;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod serve;
+pub mod visualize;
+
+#[cfg(feature = "python")]
+mod python;
+
+#[cfg(feature = "wasm")]
+mod wasm;
@@ -1,46 +1,381 @@
+use std::collections::BTreeSet;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail};
 use aoc2023::*;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 struct Cli {
     /// Run only the solution for specified day. If not specified the solutions for all days are run.
     #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..26))]
     day: Option<u8>,
+
+    /// Run the implementation with this name instead of the default one, only parts which have it are run.
+    #[arg(long = "impl", requires = "day", conflicts_with = "cross_check")]
+    implementation: Option<String>,
+
+    /// Run all the implementations of the parts and check that they give the same answer
+    #[arg(long)]
+    cross_check: bool,
+
+    /// Visualize the input of the day instead of solving it, only some days support it
+    #[arg(
+        long,
+        value_enum,
+        requires = "day",
+        num_args = 0..=1,
+        default_missing_value = "ansi",
+        conflicts_with_all = ["implementation", "cross_check"]
+    )]
+    visualize: Option<Format>,
+
+    /// Directory for the frames of `--visualize ppm`
+    #[arg(long, default_value = "frames")]
+    frames_dir: PathBuf,
+
+    /// Size of a tile in pixels for `--visualize svg` and `--visualize ppm`
+    #[arg(long, default_value_t = 4)]
+    scale: u32,
+
+    /// Solve part 2 of day 1 with the digit words from this file, one `word value` pair per line
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "day",
+        conflicts_with_all = ["implementation", "cross_check", "visualize"]
+    )]
+    dictionary: Option<PathBuf>,
+
+    /// Check the games of day 2 against a bag like `12 red, 13 green, 14 blue` instead of solving it
+    #[arg(
+        long,
+        requires = "day",
+        conflicts_with_all = ["implementation", "cross_check", "visualize", "dictionary"]
+    )]
+    bag: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Last frame as colored text in the terminal
+    Ansi,
+    /// Last frame as an SVG image on stdout
+    Svg,
+    /// All the frames as numbered PPM images
+    Ppm,
 }
 
-fn main() {
+#[derive(Subcommand)]
+enum Command {
+    /// Start a http server which exposes the solutions as a REST API
+    Serve {
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+        /// Address to listen on, for example `0.0.0.0` to accept connections from other machines
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+    /// Interactive terminal dashboard for running the days
+    Dashboard,
+    /// Interactive REPL for exploring the parsed input of a day
+    Repl {
+        #[arg(short, long)]
+        day: u8,
+        /// Use the input from this file instead of the puzzle input
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// Generate a random input for a day and print it
+    Generate {
+        #[arg(short, long)]
+        day: u8,
+        /// Roughly the number of lines or the side length of the grid
+        #[arg(short, long, default_value_t = 100)]
+        size: usize,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Print the graph in the input of a day in Graphviz DOT format
+    Graph {
+        #[arg(short, long)]
+        day: u8,
+        /// Use the input from this file instead of the puzzle input
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// Show a private leaderboard from a saved json file
+    Leaderboard {
+        #[arg(short, long)]
+        file: PathBuf,
+        /// Download the private leaderboard with this id into `file` first
+        #[arg(long, value_name = "ID")]
+        fetch: Option<u64>,
+        #[arg(long, default_value_t = 2023)]
+        year: u16,
+        /// Url of the Advent of Code site, defaults to `AOC_BASE_URL` or https://adventofcode.com
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+    /// Show the puzzle statement of a day, downloading it on first use
+    Puzzle {
+        #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..26))]
+        day: u8,
+        #[arg(long, default_value_t = 2023)]
+        year: u16,
+        /// Download the page again, for example after part 2 has been unlocked
+        #[arg(long)]
+        refresh: bool,
+        /// Replace the example files which already exist, they are kept by default
+        #[arg(long)]
+        overwrite_examples: bool,
+        /// Url of the Advent of Code site, defaults to `AOC_BASE_URL` or https://adventofcode.com
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
-    macro_rules! match_day {
-        ($($num:literal =>$day:ident),*$(,)?) => {
-            match cli.day {
-                $(Some($num) => $day::run(),)*
-                None => {
-                    $(
-                        $day::run();
-                    )*
+    match cli.command {
+        Some(Command::Serve { port, host }) => serve::serve(&host, port)?,
+        Some(Command::Dashboard) => dashboard::run()?,
+        Some(Command::Repl { day, input }) => {
+            let input = input.map(std::fs::read_to_string).transpose()?;
+            repl::run(day, input)?
+        }
+        Some(Command::Generate { day, size, seed }) => match generate::generate(day, size, seed) {
+            Some(input) => print!("{input}"),
+            None => bail!("there is no generator for day {day}"),
+        },
+        Some(Command::Graph { day, input }) => {
+            let input = match input {
+                Some(path) => std::fs::read_to_string(path)?,
+                None => match days::get(day) {
+                    Some(d) => d.input.to_string(),
+                    None => bail!("day {day} not implemented"),
+                },
+            };
+            match graph::dot(day, &input) {
+                Some(dot) => print!("{}", dot?),
+                None => bail!("day {day} has no graph"),
+            }
+        }
+        Some(Command::Leaderboard {
+            file,
+            fetch,
+            year,
+            base_url,
+        }) => {
+            if let Some(id) = fetch {
+                fetch_leaderboard(&file, id, year, base_url)?;
+            }
+            let json = std::fs::read_to_string(&file)?;
+            print!("{}", leaderboard::Leaderboard::parse(&json)?);
+        }
+        Some(Command::Puzzle {
+            day,
+            year,
+            refresh,
+            overwrite_examples,
+            base_url,
+        }) => show_puzzle(day, year, refresh, overwrite_examples, base_url)?,
+        None => match cli.day {
+            Some(d) if cli.visualize.is_some() => {
+                visualize(d, cli.visualize.unwrap(), &cli.frames_dir, cli.scale)?
+            }
+            Some(d) if cli.dictionary.is_some() => {
+                solve_with_dictionary(d, cli.dictionary.as_deref().unwrap())?
+            }
+            Some(d) if cli.bag.is_some() => check_bag(d, cli.bag.as_deref().unwrap())?,
+            Some(d) => match days::get(d) {
+                Some(day) if cli.cross_check => cross_check(day)?,
+                Some(day) => match &cli.implementation {
+                    Some(name) => run_impl(day, name)?,
+                    None => (day.run)(),
+                },
+                None => println!("day {d} not implemented"),
+            },
+            None => {
+                // days without known answers are not solved yet and would only panic
+                for day in days::DAYS.iter().filter(|d| d.is_solved()) {
+                    if cli.cross_check {
+                        cross_check(day)?;
+                    } else {
+                        (day.run)();
+                    }
                 }
-                Some(d) => println!("day {d} not implemented"),
             }
+        },
+    }
+
+    Ok(())
+}
+
+fn run_impl(day: &days::Day, name: &str) -> anyhow::Result<()> {
+    let mut found = false;
+    for part in [1, 2] {
+        if let Some(solver) = day.get_impl(part, name) {
+            found = true;
+            let answer = solver(day.input).map_err(|e| anyhow!(e))?;
+            println!("day{}::part{part}::{name} answer: {answer}", day.day);
         }
     }
-    match_day!(
-        1 => day1,
-        2 => day2,
-        3 => day3,
-        4 => day4,
-        5 => day5,
-        6 => day6,
-        7 => day7,
-        8 => day8,
-        9 => day9,
-        10 => day10,
-        11 => day11,
-        //12 => day12,
-        13 => day13,
-        14 => day14,
-        15 => day15,
-        16 => day16,
-        19 => day19,
+
+    if !found {
+        let names = [1, 2]
+            .into_iter()
+            .flat_map(|part| day.impls(part).unwrap())
+            .map(|i| i.name)
+            .collect::<BTreeSet<_>>();
+        bail!(
+            "day {} has no implementation `{name}`, available: {names:?}",
+            day.day
+        );
+    }
+
+    Ok(())
+}
+
+fn visualize(day: u8, format: Format, frames_dir: &Path, scale: u32) -> anyhow::Result<()> {
+    let Some(d) = days::get(day) else {
+        bail!("day {day} not implemented");
+    };
+    let Some(frames) = visualize::frames(day, d.input) else {
+        bail!("day {day} has no visualization");
+    };
+    let last = frames.last().expect("there is always a frame");
+
+    match format {
+        Format::Ansi => print!("{}", last.ansi()),
+        Format::Svg => print!("{}", last.svg(scale)),
+        Format::Ppm => {
+            let paths = visualize::write_frames(frames_dir, &frames, scale)?;
+            eprintln!("wrote {} frames to {}", paths.len(), frames_dir.display());
+        }
+    }
+    Ok(())
+}
+
+fn solve_with_dictionary(day: u8, path: &Path) -> anyhow::Result<()> {
+    if day != 1 {
+        bail!("only day 1 takes a dictionary");
+    }
+    let dictionary = day1::Dictionary::parse(&std::fs::read_to_string(path)?)?;
+    let matcher = day1::Matcher::new(&dictionary);
+    let Some(answer) = day1::calibration_sum(day1::INPUT, &matcher) else {
+        bail!("a line has none of the words of {}", path.display());
+    };
+    println!("day1::part2 answer with {}: {answer}", path.display());
+    Ok(())
+}
+
+fn check_bag(day: u8, bag: &str) -> anyhow::Result<()> {
+    if day != 2 {
+        bail!("only day 2 takes a bag");
+    }
+    let bag = day2::Bag::parse(bag)?;
+    let games = day2::Games::parse(day2::INPUT)?;
+    let ids = games.possible(&bag).map(|g| g.id).collect::<Vec<_>>();
+    println!(
+        "{} of {} games are possible with {bag}, the sum of their ids is {}",
+        ids.len(),
+        games.games().len(),
+        ids.iter().sum::<u64>()
     );
+
+    let admitting = games
+        .bags_admitting_all()
+        .into_iter()
+        .map(|(color, range)| format!("{color} {}..", range.start))
+        .collect::<Vec<_>>();
+    println!("all games are possible with {}", admitting.join(", "));
+    Ok(())
+}
+
+fn cross_check(day: &days::Day) -> anyhow::Result<()> {
+    for part in [1, 2] {
+        let solutions = day.solve_all(part, day.input).unwrap();
+        for (name, solution) in &solutions {
+            match &solution.answer {
+                Ok(answer) => println!(
+                    "day{}::part{part}::{name} answer: {answer} ({:.2?})",
+                    day.day, solution.elapsed
+                ),
+                Err(e) => println!("day{}::part{part}::{name} failed: {e}", day.day),
+            }
+        }
+
+        let expected = &solutions[0].1.answer;
+        if solutions
+            .iter()
+            .any(|(_, s)| s.answer.is_err() || &s.answer != expected)
+        {
+            bail!("implementations of day{}::part{part} disagree", day.day);
+        }
+    }
+
+    Ok(())
+}
+
+fn fetch_leaderboard(
+    file: &Path,
+    id: u64,
+    year: u16,
+    base_url: Option<String>,
+) -> anyhow::Result<()> {
+    let client = client(base_url)?;
+    let json = client.get(&format!("{year}/leaderboard/private/view/{id}.json"))?;
+    // the site answers with a html page instead of an error if the session is not valid
+    if let Err(e) = leaderboard::Leaderboard::parse(&json) {
+        bail!(
+            "{} did not return a leaderboard, is the session valid? ({e})",
+            client.base_url()
+        );
+    }
+    std::fs::write(file, json)?;
+    Ok(())
+}
+
+fn show_puzzle(
+    day: u8,
+    year: u16,
+    refresh: bool,
+    overwrite_examples: bool,
+    base_url: Option<String>,
+) -> anyhow::Result<()> {
+    let path = PathBuf::from(format!("puzzles/{year}/day{day}.html"));
+    let html = puzzle::cached_html(&path, refresh, || {
+        client(base_url)?.get(&format!("{year}/day/{day}"))
+    })?;
+    let puzzle = puzzle::Puzzle::parse(&html);
+    if puzzle.parts.is_empty() {
+        bail!("{} has no puzzle statement", path.display());
+    }
+
+    let markdown = puzzle.markdown();
+    if std::io::stdout().is_terminal() {
+        print!("{}", puzzle::render(&markdown));
+    } else {
+        print!("{markdown}");
+    }
+
+    let dir = PathBuf::from(format!("inputs/examples/day{day}"));
+    for path in puzzle.write_examples(&dir, overwrite_examples)? {
+        eprintln!("wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn client(base_url: Option<String>) -> anyhow::Result<client::Client> {
+    let client = client::Client::from_env()?;
+    Ok(match base_url {
+        Some(base_url) => client.with_base_url(base_url),
+        None => client,
+    })
 }
@@ -0,0 +1,334 @@
+//! Number theory and geometry helpers shared by the days.
+//!
+//! The plain functions panic on overflow like the arithmetic operators do, the `checked_` variants
+//! return `None` instead. Intermediate results are calculated with `i128`/`u128` where they could
+//! overflow even though the result fits.
+
+use crate::grid::Pos;
+
+/// `1 + 2 + ... + n`
+pub fn triangular(n: u64) -> u64 {
+    checked_triangular(n).expect("triangular number overflows u64")
+}
+
+pub fn checked_triangular(n: u64) -> Option<u64> {
+    let n = n as u128;
+    u64::try_from(n * (n + 1) / 2).ok()
+}
+
+/// Largest integer whose square is at most `n`
+pub fn isqrt(n: u64) -> u64 {
+    isqrt_wide(n as u128) as u64
+}
+
+/// Same as [`isqrt`] for `u128`
+pub fn isqrt_wide(n: u128) -> u128 {
+    // The float estimate is off by at most a few for large numbers, fix it up with exact arithmetic
+    let mut root = (n as f64).sqrt() as u128;
+    while root.checked_mul(root).is_none_or(|square| square > n) {
+        root -= 1;
+    }
+    while (root + 1)
+        .checked_mul(root + 1)
+        .is_some_and(|square| square <= n)
+    {
+        root += 1;
+    }
+    root
+}
+
+pub fn gcd(a: u64, b: u64) -> u64 {
+    num::integer::gcd(a, b)
+}
+
+/// Least common multiple of all `values`, `1` if there are none
+pub fn lcm(values: impl IntoIterator<Item = u64>) -> u64 {
+    checked_lcm(values).expect("least common multiple overflows u64")
+}
+
+pub fn checked_lcm(values: impl IntoIterator<Item = u64>) -> Option<u64> {
+    values.into_iter().try_fold(1u64, |lcm, value| {
+        if value == 0 {
+            return Some(0);
+        }
+        (lcm / gcd(lcm, value)).checked_mul(value)
+    })
+}
+
+/// Returns `(g, x, y)` such that `a * x + b * y = g = gcd(a, b)`
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+/// Chinese remainder theorem for `x ≡ residue (mod modulus)` congruences, the moduli don't need to
+/// be coprime
+///
+/// Returns the solution as `(residue, modulus)` where the modulus is the lcm of the moduli. `None`
+/// if the congruences contradict each other or the lcm doesn't fit `u64`.
+///
+/// # Panics
+/// If a modulus is zero.
+pub fn crt(congruences: impl IntoIterator<Item = (u64, u64)>) -> Option<(u64, u64)> {
+    let mut solution = (0u128, 1u128);
+    for (residue, modulus) in congruences {
+        assert!(modulus != 0, "modulus of a congruence can't be zero");
+        let (r1, m1) = solution;
+        let (r2, m2) = ((residue % modulus) as u128, modulus as u128);
+
+        let (g, inverse, _) = extended_gcd(m1 as i128, m2 as i128);
+        let diff = r2 as i128 - r1 as i128;
+        if diff % g != 0 {
+            return None;
+        }
+
+        // x = r1 + m1 * t where m1 * t ≡ r2 - r1 (mod m2)
+        let m2_g = m2 / g as u128;
+        let t = (diff / g).rem_euclid(m2_g as i128) as u128
+            * inverse.rem_euclid(m2_g as i128) as u128
+            % m2_g;
+        let lcm = m1 * m2_g;
+        if lcm > u64::MAX as u128 {
+            return None;
+        }
+        solution = ((r1 + m1 * t) % lcm, lcm);
+    }
+
+    Some((solution.0 as u64, solution.1 as u64))
+}
+
+/// Distance between two grid positions when moving only up, down, left and right
+pub fn manhattan(a: Pos, b: Pos) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+/// Twice the area of the polygon with the `vertices` in order, with the shoelace formula
+///
+/// Twice the area is always an integer for integer coordinates.
+pub fn double_area(vertices: &[(i64, i64)]) -> u128 {
+    let n = vertices.len();
+    let sum = (0..n)
+        .map(|i| {
+            let (x1, y1) = vertices[i];
+            let (x2, y2) = vertices[(i + 1) % n];
+            x1 as i128 * y2 as i128 - x2 as i128 * y1 as i128
+        })
+        .sum::<i128>();
+    sum.unsigned_abs()
+}
+
+/// Number of integer points on the edges of the polygon with the `vertices` in order
+pub fn boundary_points(vertices: &[(i64, i64)]) -> u64 {
+    let n = vertices.len();
+    (0..n)
+        .map(|i| {
+            let (x1, y1) = vertices[i];
+            let (x2, y2) = vertices[(i + 1) % n];
+            gcd(x1.abs_diff(x2), y1.abs_diff(y2))
+        })
+        .sum()
+}
+
+/// Number of integer points strictly inside the polygon with the `vertices` in order, with
+/// Pick's theorem `A = i + b / 2 - 1`
+pub fn interior_points(vertices: &[(i64, i64)]) -> u64 {
+    let double_area = double_area(vertices);
+    let boundary = boundary_points(vertices) as u128;
+    ((double_area + 2 - boundary) / 2) as u64
+}
+
+/// Next value of the sequence, with the finite differences of the values
+///
+/// Exact if the values are a polynomial of a lower degree than their count.
+pub fn extrapolate_next(values: &[i64]) -> i64 {
+    let mut row = values.to_vec();
+    let mut next = 0;
+    while row.iter().any(|&v| v != 0) {
+        next += row[row.len() - 1];
+        row = row.windows(2).map(|w| w[1] - w[0]).collect();
+    }
+    next
+}
+
+/// Value before the first value of the sequence, see [`extrapolate_next`]
+pub fn extrapolate_prev(values: &[i64]) -> i64 {
+    let mut row = values.to_vec();
+    let mut prev = 0;
+    let mut sign = 1;
+    while row.iter().any(|&v| v != 0) {
+        prev += sign * row[0];
+        sign = -sign;
+        row = row.windows(2).map(|w| w[1] - w[0]).collect();
+    }
+    prev
+}
+
+/// Value at `x` of the lowest degree polynomial through `(i, values[i])`, with Lagrange
+/// interpolation
+///
+/// # Panics
+/// If the calculation overflows, see [`checked_lagrange`].
+pub fn lagrange(values: &[i64], x: i64) -> i64 {
+    checked_lagrange(values, x).expect("lagrange interpolation overflows")
+}
+
+pub fn checked_lagrange(values: &[i64], x: i64) -> Option<i64> {
+    let n = values.len() as i128;
+    let x = x as i128;
+
+    let mut sum = 0i128;
+    for (i, &value) in (0..).zip(values) {
+        // The basis polynomial maps integers to integers, so dividing after every step keeps
+        // the intermediate values exact and small.
+        let mut basis = 1i128;
+        let mut denominator = 1i128;
+        for j in (0..n).filter(|&j| j != i) {
+            basis = basis.checked_mul(x - j)?;
+            denominator = denominator.checked_mul(i - j)?;
+            let g = num::integer::gcd(basis, denominator);
+            if g != 0 {
+                basis /= g;
+                denominator /= g;
+            }
+        }
+        sum = sum.checked_add((value as i128).checked_mul(basis / denominator)?)?;
+    }
+
+    i64::try_from(sum).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_triangular() {
+        assert_eq!(triangular(0), 0);
+        assert_eq!(triangular(1), 1);
+        assert_eq!(triangular(4), 10);
+        assert_eq!(
+            checked_triangular(u32::MAX as u64),
+            Some(9223372034707292160)
+        );
+        assert_eq!(checked_triangular(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_isqrt() {
+        for n in 0..1000 {
+            let root = isqrt(n);
+            assert!(root * root <= n && (root + 1) * (root + 1) > n, "{n}");
+        }
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+        assert_eq!(isqrt((1 << 52) + 1), 1 << 26);
+        let big = 4_294_967_295u64;
+        assert_eq!(isqrt(big * big), big);
+        assert_eq!(isqrt(big * big - 1), big - 1);
+
+        assert_eq!(isqrt_wide(u128::MAX), u64::MAX as u128);
+        let big = u64::MAX as u128 - 5;
+        assert_eq!(isqrt_wide(big * big), big);
+        assert_eq!(isqrt_wide(big * big - 1), big - 1);
+    }
+
+    #[test]
+    fn test_lcm() {
+        assert_eq!(lcm([]), 1);
+        assert_eq!(lcm([4, 6, 10]), 60);
+        assert_eq!(lcm([4, 0]), 0);
+        assert_eq!(checked_lcm([u64::MAX, u64::MAX - 1]), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt([]), Some((0, 1)));
+        // coprime
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // common factors
+        assert_eq!(crt([(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt([(1, 4), (2, 6)]), None);
+        // residues larger than the modulus
+        assert_eq!(crt([(7, 4), (9, 6)]), Some((3, 12)));
+        assert_eq!(crt([(0, u64::MAX), (0, u64::MAX - 1)]), None);
+    }
+
+    #[test]
+    fn test_manhattan() {
+        assert_eq!(manhattan((0, 0), (0, 0)), 0);
+        assert_eq!(manhattan((6, 1), (11, 5)), 9);
+        assert_eq!(manhattan((11, 5), (6, 1)), 9);
+    }
+
+    #[test]
+    fn test_polygon() {
+        // 4x3 rectangle, either direction
+        let rectangle = [(0, 0), (4, 0), (4, 3), (0, 3)];
+        assert_eq!(double_area(&rectangle), 24);
+        assert_eq!(boundary_points(&rectangle), 14);
+        assert_eq!(interior_points(&rectangle), 3 * 2);
+        let mut reversed = rectangle;
+        reversed.reverse();
+        assert_eq!(double_area(&reversed), 24);
+
+        // the diagonal has no integer points besides the corners
+        let triangle = [(0, 0), (3, 0), (0, 2)];
+        assert_eq!(double_area(&triangle), 6);
+        assert_eq!(boundary_points(&triangle), 6);
+        assert_eq!(interior_points(&triangle), 1);
+
+        // points between the corners don't change anything
+        let with_points = [(0, 0), (2, 0), (4, 0), (4, 3), (0, 3), (0, 1)];
+        assert_eq!(interior_points(&with_points), 6);
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        let values = [10, 13, 16, 21, 30, 45];
+        assert_eq!(extrapolate_next(&values), 68);
+        assert_eq!(extrapolate_prev(&values), 5);
+        assert_eq!(lagrange(&values, 6), 68);
+        assert_eq!(lagrange(&values, -1), 5);
+        assert_eq!(lagrange(&values, 3), 21);
+
+        assert_eq!(extrapolate_next(&[]), 0);
+        assert_eq!(extrapolate_next(&[7]), 7);
+        assert_eq!(lagrange(&[7], 100), 7);
+        assert_eq!(checked_lagrange(&[i64::MAX, 0], -1), None);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_isqrt_wide(n in any::<u128>()) {
+            let root = isqrt_wide(n);
+            prop_assert!(root * root <= n);
+            prop_assert!((root + 1).checked_mul(root + 1).is_none_or(|s| s > n));
+        }
+
+        #[test]
+        fn test_crt_brute_force(r1 in 0..30u64, m1 in 1..30u64, r2 in 0..30u64, m2 in 1..30u64) {
+            let expected = (0..m1 * m2).find(|x| x % m1 == r1 % m1 && x % m2 == r2 % m2);
+            let solution = crt([(r1, m1), (r2, m2)]);
+            prop_assert_eq!(solution.map(|(r, _)| r), expected);
+            if let Some((_, m)) = solution {
+                prop_assert_eq!(m, lcm([m1, m2]));
+            }
+        }
+
+        #[test]
+        fn test_extrapolation_agrees(coefficients in prop::collection::vec(-20..20i64, 1..6), len in 6..15usize) {
+            let polynomial = |x: i64| coefficients.iter().rev().fold(0, |acc, c| acc * x + c);
+            let values = (0..len as i64).map(polynomial).collect::<Vec<_>>();
+
+            prop_assert_eq!(extrapolate_next(&values), polynomial(len as i64));
+            prop_assert_eq!(extrapolate_prev(&values), polynomial(-1));
+            prop_assert_eq!(lagrange(&values, len as i64), polynomial(len as i64));
+            prop_assert_eq!(lagrange(&values, -1), polynomial(-1));
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! Helpers for the shapes that keep coming back in the puzzle inputs.
+//!
+//! Everything borrows from the input, the iterators don't allocate while the input is valid.
+//! Malformed input is reported with a [`ParseError`] which owns a copy of the offending text, so
+//! that it can outlive the input.
+
+use std::fmt;
+use std::str::FromStr;
+
+pub type Result<T> = std::result::Result<T, ParseError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input ended before the `expected` value
+    UnexpectedEnd {
+        expected: &'static str,
+    },
+    MissingSeparator {
+        separator: String,
+        input: String,
+    },
+    MissingPrefix {
+        prefix: String,
+        input: String,
+    },
+    MissingSuffix {
+        suffix: String,
+        input: String,
+    },
+    InvalidNumber {
+        input: String,
+        reason: String,
+    },
+    /// `input` is not one of the values allowed for `expected`
+    InvalidValue {
+        expected: &'static str,
+        input: String,
+    },
+    WrongCount {
+        expected: usize,
+        found: usize,
+    },
+    /// Line `line` of a grid isn't as wide as the first one
+    WrongWidth {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The input is larger than the solution supports, for example a grid that doesn't fit into
+    /// the bits of an integer
+    TooLarge {
+        what: &'static str,
+        max: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedEnd { expected } => {
+                write!(f, "unexpected end of input, expected {expected}")
+            }
+            ParseError::MissingSeparator { separator, input } => {
+                write!(f, "expected `{separator}` in `{input}`")
+            }
+            ParseError::MissingPrefix { prefix, input } => {
+                write!(f, "expected `{input}` to start with `{prefix}`")
+            }
+            ParseError::MissingSuffix { suffix, input } => {
+                write!(f, "expected `{input}` to end with `{suffix}`")
+            }
+            ParseError::InvalidNumber { input, reason } => {
+                write!(f, "invalid number `{input}`: {reason}")
+            }
+            ParseError::InvalidValue { expected, input } => {
+                write!(f, "invalid {expected} `{input}`")
+            }
+            ParseError::WrongCount { expected, found } => {
+                write!(f, "expected {expected} values, found {found}")
+            }
+            ParseError::WrongWidth {
+                line,
+                expected,
+                found,
+            } => {
+                write!(f, "line {line} is {found} wide, expected {expected}")
+            }
+            ParseError::TooLarge { what, max, found } => {
+                write!(f, "{what} {found} is larger than the supported {max}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a number, surrounding whitespace is ignored
+pub fn number<T>(s: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let s = s.trim();
+    s.parse().map_err(|e: T::Err| ParseError::InvalidNumber {
+        input: s.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Iterator over the whitespace separated numbers in `s`, any amount of whitespace is allowed
+pub fn numbers<T>(s: &str) -> Numbers<'_, T> {
+    Numbers {
+        iter: s.split_ascii_whitespace(),
+        _marker: std::marker::PhantomData,
+    }
+}
+
+pub struct Numbers<'a, T> {
+    iter: std::str::SplitAsciiWhitespace<'a>,
+    _marker: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T> Iterator for Numbers<'_, T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(number)
+    }
+}
+
+/// Splits `s` by `separator` and parses every item with `f`
+pub fn separated<'a, T>(
+    s: &'a str,
+    separator: &'a str,
+    f: impl FnMut(&'a str) -> Result<T> + 'a,
+) -> impl Iterator<Item = Result<T>> + 'a
+where
+    T: 'a,
+{
+    s.split(separator).map(f)
+}
+
+/// Iterator over the blocks of lines separated by blank lines
+///
+/// The blocks don't include the trailing newline and empty blocks are skipped.
+pub fn blocks(s: &str) -> Blocks<'_> {
+    Blocks { rest: s }
+}
+
+pub struct Blocks<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let rest = self.rest.trim_start_matches(['\n', '\r']);
+            if rest.is_empty() {
+                self.rest = rest;
+                return None;
+            }
+
+            let (block, rest) = match (rest.find("\n\n"), rest.find("\n\r\n")) {
+                (Some(a), Some(b)) => rest.split_at(a.min(b)),
+                (Some(i), None) | (None, Some(i)) => rest.split_at(i),
+                (None, None) => (rest, ""),
+            };
+            self.rest = rest;
+
+            let block = block.trim_end_matches(['\n', '\r']);
+            if !block.trim().is_empty() {
+                return Some(block);
+            }
+        }
+    }
+}
+
+/// Splits `s` at the first `separator`
+pub fn split_once<'a>(s: &'a str, separator: &str) -> Result<(&'a str, &'a str)> {
+    s.split_once(separator)
+        .ok_or_else(|| ParseError::MissingSeparator {
+            separator: separator.to_string(),
+            input: s.to_string(),
+        })
+}
+
+/// Returns `s` without the `prefix`
+pub fn prefix<'a>(s: &'a str, prefix: &str) -> Result<&'a str> {
+    s.strip_prefix(prefix)
+        .ok_or_else(|| ParseError::MissingPrefix {
+            prefix: prefix.to_string(),
+            input: s.to_string(),
+        })
+}
+
+/// Returns `s` without the `suffix`
+pub fn suffix<'a>(s: &'a str, suffix: &str) -> Result<&'a str> {
+    s.strip_suffix(suffix)
+        .ok_or_else(|| ParseError::MissingSuffix {
+            suffix: suffix.to_string(),
+            input: s.to_string(),
+        })
+}
+
+/// Parses a `key: value` line, for example `Card 1: 41 48 | 83 86`
+pub fn header(line: &str) -> Result<(&str, &str)> {
+    let (key, value) = split_once(line, ":")?;
+    Ok((key.trim(), value.trim()))
+}
+
+/// Returns the value of a `key: value` line whose key must be `key`
+pub fn labeled<'a>(line: &'a str, key: &str) -> Result<&'a str> {
+    let value = prefix(line.trim_start(), key)?;
+    Ok(prefix(value, ":")?.trim())
+}
+
+/// Parses a `name{body}` item, for example `px{a<2006:qkq,rfg}`
+pub fn braced(s: &str) -> Result<(&str, &str)> {
+    let (name, body) = split_once(s.trim(), "{")?;
+    Ok((name, suffix(body, "}")?))
+}
+
+/// Parses a `(a, b)` pair
+pub fn pair(s: &str) -> Result<(&str, &str)> {
+    let s = prefix(s.trim(), "(")?;
+    let (a, b) = split_once(suffix(s, ")")?, ",")?;
+    Ok((a.trim(), b.trim()))
+}
+
+/// Parses an `a = b` assignment
+pub fn assignment(s: &str) -> Result<(&str, &str)> {
+    let (name, value) = split_once(s, "=")?;
+    Ok((name.trim(), value.trim()))
+}
+
+/// Collects exactly `N` values from `iter`, the first error is returned as is
+pub fn exactly<T, const N: usize>(iter: impl IntoIterator<Item = Result<T>>) -> Result<[T; N]> {
+    let mut iter = iter.into_iter();
+    let mut found = 0;
+    let mut error = None;
+    let values: [Option<T>; N] = std::array::from_fn(|_| match iter.next()? {
+        Ok(value) => {
+            found += 1;
+            Some(value)
+        }
+        Err(e) => {
+            error.get_or_insert(e);
+            None
+        }
+    });
+
+    if let Some(e) = error {
+        return Err(e);
+    }
+    if found < N {
+        return Err(ParseError::WrongCount { expected: N, found });
+    }
+    let rest = iter.count();
+    if rest > 0 {
+        return Err(ParseError::WrongCount {
+            expected: N,
+            found: N + rest,
+        });
+    }
+
+    Ok(values.map(|v| v.unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers() {
+        let nums = numbers::<i64>("  1 -2\t 30 ").collect::<Result<Vec<_>>>();
+        assert_eq!(nums, Ok(vec![1, -2, 30]));
+
+        let mut nums = numbers::<u8>("1 x 3");
+        assert_eq!(nums.next(), Some(Ok(1)));
+        assert!(matches!(
+            nums.next(),
+            Some(Err(ParseError::InvalidNumber { input, .. })) if input == "x"
+        ));
+        assert_eq!(nums.next(), Some(Ok(3)));
+        assert_eq!(nums.next(), None);
+    }
+
+    #[test]
+    fn test_blocks() {
+        let input = indoc::indoc! {"
+        a
+        b
+
+        c
+
+
+        d
+        "};
+        assert_eq!(blocks(input).collect::<Vec<_>>(), ["a\nb", "c", "d"]);
+        assert_eq!(blocks("a\r\n\r\nb\r\n").collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(blocks("\n\n").next(), None);
+    }
+
+    #[test]
+    fn test_shapes() {
+        assert_eq!(header("Card 1: 41 48"), Ok(("Card 1", "41 48")));
+        assert_eq!(labeled("Time:      7  15", "Time"), Ok("7  15"));
+        assert_eq!(
+            labeled("Distance: 9", "Time"),
+            Err(ParseError::MissingPrefix {
+                prefix: "Time".into(),
+                input: "Distance: 9".into()
+            })
+        );
+        assert_eq!(braced("px{a<2006:qkq,rfg}"), Ok(("px", "a<2006:qkq,rfg")));
+        assert_eq!(braced("{x=787}"), Ok(("", "x=787")));
+        assert_eq!(assignment("AAA = (BBB, CCC)"), Ok(("AAA", "(BBB, CCC)")));
+        assert_eq!(pair("(BBB, CCC)"), Ok(("BBB", "CCC")));
+        assert!(pair("BBB, CCC").is_err());
+    }
+
+    #[test]
+    fn test_exactly() {
+        assert_eq!(exactly(numbers::<u32>("1 2 3")), Ok([1, 2, 3]));
+        assert_eq!(
+            exactly::<u32, 3>(numbers("1 2")),
+            Err(ParseError::WrongCount {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            exactly::<u32, 2>(numbers("1 2 3 4")),
+            Err(ParseError::WrongCount {
+                expected: 2,
+                found: 4
+            })
+        );
+        assert!(exactly::<u32, 2>(numbers("1 y")).is_err());
+    }
+
+    #[test]
+    fn test_error_message() {
+        let e = split_once("abc", " | ").unwrap_err();
+        assert_eq!(e.to_string(), "expected ` | ` in `abc`");
+    }
+}
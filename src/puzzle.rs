@@ -0,0 +1,566 @@
+//! Puzzle statements downloaded from the site and converted to Markdown.
+//!
+//! Each `<article>` of the page is one part of the puzzle. The `<pre><code>` blocks in them are
+//! the example inputs and the last `<code><em>` of an article is usually the answer of that part
+//! for the example right before it.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+
+pub struct Puzzle {
+    pub parts: Vec<Part>,
+}
+
+pub struct Part {
+    pub markdown: String,
+    pub examples: Vec<String>,
+    pub answer: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Example {
+    pub input: String,
+    /// Expected answers of part 1 and 2 if they could be found
+    pub answers: [Option<String>; 2],
+}
+
+impl Puzzle {
+    pub fn parse(html: &str) -> Self {
+        let tokens = tokenize(html);
+        let mut parts = Vec::new();
+        let mut start = None;
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                Token::Open("article", _) => start = Some(i + 1),
+                Token::Close("article") => {
+                    if let Some(start) = start.take() {
+                        parts.push(Part::convert(&tokens[start..i]));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self { parts }
+    }
+
+    pub fn markdown(&self) -> String {
+        let mut markdown = self
+            .parts
+            .iter()
+            .map(|p| p.markdown.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        markdown.push('\n');
+        markdown
+    }
+
+    /// Distinct examples of all parts, the answer of a part is assigned to the last example
+    /// seen so far as part 2 often reuses the example of part 1
+    pub fn examples(&self) -> Vec<Example> {
+        let mut examples = Vec::<Example>::new();
+        for (part, p) in self.parts.iter().take(2).enumerate() {
+            for input in &p.examples {
+                if !examples.iter().any(|e| &e.input == input) {
+                    examples.push(Example {
+                        input: input.clone(),
+                        answers: [None, None],
+                    });
+                }
+            }
+
+            let last = match p.examples.last() {
+                Some(input) => examples.iter().position(|e| &e.input == input),
+                None => examples.len().checked_sub(1),
+            };
+            if let (Some(last), Some(answer)) = (last, &p.answer) {
+                examples[last].answers[part] = Some(answer.clone());
+            }
+        }
+        examples
+    }
+
+    /// Writes the examples into `dir` as `example{n}.txt` and their answers into `answers.txt`,
+    /// returns the paths of the written files
+    ///
+    /// Files which already exist, for example fixed by hand, are kept unless `overwrite` is set.
+    pub fn write_examples(&self, dir: &Path, overwrite: bool) -> std::io::Result<Vec<PathBuf>> {
+        let examples = self.examples();
+        if examples.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        std::fs::create_dir_all(dir)?;
+        let mut written = Vec::new();
+        let mut write = |path: PathBuf, contents: &str| {
+            if overwrite || !path.exists() {
+                std::fs::write(&path, contents)?;
+                written.push(path);
+            }
+            std::io::Result::Ok(())
+        };
+
+        let mut answers = String::new();
+        for (i, example) in examples.iter().enumerate() {
+            let name = format!("example{}.txt", i + 1);
+            write(dir.join(&name), &example.input)?;
+
+            for (part, answer) in example.answers.iter().enumerate() {
+                if let Some(answer) = answer {
+                    writeln!(answers, "{name} part{}: {answer}", part + 1).unwrap();
+                }
+            }
+        }
+
+        if !answers.is_empty() {
+            write(dir.join("answers.txt"), &answers)?;
+        }
+        Ok(written)
+    }
+}
+
+impl Part {
+    fn convert(tokens: &[Token<'_>]) -> Self {
+        let mut md = String::new();
+        let mut examples = Vec::new();
+        let mut answer = None;
+        let mut pre = None::<String>;
+        let mut links = Vec::new();
+        let mut in_code = false;
+        // `<code><em>` is written as **`...`** as emphasis does not work inside code spans
+        let mut code_em = false;
+
+        for (i, token) in tokens.iter().enumerate() {
+            match *token {
+                Token::Text(text) => {
+                    let text = decode_entities(text);
+                    if let Some(pre) = &mut pre {
+                        pre.push_str(&text);
+                    } else if in_code {
+                        md.push_str(&text);
+                        if code_em {
+                            answer.get_or_insert_with(String::new).push_str(&text);
+                        }
+                    } else {
+                        let text = collapse_whitespace(&text);
+                        let text = if md.is_empty() || md.ends_with('\n') {
+                            text.trim_start()
+                        } else {
+                            &text
+                        };
+                        md.push_str(&escape(text));
+                    }
+                }
+                Token::Open("pre", _) => pre = Some(String::new()),
+                Token::Close("pre") => {
+                    let code = pre.take().unwrap_or_default();
+                    md.push_str("```\n");
+                    md.push_str(&code);
+                    if !code.ends_with('\n') {
+                        md.push('\n');
+                    }
+                    md.push_str("```\n\n");
+                    examples.push(code);
+                }
+                _ if pre.is_some() => {}
+                Token::Open("h2", _) => md.push_str("## "),
+                Token::Close("h2" | "p") => md.push_str("\n\n"),
+                Token::Open("li", _) => md.push_str("- "),
+                Token::Close("li" | "ul") => md.push('\n'),
+                Token::Open("code", _) => {
+                    in_code = true;
+                    code_em = matches!(tokens.get(i + 1), Some(Token::Open("em", _)));
+                    if code_em {
+                        md.push_str("**");
+                        answer = Some(String::new());
+                    }
+                    md.push('`');
+                }
+                Token::Close("code") => {
+                    in_code = false;
+                    md.push('`');
+                    if code_em {
+                        md.push_str("**");
+                        code_em = false;
+                    }
+                }
+                Token::Open("em", _) | Token::Close("em") if !in_code => md.push_str("**"),
+                Token::Open("a", attrs) => {
+                    links.push(attribute(attrs, "href").unwrap_or_default());
+                    md.push('[');
+                }
+                Token::Close("a") => {
+                    let href = links.pop().unwrap_or_default();
+                    write!(md, "]({href})").unwrap();
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            markdown: md.trim_end().to_string(),
+            examples,
+            answer,
+        }
+    }
+}
+
+/// Returns the cached page at `path` or downloads it with `fetch` if it is not cached yet
+/// or `refresh` is set
+pub fn cached_html(
+    path: &Path,
+    refresh: bool,
+    fetch: impl FnOnce() -> anyhow::Result<String>,
+) -> anyhow::Result<String> {
+    if !refresh && path.exists() {
+        return std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()));
+    }
+
+    let html = fetch()?;
+    if !html.contains("<article") {
+        bail!("the downloaded page has no puzzle statement, is the day unlocked yet?");
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, &html)?;
+    Ok(html)
+}
+
+/// Renders the Markdown produced by [`Puzzle::markdown`] with ANSI escape codes
+pub fn render(markdown: &str) -> String {
+    let mut out = String::new();
+    let mut in_block = false;
+    for line in markdown.lines() {
+        if line.starts_with("```") {
+            in_block = !in_block;
+            continue;
+        }
+
+        if in_block {
+            out.push_str("    ");
+            out.push_str(line);
+        } else if let Some(heading) = line.strip_prefix("## ") {
+            out.push_str("\x1b[1;4m");
+            render_inline(heading, &mut out);
+            out.push_str("\x1b[0m");
+        } else if let Some(item) = line.strip_prefix("- ") {
+            out.push_str("  • ");
+            render_inline(item, &mut out);
+        } else {
+            render_inline(line, &mut out);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn render_inline(line: &str, out: &mut String) {
+    let mut chars = line.chars().peekable();
+    let mut bold = false;
+    let mut in_code = false;
+    while let Some(c) = chars.next() {
+        if in_code {
+            if c == '`' {
+                in_code = false;
+                out.push_str("\x1b[39m");
+            } else {
+                out.push(c);
+            }
+            continue;
+        }
+
+        match c {
+            '\\' => out.extend(chars.next()),
+            '`' => {
+                in_code = true;
+                out.push_str("\x1b[36m");
+            }
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                bold = !bold;
+                out.push_str(if bold { "\x1b[1m" } else { "\x1b[22m" });
+            }
+            '[' => out.push_str("\x1b[4m"),
+            ']' if chars.peek() == Some(&'(') => {
+                out.push_str("\x1b[24m");
+                chars.by_ref().find(|&c| c == ')');
+            }
+            c => out.push(c),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    /// Tag name and the raw attributes
+    Open(&'a str, &'a str),
+    Close(&'a str),
+    Text(&'a str),
+}
+
+/// Splits the html into tags and text, comments are skipped. Good enough for the pages of
+/// the site but not for html in general.
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.split_once("-->").map_or("", |(_, rest)| rest);
+        } else if let Some(tag) = rest.strip_prefix('<') {
+            let Some((tag, after)) = tag.split_once('>') else {
+                tokens.push(Token::Text(rest));
+                break;
+            };
+            rest = after;
+            let tag = tag.trim_end_matches('/');
+            if let Some(name) = tag.strip_prefix('/') {
+                tokens.push(Token::Close(name.trim()));
+            } else {
+                let (name, attrs) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+                tokens.push(Token::Open(name, attrs));
+            }
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            tokens.push(Token::Text(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+    tokens
+}
+
+fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let start = attrs.find(&format!("{name}=\""))? + name.len() + 2;
+    let len = attrs[start..].find('"')?;
+    Some(&attrs[start..start + len])
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let decoded = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                entity => {
+                    let code = match entity.strip_prefix("#x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => entity.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end + 1))
+        });
+
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !out.ends_with(' ') {
+                out.push(' ');
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use indoc::indoc;
+
+    use super::*;
+
+    const TEST_INPUT: &str = include_str!("../tests/fixtures/puzzle.html");
+
+    #[test]
+    fn test_markdown() {
+        let puzzle = Puzzle::parse(TEST_INPUT);
+        assert_eq!(puzzle.parts.len(), 2);
+
+        let expected = indoc! {"
+            ## --- Day 1: Sample Sums ---
+
+            The elves need the **sum** of every line of their [calibration document](https://en.wikipedia.org/wiki/Calibration).
+
+            For example:
+
+            ```
+            1 + 2
+            3 * 4 & 5
+            <6>
+            ```
+
+            - Numbers are separated by `+` or `*`.
+            - The last line is **special**.
+
+            In this example, the answer is **`142`**.
+
+            What is the answer for your document?
+
+            ## --- Part Two ---
+
+            Some lines are spelled out:
+
+            ```
+            two1nine
+            eightwo
+            ```
+
+            Now the answer is **`281`**.
+        "};
+        assert_eq!(puzzle.markdown(), expected);
+    }
+
+    #[test]
+    fn test_examples() {
+        let puzzle = Puzzle::parse(TEST_INPUT);
+        assert_eq!(
+            puzzle.examples(),
+            [
+                Example {
+                    input: "1 + 2\n3 * 4 & 5\n<6>\n".to_string(),
+                    answers: [Some("142".to_string()), None],
+                },
+                Example {
+                    input: "two1nine\neightwo\n".to_string(),
+                    answers: [None, Some("281".to_string())],
+                },
+            ]
+        );
+
+        // part 2 without its own example uses the one from part 1
+        let html = "<article><pre><code>a</code></pre><code><em>1</em></code></article>\
+                    <article><code><em>2</em></code> and <code>3</code></article>";
+        assert_eq!(
+            Puzzle::parse(html).examples(),
+            [Example {
+                input: "a".to_string(),
+                answers: [Some("1".to_string()), Some("2".to_string())],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_write_examples() {
+        let dir = std::env::temp_dir().join(format!("aoc2023-puzzle-{}", std::process::id()));
+        let puzzle = Puzzle::parse(TEST_INPUT);
+        let written = puzzle.write_examples(&dir, false).unwrap();
+        assert_eq!(
+            written,
+            ["example1.txt", "example2.txt", "answers.txt"].map(|f| dir.join(f))
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("answers.txt")).unwrap(),
+            "example1.txt part1: 142\nexample2.txt part2: 281\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("example2.txt")).unwrap(),
+            "two1nine\neightwo\n"
+        );
+
+        // the files edited by hand are kept, the missing ones are written again
+        std::fs::write(dir.join("example1.txt"), "fixed\n").unwrap();
+        std::fs::remove_file(dir.join("example2.txt")).unwrap();
+        let written = puzzle.write_examples(&dir, false).unwrap();
+        assert_eq!(written, [dir.join("example2.txt")]);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("example1.txt")).unwrap(),
+            "fixed\n"
+        );
+
+        let written = puzzle.write_examples(&dir, true).unwrap();
+        assert_eq!(written.len(), 3);
+        assert_ne!(
+            std::fs::read_to_string(dir.join("example1.txt")).unwrap(),
+            "fixed\n"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_cached_html() {
+        let path = std::env::temp_dir()
+            .join(format!("aoc2023-cache-{}", std::process::id()))
+            .join("day1.html");
+        let fetches = Cell::new(0);
+        let fetch = || {
+            fetches.set(fetches.get() + 1);
+            Ok(TEST_INPUT.to_string())
+        };
+
+        assert_eq!(cached_html(&path, false, fetch).unwrap(), TEST_INPUT);
+        assert_eq!(cached_html(&path, false, fetch).unwrap(), TEST_INPUT);
+        assert_eq!(fetches.get(), 1);
+        assert_eq!(cached_html(&path, true, fetch).unwrap(), TEST_INPUT);
+        assert_eq!(fetches.get(), 2);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(cached_html(&path, false, || Ok("<html></html>".to_string())).is_err());
+        assert!(!path.exists());
+        std::fs::remove_dir(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_render() {
+        let rendered =
+            render("## Title\n\nA **bold** `co*de` [link](url) \\*\n```\n**raw**\n```\n- item\n");
+        assert_eq!(
+            rendered,
+            "\x1b[1;4mTitle\x1b[0m\n\
+             \n\
+             A \x1b[1mbold\x1b[22m \x1b[36mco*de\x1b[39m \x1b[4mlink\x1b[24m *\n    \
+             **raw**\n  \
+             • item\n"
+        );
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &lt;&amp;&gt; &#39;&#x41;&quot; & b;"),
+            "a <&> 'A\" & b;"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Python bindings for the solutions.
+//!
+//! Build the extension module with `maturin build --release`, the wheel installs an `aoc2023` module.
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::{day19, day5, days, parse};
+
+/// Solves given part of the day with `input` and returns the answer.
+///
+/// Raises `ValueError` for unknown days or parts and `RuntimeError` if the solution fails.
+#[pyfunction]
+fn solve(day: u8, part: u8, input: &str) -> PyResult<String> {
+    let solver = days::get(day)
+        .ok_or_else(|| PyValueError::new_err(format!("day {day} is not implemented")))?;
+    let solution = solver
+        .solve(part, input)
+        .ok_or_else(|| PyValueError::new_err(format!("day {day} has no part {part}")))?;
+
+    solution.answer.map_err(PyRuntimeError::new_err)
+}
+
+/// Returns the list of implemented days
+#[pyfunction]
+#[pyo3(name = "days")]
+fn list_days() -> Vec<u32> {
+    // Vec<u8> would be converted to `bytes`
+    days::DAYS.iter().map(|d| d.day as u32).collect()
+}
+
+/// Returns the known answers for the day's puzzle input if the day is solved
+#[pyfunction]
+fn answers(day: u8) -> Option<(String, String)> {
+    let [p1, p2] = days::get(day)?.answers?;
+    Some((p1.to_string(), p2.to_string()))
+}
+
+/// Day 5 almanac which maps seeds to their locations
+#[pyclass(frozen)]
+struct Almanac {
+    seeds: Vec<u64>,
+    almanac: day5::Almanac,
+}
+
+#[pymethods]
+impl Almanac {
+    #[new]
+    fn new(input: &str) -> PyResult<Self> {
+        let (seeds, almanac) = day5::parse_part1(input).map_err(value_error)?;
+        Ok(Self { seeds, almanac })
+    }
+
+    #[getter]
+    fn seeds(&self) -> Vec<u64> {
+        self.seeds.clone()
+    }
+
+    fn location(&self, seed: u64) -> u64 {
+        self.almanac.get_location(seed)
+    }
+
+    fn locations(&self, seeds: Vec<u64>) -> Vec<u64> {
+        seeds
+            .into_iter()
+            .map(|s| self.almanac.get_location(s))
+            .collect()
+    }
+}
+
+/// Day 19 workflows which accept or reject parts
+#[pyclass(frozen)]
+struct Workflows {
+    workflows: day19::Workflows,
+    names: Vec<String>,
+}
+
+#[pymethods]
+impl Workflows {
+    /// `input` is either the workflows or the whole puzzle input
+    ///
+    /// Raises `ValueError` if a workflow is missing or the workflows form a cycle.
+    #[new]
+    fn new(input: &str) -> PyResult<Self> {
+        let workflows = day19::Workflows::parse(input).map_err(value_error)?;
+        let graph = day19::graph(input).map_err(value_error)?;
+        let mut names = graph
+            .ids()
+            .map(|id| *graph.node(id))
+            .filter(|&name| name != "A" && name != "R")
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        names.sort();
+
+        Ok(Self { workflows, names })
+    }
+
+    fn accepts(&self, x: u16, m: u16, a: u16, s: u16) -> bool {
+        self.workflows.is_accepted(&day19::Part { x, m, a, s })
+    }
+
+    fn workflow_names(&self) -> Vec<String> {
+        self.names.clone()
+    }
+}
+
+fn value_error(e: parse::ParseError) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+#[pymodule]
+fn aoc2023(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(list_days, m)?)?;
+    m.add_function(wrap_pyfunction!(answers, m)?)?;
+    m.add_class::<Almanac>()?;
+    m.add_class::<Workflows>()?;
+    Ok(())
+}
@@ -0,0 +1,356 @@
+//! Interactive REPL for exploring the parsed model of a day.
+//!
+//! The input is parsed once, after which day specific commands can be used to inspect it.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use anyhow::{anyhow, bail};
+
+use crate::days::{self, catch_quietly};
+use crate::{day19, day5, day7, parse};
+
+const COMMON_HELP: &str = "\
+commands:
+  part1, part2      solve the part with the loaded input
+  help              show this help
+  quit, exit        exit the repl";
+
+/// Starts the REPL on stdin for the `day`. If `input` is `None` the puzzle input of the day is used.
+pub fn run(day: u8, input: Option<String>) -> anyhow::Result<()> {
+    let input = match input {
+        Some(input) => input,
+        None => days::get(day)
+            .ok_or_else(|| anyhow!("day {day} not implemented"))?
+            .input
+            .to_string(),
+    };
+
+    repl(day, &input, io::stdin().lock(), io::stdout())
+}
+
+fn repl(
+    day: u8,
+    input: &str,
+    mut reader: impl BufRead,
+    mut writer: impl Write,
+) -> anyhow::Result<()> {
+    let session: Box<dyn Session + '_> = match day {
+        5 => Box::new(Day5::new(input)?),
+        7 => Box::new(Day7::new(input)),
+        19 => Box::new(Day19::new(input)?),
+        _ => bail!("there is no repl for day {day}"),
+    };
+
+    writeln!(writer, "{COMMON_HELP}\n{}", session.help())?;
+
+    let mut line = String::new();
+    loop {
+        write!(writer, "day{day}> ")?;
+        writer.flush()?;
+
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+
+        let line = line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        // Invalid arguments can make the parsers panic, those are shown as errors instead
+        let output = match command {
+            "" => continue,
+            "quit" | "exit" => return Ok(()),
+            "help" => Ok(format!("{COMMON_HELP}\n{}", session.help())),
+            "part1" | "part2" => catch_quietly(|| solve(day, command, input)).and_then(|r| r),
+            _ => catch_quietly(|| session.eval(command, args.trim())).and_then(|r| r),
+        };
+
+        match output {
+            Ok(output) => writeln!(writer, "{output}")?,
+            Err(e) => writeln!(writer, "error: {e}")?,
+        }
+    }
+}
+
+fn solve(day: u8, command: &str, input: &str) -> Result<String, String> {
+    let part = if command == "part1" { 1 } else { 2 };
+    let solution = days::get(day)
+        .and_then(|d| d.solve(part, input))
+        .ok_or_else(|| format!("day {day} not implemented"))?;
+
+    solution
+        .answer
+        .map(|answer| format!("{answer} ({:.2?})", solution.elapsed))
+}
+
+trait Session {
+    /// Help for the day specific commands
+    fn help(&self) -> &'static str;
+
+    /// Runs one command and returns it's output
+    fn eval(&self, command: &str, args: &str) -> Result<String, String>;
+}
+
+fn unknown_command(command: &str) -> Result<String, String> {
+    Err(format!("unknown command `{command}`, see `help`"))
+}
+
+struct Day5 {
+    seeds: Vec<u64>,
+    almanac: day5::Almanac,
+}
+
+impl Day5 {
+    fn new(input: &str) -> parse::Result<Self> {
+        let (seeds, almanac) = day5::parse_part1(input)?;
+        Ok(Self { seeds, almanac })
+    }
+}
+
+impl Session for Day5 {
+    fn help(&self) -> &'static str {
+        "  seed <n>          map the seed through every map of the almanac
+  seeds             show the location of every seed in the input"
+    }
+
+    fn eval(&self, command: &str, args: &str) -> Result<String, String> {
+        match command {
+            "seed" => {
+                let seed = args
+                    .parse::<u64>()
+                    .map_err(|e| format!("invalid seed: {e}"))?;
+                let path = self.almanac.get_path(seed);
+                Ok(day5::Almanac::CATEGORIES
+                    .iter()
+                    .zip(path)
+                    .map(|(category, value)| format!("{category} {value}"))
+                    .collect::<Vec<_>>()
+                    .join(" -> "))
+            }
+            "seeds" => Ok(self
+                .seeds
+                .iter()
+                .map(|&seed| {
+                    format!(
+                        "seed {seed} -> location {}",
+                        self.almanac.get_location(seed)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")),
+            _ => unknown_command(command),
+        }
+    }
+}
+
+struct Day7<'a> {
+    /// Bid and ranks for part1 and part2 rules for every hand in the input
+    hands: HashMap<&'a str, (u64, [u64; 2])>,
+}
+
+impl<'a> Day7<'a> {
+    fn new(input: &'a str) -> Self {
+        let mut hands = HashMap::new();
+
+        for (hand, rank) in day7::read_hands_part1(input).iter().rev().zip(1..) {
+            hands.insert(hand.cards, (hand.bid, [rank, 0]));
+        }
+        for (hand, rank) in day7::read_hands_part2(input).iter().rev().zip(1..) {
+            hands.entry(hand.cards).or_insert((hand.bid, [0, 0])).1[1] = rank;
+        }
+
+        Self { hands }
+    }
+}
+
+impl Session for Day7<'_> {
+    fn help(&self) -> &'static str {
+        "  hand <cards>      show the kind of the hand and it's rank if it's in the input"
+    }
+
+    fn eval(&self, command: &str, args: &str) -> Result<String, String> {
+        match command {
+            "hand" => {
+                let is_valid = args.len() == 5 && args.chars().all(|c| "AKQJT98765432".contains(c));
+                if !is_valid {
+                    return Err(format!("invalid hand `{args}`"));
+                }
+
+                let mut output = format!(
+                    "part1: {:?}, part2: {:?}",
+                    day7::HandKind::from_hand_part1(args),
+                    day7::HandKind::from_hand_part2(args)
+                );
+                if let Some((bid, [rank1, rank2])) = self.hands.get(args) {
+                    output += &format!("\nbid {bid}, rank part1: {rank1}, rank part2: {rank2}");
+                }
+                Ok(output)
+            }
+            _ => unknown_command(command),
+        }
+    }
+}
+
+struct Day19<'a> {
+    workflows: HashMap<&'a str, Vec<day19::RuleDef>>,
+}
+
+impl<'a> Day19<'a> {
+    fn new(input: &'a str) -> parse::Result<Self> {
+        Ok(Self {
+            workflows: day19::parse2(input)?,
+        })
+    }
+}
+
+impl Session for Day19<'_> {
+    fn help(&self) -> &'static str {
+        "  eval <part>       show the rules the part matches, for example `eval {x=1,m=2,a=3,s=4}`
+  workflow <name>   show the rules of the workflow"
+    }
+
+    fn eval(&self, command: &str, args: &str) -> Result<String, String> {
+        match command {
+            "eval" => {
+                let part = day19::parse_parts(args)
+                    .next()
+                    .ok_or_else(|| String::from("missing part"))?
+                    .map_err(|e| e.to_string())?;
+                let trace = day19::trace_part(&self.workflows, &part).map_err(|e| e.to_string())?;
+
+                let mut output = String::new();
+                for (workflow, rule) in &trace {
+                    output += &format!("{workflow}: {rule}\n");
+                }
+                let accepted = trace.last().is_some_and(|(_, rule)| rule.is_accept());
+                output += if accepted { "accepted" } else { "rejected" };
+                Ok(output)
+            }
+            "workflow" => {
+                let rules = self
+                    .workflows
+                    .get(args)
+                    .ok_or_else(|| format!("unknown workflow `{args}`"))?;
+                Ok(rules
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","))
+            }
+            _ => unknown_command(command),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_commands(day: u8, input: &str, commands: &str) -> String {
+        let mut output = Vec::new();
+        repl(day, input, commands.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_day5() {
+        let output = run_commands(5, day5::INPUT, "seed 0\nseed x\nfoo\nquit\nseed 1\n");
+        assert!(output.contains("seed 0 -> soil "), "{output}");
+        assert!(output.contains("error: invalid seed"), "{output}");
+        assert!(output.contains("error: unknown command `foo`"), "{output}");
+        // quit stops the repl
+        assert!(!output.contains("seed 1 -> "), "{output}");
+    }
+
+    #[test]
+    fn test_day7() {
+        let input = indoc::indoc! {"
+        32T3K 765
+        T55J5 684
+        KK677 28
+        KTJJT 220
+        QQQJA 483
+        "};
+        let output = run_commands(7, input, "hand KTJJT\nhand QQQ\npart1\n");
+        assert!(
+            output.contains("part1: TwoPair, part2: FourOfAKind"),
+            "{output}"
+        );
+        assert!(
+            output.contains("bid 220, rank part1: 2, rank part2: 5"),
+            "{output}"
+        );
+        assert!(output.contains("error: invalid hand `QQQ`"), "{output}");
+        assert!(output.contains("6440 ("), "{output}");
+    }
+
+    #[test]
+    fn test_day19() {
+        let input = indoc::indoc! {"
+        px{a<2006:qkq,m>2090:A,rfg}
+        pv{a>1716:R,A}
+        lnx{m>1548:A,A}
+        rfg{s<537:gd,x>2440:R,A}
+        qs{s>3448:A,lnx}
+        qkq{x<1416:A,crn}
+        crn{x>2662:A,R}
+        in{s<1351:px,qqz}
+        qqz{s>2770:qs,m<1801:hdj,R}
+        gd{a>3333:R,R}
+        hdj{m>838:A,pv}
+
+        {x=787,m=2655,a=1222,s=2876}
+        "};
+        let commands = "eval {x=1679,m=44,a=2067,s=496}\nworkflow px\neval {x=1}\n";
+        let output = run_commands(19, input, commands);
+        assert!(
+            output.contains("in: s<1351:px\npx: rfg\nrfg: s<537:gd\ngd: R\nrejected"),
+            "{output}"
+        );
+        assert!(output.contains("a<2006:qkq,m>2090:A,rfg"), "{output}");
+        assert!(
+            output.contains("error: expected 4 values, found 1"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let error = repl(5, "seeds: 1 x\n", "".as_bytes(), Vec::new()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid number `x`: invalid digit found in string"
+        );
+    }
+
+    /// Writer which fails after its first two writes, like a pipe closed by the reader
+    struct ClosedPipe(usize);
+
+    impl Write for ClosedPipe {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += 1;
+            if self.0 > 2 {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_closed_output() {
+        let error = repl(5, day5::INPUT, "seed x\n".as_bytes(), ClosedPipe(0)).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<io::Error>().map(io::Error::kind),
+            Some(io::ErrorKind::BrokenPipe)
+        );
+    }
+
+    #[test]
+    fn test_unsupported_day() {
+        assert!(repl(1, "", "".as_bytes(), Vec::new()).is_err());
+    }
+}
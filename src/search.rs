@@ -0,0 +1,548 @@
+//! Shortest paths over implicit state spaces.
+//!
+//! The states are only known through a `successors` function, so they can carry anything besides
+//! the position, like the direction and how far the last straight run went. The searches stop at
+//! the first state for which `is_goal` returns `true`, pass `|_| false` to explore everything
+//! reachable.
+//!
+//! The cheapest known cost and the predecessor of each reached state are kept in a [`Visited`]
+//! store, either a `HashMap` or a [`Dense`] array indexed by a state encoder, which is a lot faster
+//! when the states can be numbered.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+/// How a state was reached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Visit<S> {
+    pub cost: u64,
+    /// The previous state on the cheapest known path, `None` for the start states
+    pub parent: Option<S>,
+}
+
+/// Store of the reached states
+pub trait Visited<S> {
+    fn get(&self, state: &S) -> Option<&Visit<S>>;
+
+    fn insert(&mut self, state: S, visit: Visit<S>);
+}
+
+impl<S: Hash + Eq> Visited<S> for HashMap<S, Visit<S>> {
+    fn get(&self, state: &S) -> Option<&Visit<S>> {
+        HashMap::get(self, state)
+    }
+
+    fn insert(&mut self, state: S, visit: Visit<S>) {
+        HashMap::insert(self, state, visit);
+    }
+}
+
+/// Array of visits indexed by `encode(state)`, which must be below the `len` given to [`Dense::new`]
+pub struct Dense<S, F> {
+    encode: F,
+    visits: Vec<Option<Visit<S>>>,
+}
+
+impl<S, F: Fn(&S) -> usize> Dense<S, F> {
+    pub fn new(len: usize, encode: F) -> Self {
+        Dense {
+            encode,
+            visits: std::iter::repeat_with(|| None).take(len).collect(),
+        }
+    }
+}
+
+impl<S, F: Fn(&S) -> usize> Visited<S> for Dense<S, F> {
+    fn get(&self, state: &S) -> Option<&Visit<S>> {
+        self.visits[(self.encode)(state)].as_ref()
+    }
+
+    fn insert(&mut self, state: S, visit: Visit<S>) {
+        let index = (self.encode)(&state);
+        self.visits[index] = Some(visit);
+    }
+}
+
+/// Result of a search
+#[derive(Debug, Clone)]
+pub struct Found<S, V> {
+    /// The first goal that was reached, `None` if there is no path to a goal
+    pub goal: Option<S>,
+    pub visited: V,
+}
+
+impl<S: Clone, V: Visited<S>> Found<S, V> {
+    /// Cost of the path to the goal
+    pub fn cost(&self) -> Option<u64> {
+        self.cost_to(self.goal.as_ref()?)
+    }
+
+    /// States from a start to the goal, both included
+    pub fn path(&self) -> Option<Vec<S>> {
+        self.path_to(self.goal.as_ref()?)
+    }
+
+    /// Cost of the cheapest known path to `state`
+    ///
+    /// Only states reached before the goal are final, later ones could still get cheaper.
+    pub fn cost_to(&self, state: &S) -> Option<u64> {
+        self.visited.get(state).map(|visit| visit.cost)
+    }
+
+    /// States from a start to `state`, see [`Found::cost_to`]
+    pub fn path_to(&self, state: &S) -> Option<Vec<S>> {
+        let mut visit = self.visited.get(state)?;
+        let mut path = vec![state.clone()];
+        while let Some(parent) = &visit.parent {
+            path.push(parent.clone());
+            visit = self.visited.get(parent).unwrap();
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Entry of the priority queue, ordered by the smallest `priority` first
+struct Entry<S> {
+    priority: u64,
+    cost: u64,
+    state: S,
+}
+
+impl<S> PartialEq for Entry<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<S> Eq for Entry<S> {}
+
+impl<S> PartialOrd for Entry<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S> Ord for Entry<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+/// Records `visit` if it's cheaper than the known one, returns `true` if it was
+fn relax<S: Clone, V: Visited<S>>(visited: &mut V, state: &S, visit: Visit<S>) -> bool {
+    if visited
+        .get(state)
+        .is_some_and(|known| known.cost <= visit.cost)
+    {
+        return false;
+    }
+    visited.insert(state.clone(), visit);
+    true
+}
+
+/// Dijkstra's algorithm, `successors` returns the next states with the cost of the step
+pub fn dijkstra<S, V, I>(
+    starts: impl IntoIterator<Item = S>,
+    visited: V,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Found<S, V>
+where
+    S: Clone,
+    V: Visited<S>,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    astar(starts, visited, successors, |_| 0, is_goal)
+}
+
+/// A* search, `heuristic` must never be more than the real cost from the state to a goal
+pub fn astar<S, V, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut visited: V,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> u64,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Found<S, V>
+where
+    S: Clone,
+    V: Visited<S>,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    let mut queue = BinaryHeap::new();
+    for state in starts {
+        if relax(
+            &mut visited,
+            &state,
+            Visit {
+                cost: 0,
+                parent: None,
+            },
+        ) {
+            let priority = heuristic(&state);
+            queue.push(Entry {
+                priority,
+                cost: 0,
+                state,
+            });
+        }
+    }
+
+    while let Some(Entry { cost, state, .. }) = queue.pop() {
+        // a cheaper way to the state was found after this entry was queued
+        if visited.get(&state).is_some_and(|known| known.cost < cost) {
+            continue;
+        }
+        if is_goal(&state) {
+            return Found {
+                goal: Some(state),
+                visited,
+            };
+        }
+
+        for (next, step) in successors(&state) {
+            let visit = Visit {
+                cost: cost + step,
+                parent: Some(state.clone()),
+            };
+            if relax(&mut visited, &next, visit) {
+                let priority = cost + step + heuristic(&next);
+                queue.push(Entry {
+                    priority,
+                    cost: cost + step,
+                    state: next,
+                });
+            }
+        }
+    }
+
+    Found {
+        goal: None,
+        visited,
+    }
+}
+
+/// Dijkstra's algorithm with a bucket per cost (Dial's algorithm) instead of a heap
+///
+/// Faster when the step costs are small integers, the memory grows with the largest cost.
+pub fn dial<S, V, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut visited: V,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Found<S, V>
+where
+    S: Clone,
+    V: Visited<S>,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    let mut buckets = vec![Vec::new()];
+    for state in starts {
+        if relax(
+            &mut visited,
+            &state,
+            Visit {
+                cost: 0,
+                parent: None,
+            },
+        ) {
+            buckets[0].push(state);
+        }
+    }
+
+    let mut cost = 0;
+    let mut queued = buckets[0].len();
+    while queued > 0 {
+        let Some(state) = buckets[cost].pop() else {
+            cost += 1;
+            continue;
+        };
+        queued -= 1;
+
+        if visited
+            .get(&state)
+            .is_some_and(|known| known.cost < cost as u64)
+        {
+            continue;
+        }
+        if is_goal(&state) {
+            return Found {
+                goal: Some(state),
+                visited,
+            };
+        }
+
+        for (next, step) in successors(&state) {
+            let next_cost = cost + step as usize;
+            let visit = Visit {
+                cost: next_cost as u64,
+                parent: Some(state.clone()),
+            };
+            if relax(&mut visited, &next, visit) {
+                if buckets.len() <= next_cost {
+                    buckets.resize_with(next_cost + 1, Vec::new);
+                }
+                buckets[next_cost].push(next);
+                queued += 1;
+            }
+        }
+    }
+
+    Found {
+        goal: None,
+        visited,
+    }
+}
+
+/// Breadth first search where every step costs 1
+pub fn bfs<S, V, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut visited: V,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Found<S, V>
+where
+    S: Clone,
+    V: Visited<S>,
+    I: IntoIterator<Item = S>,
+{
+    let mut queue = VecDeque::new();
+    for state in starts {
+        if relax(
+            &mut visited,
+            &state,
+            Visit {
+                cost: 0,
+                parent: None,
+            },
+        ) {
+            queue.push_back((state, 0));
+        }
+    }
+
+    while let Some((state, cost)) = queue.pop_front() {
+        if is_goal(&state) {
+            return Found {
+                goal: Some(state),
+                visited,
+            };
+        }
+
+        for next in successors(&state) {
+            if visited.get(&next).is_none() {
+                let visit = Visit {
+                    cost: cost + 1,
+                    parent: Some(state.clone()),
+                };
+                visited.insert(next.clone(), visit);
+                queue.push_back((next, cost + 1));
+            }
+        }
+    }
+
+    Found {
+        goal: None,
+        visited,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::grid::{Direction, Grid, Pos};
+    use crate::math;
+
+    /// Example of day 17, the cost of entering a block is its digit
+    const CRUCIBLE_INPUT: &str = indoc::indoc! {"
+    2413432311323
+    3215453535623
+    3255245654254
+    3446585845452
+    4546657867536
+    1438598798454
+    4457876987766
+    3637877979653
+    4654967986887
+    4564679986453
+    1224686865563
+    2546548887735
+    4322674655533
+    "};
+
+    /// Position, direction and the length of the straight run so far
+    type Crucible = (Pos, Direction, u8);
+
+    /// Moves which turn or go straight, a crucible goes straight for `min..=max` blocks at a time
+    fn crucible_moves<'a>(
+        grid: &'a Grid<u64>,
+        (min, max): (u8, u8),
+    ) -> impl Fn(&Crucible) -> Vec<(Crucible, u64)> + 'a {
+        move |&(pos, dir, run)| {
+            let mut dirs = Vec::new();
+            if run < max {
+                dirs.push(dir);
+            }
+            if run >= min {
+                dirs.extend([dir.turn_left(), dir.turn_right()]);
+            }
+
+            dirs.into_iter()
+                .filter_map(|next_dir| {
+                    let next = grid.step(pos, next_dir)?;
+                    let run = if next_dir == dir { run + 1 } else { 1 };
+                    Some(((next, next_dir, run), grid[next]))
+                })
+                .collect()
+        }
+    }
+
+    fn crucible_search(input: &str, runs: (u8, u8), strategy: &str) -> Option<u64> {
+        let grid = Grid::parse(input, |c| c.to_digit(10).unwrap() as u64);
+        let end = (grid.rows() - 1, grid.cols() - 1);
+        let starts = [((0, 0), Direction::Right, 0), ((0, 0), Direction::Down, 0)];
+        let moves = crucible_moves(&grid, runs);
+        let is_goal = |&(pos, _, run): &Crucible| pos == end && run >= runs.0;
+
+        let dir_index = |dir| {
+            Direction::ORTHOGONAL
+                .iter()
+                .position(|&d| d == dir)
+                .unwrap()
+        };
+        let encode = |&((row, col), dir, run): &Crucible| {
+            ((row * grid.cols() + col) * 4 + dir_index(dir)) * (runs.1 as usize + 1) + run as usize
+        };
+        let dense = || {
+            Dense::new(
+                grid.rows() * grid.cols() * 4 * (runs.1 as usize + 1),
+                encode,
+            )
+        };
+
+        match strategy {
+            "dijkstra" => dijkstra(starts, HashMap::new(), moves, is_goal).cost(),
+            "dijkstra_dense" => dijkstra(starts, dense(), moves, is_goal).cost(),
+            "astar" => {
+                let heuristic = |&(pos, _, _): &Crucible| math::manhattan(pos, end) as u64;
+                astar(starts, dense(), moves, heuristic, is_goal).cost()
+            }
+            "dial" => dial(starts, dense(), moves, is_goal).cost(),
+            _ => unreachable!(),
+        }
+    }
+
+    const STRATEGIES: [&str; 4] = ["dijkstra", "dijkstra_dense", "astar", "dial"];
+
+    #[test]
+    fn test_crucible() {
+        for strategy in STRATEGIES {
+            assert_eq!(
+                crucible_search(CRUCIBLE_INPUT, (0, 3), strategy),
+                Some(102),
+                "{strategy}"
+            );
+            assert_eq!(
+                crucible_search(CRUCIBLE_INPUT, (4, 10), strategy),
+                Some(94),
+                "{strategy}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_unreachable() {
+        // an ultra crucible can't go straight for 4 blocks here
+        let input = "111\n111\n111\n";
+        for strategy in STRATEGIES {
+            assert_eq!(crucible_search(input, (4, 10), strategy), None);
+        }
+    }
+
+    #[test]
+    fn test_bfs_path() {
+        let grid = Grid::parse(
+            indoc::indoc! {"
+            S.#.
+            .##.
+            ...E
+            "},
+            |c| c,
+        );
+        let start = grid.position(|&c| c == 'S').unwrap();
+        let successors = |&pos: &Pos| {
+            grid.neighbours4(pos)
+                .filter(|&(_, next)| grid[next] != '#')
+                .map(|(_, next)| next)
+                .collect::<Vec<_>>()
+        };
+
+        let found = bfs([start], HashMap::new(), successors, |&pos| grid[pos] == 'E');
+        assert_eq!(found.cost(), Some(5));
+        assert_eq!(
+            found.path().unwrap(),
+            [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2), (2, 3)]
+        );
+
+        // without a goal everything reachable is explored
+        let found = bfs([start], HashMap::new(), successors, |_| false);
+        assert_eq!(found.goal, None);
+        assert_eq!(found.cost_to(&(0, 3)), Some(7));
+        assert_eq!(found.path_to(&(0, 2)), None);
+    }
+
+    /// Cheapest costs from node 0 with Bellman-Ford
+    fn bellman_ford(nodes: usize, edges: &[(usize, usize, u64)]) -> Vec<Option<u64>> {
+        let mut costs = vec![None; nodes];
+        costs[0] = Some(0);
+        for _ in 0..nodes {
+            for &(from, to, cost) in edges {
+                if let Some(c) = costs[from] {
+                    if costs[to].is_none_or(|known| c + cost < known) {
+                        costs[to] = Some(c + cost);
+                    }
+                }
+            }
+        }
+        costs
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_against_bellman_ford(
+            edges in prop::collection::vec((0..15usize, 0..15usize, 0..10u64), 0..60),
+            goal in 0..15usize,
+        ) {
+            let expected = bellman_ford(15, &edges)[goal];
+            let successors = |&node: &usize| {
+                edges
+                    .iter()
+                    .filter(move |&&(from, _, _)| from == node)
+                    .map(|&(_, to, cost)| (to, cost))
+                    .collect::<Vec<_>>()
+            };
+
+            let found = dijkstra([0], HashMap::new(), successors, |&n| n == goal);
+            prop_assert_eq!(found.cost(), expected);
+            if let Some(path) = found.path() {
+                // the path follows the edges and adds up to the cost
+                let mut cost = 0;
+                for step in path.windows(2) {
+                    cost += successors(&step[0])
+                        .into_iter()
+                        .filter(|&(to, _)| to == step[1])
+                        .map(|(_, c)| c)
+                        .min()
+                        .unwrap();
+                }
+                prop_assert_eq!(path[0], 0);
+                prop_assert_eq!(Some(cost), expected);
+            }
+
+            let found = dial([0], Dense::new(15, |&n: &usize| n), successors, |&n| n == goal);
+            prop_assert_eq!(found.cost(), expected);
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! Small http server that exposes the solutions as a REST API.
+//!
+//! * `GET /days` lists the registered days
+//! * `POST /days/{day}/parts/{part}` solves given part with the request body as input
+
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::days;
+
+/// What a request may cost the server
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Largest accepted request body in bytes
+    pub max_body: u64,
+    /// How long a solver may run before the request fails
+    pub timeout: Duration,
+    /// Number of solvers which may run at the same time
+    pub max_solvers: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            // the puzzle inputs are a few dozen kilobytes
+            max_body: 1 << 20,
+            timeout: Duration::from_secs(10),
+            max_solvers: thread::available_parallelism().map_or(4, |n| n.get()),
+        }
+    }
+}
+
+/// Serves the API on `host`, which is only reachable from the same machine for `127.0.0.1`
+pub fn serve(host: &str, port: u16) -> anyhow::Result<()> {
+    let server = Server::http((host, port)).map_err(|e| anyhow::anyhow!(e))?;
+    println!("listening on http://{}", server.server_addr());
+    serve_requests(&server, Limits::default());
+    Ok(())
+}
+
+/// Handles every request on its own thread, so a slow client doesn't hold up the others
+fn serve_requests(server: &Server, limits: Limits) {
+    let running = Arc::new(AtomicUsize::new(0));
+    for request in server.incoming_requests() {
+        let running = Arc::clone(&running);
+        thread::spawn(move || serve_request(request, limits, running));
+    }
+}
+
+fn serve_request(mut request: Request, limits: Limits, running: Arc<AtomicUsize>) {
+    let (status, value) = match read_body(&mut request, limits.max_body) {
+        Ok(body) => {
+            let method = request.method().clone();
+            let url = request.url().to_string();
+            handle_with_limits(method, url, body, limits, running)
+        }
+        Err(response) => response,
+    };
+
+    if let Err(e) = respond(request, status, value) {
+        eprintln!("failed to send response: {e}");
+    }
+}
+
+fn read_body(request: &mut Request, max_body: u64) -> Result<String, (u16, Value)> {
+    let too_large = || {
+        let error = format!("the body is larger than {max_body} bytes");
+        (413, json!({ "error": error }))
+    };
+    if request
+        .body_length()
+        .is_some_and(|len| len as u64 > max_body)
+    {
+        return Err(too_large());
+    }
+
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(max_body + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| (400, json!({ "error": format!("failed to read body: {e}") })))?;
+    if body.len() as u64 > max_body {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+/// One of the [`Limits::max_solvers`] solvers, which gives its place back when it is dropped
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    /// Returns `None` if `max` solvers are already running
+    fn acquire(running: Arc<AtomicUsize>, max: usize) -> Option<Slot> {
+        if running.fetch_add(1, Ordering::SeqCst) >= max {
+            running.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Slot(running))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Runs [`handle`] on a worker thread and gives up on it after [`Limits::timeout`]
+///
+/// A thread can't be stopped, so a solver which timed out keeps running. The worker thread
+/// owns its [`Slot`], so the solver counts against [`Limits::max_solvers`] until the thread
+/// ends, even if it panics.
+fn handle_with_limits(
+    method: Method,
+    url: String,
+    body: String,
+    limits: Limits,
+    running: Arc<AtomicUsize>,
+) -> (u16, Value) {
+    let Some(slot) = Slot::acquire(running, limits.max_solvers) else {
+        return (503, json!({ "error": "too many solvers are running" }));
+    };
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _slot = slot;
+        let response = handle(&method, &url, &body);
+        let _ = tx.send(response);
+    });
+
+    match rx.recv_timeout(limits.timeout) {
+        Ok(response) => response,
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            let error = format!("no answer within {:?}", limits.timeout);
+            (504, json!({ "error": error }))
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => (500, json!({ "error": "the solver failed" })),
+    }
+}
+
+fn respond(request: Request, status: u16, value: Value) -> std::io::Result<()> {
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let response = Response::from_string(value.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    request.respond(response)
+}
+
+/// Returns the status code and the json body of the response
+fn handle(method: &Method, url: &str, body: &str) -> (u16, Value) {
+    let path = url.split_once('?').map_or(url, |(path, _)| path);
+    let segments = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+
+    match (method, segments.as_slice()) {
+        (Method::Get, ["days"]) => {
+            let days = days::DAYS
+                .iter()
+                .map(|d| json!({ "day": d.day, "parts": [1, 2], "solved": d.is_solved() }))
+                .collect::<Vec<_>>();
+            (200, json!({ "days": days }))
+        }
+        (Method::Post, ["days", day, "parts", part]) => solve(day, part, body),
+        (_, ["days"] | ["days", _, "parts", _]) => (405, json!({ "error": "method not allowed" })),
+        _ => (404, json!({ "error": format!("unknown path `{path}`") })),
+    }
+}
+
+fn solve(day: &str, part: &str, input: &str) -> (u16, Value) {
+    let (Ok(day), Ok(part)) = (day.parse::<u8>(), part.parse::<u8>()) else {
+        return (400, json!({ "error": "day and part must be numbers" }));
+    };
+    let Some(solver) = days::get(day) else {
+        return (
+            404,
+            json!({ "error": format!("day {day} not implemented") }),
+        );
+    };
+    let Some(solution) = solver.solve(part, input) else {
+        return (
+            404,
+            json!({ "error": format!("day {day} has no part {part}") }),
+        );
+    };
+
+    let time_us = solution.elapsed.as_micros() as u64;
+    match solution.answer {
+        Ok(answer) => (
+            200,
+            json!({ "day": day, "part": part, "answer": answer, "time_us": time_us, "error": null }),
+        ),
+        Err(error) => (
+            422,
+            json!({ "day": day, "part": part, "answer": null, "time_us": time_us, "error": error }),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::TcpStream;
+
+    use super::*;
+
+    #[test]
+    fn test_list_days() {
+        let (status, value) = handle(&Method::Get, "/days", "");
+        assert_eq!(status, 200);
+        assert_eq!(value["days"][0]["day"], 1);
+        assert_eq!(value["days"].as_array().unwrap().len(), days::DAYS.len());
+    }
+
+    #[test]
+    fn test_solve() {
+        let (status, value) = handle(&Method::Post, "/days/9/parts/1", "0 3 6 9 12 15");
+        assert_eq!(status, 200);
+        assert_eq!(value["answer"], "18");
+        assert!(value["error"].is_null());
+
+        let (status, value) = handle(&Method::Post, "/days/9/parts/1", "0 3 x");
+        assert_eq!(status, 422);
+        assert!(value["answer"].is_null());
+        assert!(value["error"].is_string());
+
+        let (status, _) = handle(&Method::Post, "/days/9/parts/3", "0 3 6 9 12 15");
+        assert_eq!(status, 404);
+
+        let (status, _) = handle(&Method::Post, "/days/25/parts/1", "");
+        assert_eq!(status, 404);
+
+        let (status, _) = handle(&Method::Get, "/days/9/parts/1", "");
+        assert_eq!(status, 405);
+    }
+
+    /// Starts a server with `limits` on a free port and returns its address
+    fn start(limits: Limits) -> std::net::SocketAddr {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        std::thread::spawn(move || serve_requests(&server, limits));
+        addr
+    }
+
+    /// Sends a POST request and returns the response
+    fn post(addr: std::net::SocketAddr, path: &str, body: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_server() {
+        let addr = start(Limits::default());
+        let response = post(addr, "/days/9/parts/2", "0 3 6 9 12 15");
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+        let (_, json) = response.split_once("\r\n\r\n").unwrap();
+        let value = serde_json::from_str::<Value>(json).unwrap();
+        assert_eq!(value["answer"], "-3");
+    }
+
+    #[test]
+    fn test_limits() {
+        let addr = start(Limits {
+            max_body: 16,
+            ..Limits::default()
+        });
+        let response = post(addr, "/days/9/parts/1", "0 3 6 9 12 15 18 21");
+        assert!(response.starts_with("HTTP/1.1 413"), "{response}");
+
+        // the solver is still starting when the time is up
+        let addr = start(Limits {
+            timeout: Duration::from_nanos(1),
+            ..Limits::default()
+        });
+        let response = post(addr, "/days/16/parts/2", crate::day16::INPUT);
+        assert!(response.starts_with("HTTP/1.1 504"), "{response}");
+
+        let addr = start(Limits {
+            max_solvers: 0,
+            ..Limits::default()
+        });
+        let response = post(addr, "/days/9/parts/1", "0 3 6");
+        assert!(response.starts_with("HTTP/1.1 503"), "{response}");
+    }
+
+    #[test]
+    fn test_slot() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let slot = Slot::acquire(Arc::clone(&running), 1).unwrap();
+        assert!(Slot::acquire(Arc::clone(&running), 1).is_none());
+
+        // the slot is only given back when the thread which owns it ends
+        let (tx, rx) = mpsc::channel::<()>();
+        let worker = thread::spawn(move || {
+            let _slot = slot;
+            let _ = rx.recv();
+        });
+        assert!(Slot::acquire(Arc::clone(&running), 1).is_none());
+        drop(tx);
+        worker.join().unwrap();
+        assert!(Slot::acquire(Arc::clone(&running), 1).is_some());
+
+        let slot = Slot::acquire(Arc::clone(&running), 1).unwrap();
+        let worker = thread::spawn(move || {
+            let _slot = slot;
+            panic!("the solver failed");
+        });
+        assert!(worker.join().is_err());
+        assert_eq!(running.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,465 @@
+//! Pictures of the grids in the inputs, drawn as colored text in the terminal, as SVG images or
+//! as PPM frames of an animation.
+//!
+//! A [`Canvas`] is a grid of [`Cell`]s, each with a character and colors, plus paths drawn on top
+//! of the cells. The days which can be visualized build a sequence of canvases, see [`frames`].
+//! PPM images have no font, so there the character of a cell is drawn as a block of its
+//! foreground color unless it is a space.
+
+use std::fmt::{self, Write};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+
+use crate::grid::{Grid, Pos};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(230, 230, 230);
+    pub const GREY: Rgb = Rgb(110, 110, 110);
+    pub const RED: Rgb = Rgb(220, 50, 47);
+    pub const GREEN: Rgb = Rgb(60, 160, 60);
+    pub const YELLOW: Rgb = Rgb(240, 200, 40);
+    pub const ORANGE: Rgb = Rgb(230, 120, 30);
+    pub const BLUE: Rgb = Rgb(40, 110, 220);
+
+    fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Rgb,
+    /// `None` leaves the background of the terminal or the image
+    pub bg: Option<Rgb>,
+}
+
+impl Cell {
+    /// White character without a background
+    pub fn new(ch: char) -> Self {
+        Cell {
+            ch,
+            fg: Rgb::WHITE,
+            bg: None,
+        }
+    }
+
+    pub fn fg(self, fg: Rgb) -> Self {
+        Cell { fg, ..self }
+    }
+
+    pub fn bg(self, bg: Rgb) -> Self {
+        Cell {
+            bg: Some(bg),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Line {
+    points: Vec<Pos>,
+    color: Rgb,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    cells: Grid<Cell>,
+    paths: Vec<Line>,
+}
+
+impl Canvas {
+    /// Canvas of spaces
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Canvas {
+            cells: Grid::filled(rows, cols, Cell::new(' ')),
+            paths: Vec::new(),
+        }
+    }
+
+    /// Canvas with a cell for every tile of `grid`
+    pub fn from_grid<T>(grid: &Grid<T>, f: impl FnMut(&T) -> Cell) -> Self {
+        Canvas {
+            cells: grid.map(f),
+            paths: Vec::new(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.cells.rows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cells.cols()
+    }
+
+    pub fn cell(&self, pos: Pos) -> &Cell {
+        &self.cells[pos]
+    }
+
+    pub fn set(&mut self, pos: Pos, cell: Cell) {
+        self.cells[pos] = cell;
+    }
+
+    /// Sets the background of the cells at `positions`
+    pub fn highlight(&mut self, positions: impl IntoIterator<Item = Pos>, color: Rgb) {
+        for pos in positions {
+            self.cells[pos].bg = Some(color);
+        }
+    }
+
+    /// Draws a line through the centers of the cells at `points` on top of the cells
+    ///
+    /// In the terminal the characters on the path get its color instead.
+    pub fn path(&mut self, points: impl IntoIterator<Item = Pos>, color: Rgb) {
+        self.paths.push(Line {
+            points: points.into_iter().collect(),
+            color,
+        });
+    }
+
+    /// The characters with ANSI escapes for 24-bit colors, one line per row
+    pub fn ansi(&self) -> String {
+        let mut fg = self.cells.map(|cell| cell.fg);
+        for path in &self.paths {
+            for &pos in &path.points {
+                fg[pos] = path.color;
+            }
+        }
+
+        let mut out = String::new();
+        for (r, row) in self.cells.iter_rows().enumerate() {
+            let mut current = (None, None);
+            for (c, cell) in row.iter().enumerate() {
+                let colors = (Some(fg[(r, c)]), cell.bg);
+                if colors.0 != current.0 {
+                    let Rgb(red, green, blue) = fg[(r, c)];
+                    write!(out, "\x1b[38;2;{red};{green};{blue}m").unwrap();
+                }
+                if colors.1 != current.1 {
+                    match cell.bg {
+                        Some(Rgb(red, green, blue)) => {
+                            write!(out, "\x1b[48;2;{red};{green};{blue}m").unwrap()
+                        }
+                        None => out.push_str("\x1b[49m"),
+                    }
+                }
+                current = colors;
+                out.push(cell.ch);
+            }
+            out.push_str("\x1b[0m\n");
+        }
+        out
+    }
+
+    /// SVG image where every cell is a square of `cell_size` pixels
+    pub fn svg(&self, cell_size: u32) -> String {
+        let size = cell_size as usize;
+        let (width, height) = (self.cols() * size, self.rows() * size);
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        )
+        .unwrap();
+        writeln!(
+            out,
+            r#"<rect width="{width}" height="{height}" fill="{}"/>"#,
+            Rgb::BLACK.hex()
+        )
+        .unwrap();
+
+        for ((r, c), cell) in self.cells.positions().zip(self.cells.iter()) {
+            if let Some(bg) = cell.bg {
+                writeln!(
+                    out,
+                    r#"<rect x="{}" y="{}" width="{size}" height="{size}" fill="{}"/>"#,
+                    c * size,
+                    r * size,
+                    bg.hex()
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(
+            out,
+            r#"<g font-family="monospace" font-size="{size}" text-anchor="middle" dominant-baseline="central">"#
+        )
+        .unwrap();
+        for ((r, c), cell) in self.cells.positions().zip(self.cells.iter()) {
+            if !cell.ch.is_whitespace() {
+                writeln!(
+                    out,
+                    r#"<text x="{}" y="{}" fill="{}">{}</text>"#,
+                    c * size + size / 2,
+                    r * size + size / 2,
+                    cell.fg.hex(),
+                    XmlEscaped(cell.ch)
+                )
+                .unwrap();
+            }
+        }
+        writeln!(out, "</g>").unwrap();
+
+        for path in &self.paths {
+            let points = path
+                .points
+                .iter()
+                .map(|&(r, c)| format!("{},{}", c * size + size / 2, r * size + size / 2))
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(
+                out,
+                r#"<polyline points="{points}" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round"/>"#,
+                path.color.hex(),
+                (size / 4).max(1)
+            )
+            .unwrap();
+        }
+
+        writeln!(out, "</svg>").unwrap();
+        out
+    }
+
+    /// Binary PPM image where every cell is a square of `scale` pixels
+    pub fn ppm(&self, scale: u32) -> Vec<u8> {
+        let scale = scale.max(1) as usize;
+        let mut image = Image::new(self.cols() * scale, self.rows() * scale);
+
+        let margin = scale / 4;
+        for ((r, c), cell) in self.cells.positions().zip(self.cells.iter()) {
+            let (x, y) = (c * scale, r * scale);
+            if let Some(bg) = cell.bg {
+                image.fill(x, y, scale, scale, bg);
+            }
+            if !cell.ch.is_whitespace() {
+                let size = scale - 2 * margin;
+                image.fill(x + margin, y + margin, size, size, cell.fg);
+            }
+        }
+
+        let thickness = (scale / 4).max(1);
+        let center = |(r, c): Pos| (c * scale + scale / 2, r * scale + scale / 2);
+        for path in &self.paths {
+            for (&from, &to) in path.points.iter().zip(path.points.iter().skip(1)) {
+                image.line(center(from), center(to), thickness, path.color);
+            }
+            if let [point] = path.points[..] {
+                image.line(center(point), center(point), thickness, path.color);
+            }
+        }
+
+        image.ppm()
+    }
+}
+
+/// The characters without colors
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.iter_rows() {
+            for cell in row {
+                write!(f, "{}", cell.ch)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+struct XmlEscaped(char);
+
+impl fmt::Display for XmlEscaped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            '<' => write!(f, "&lt;"),
+            '>' => write!(f, "&gt;"),
+            '&' => write!(f, "&amp;"),
+            c => write!(f, "{c}"),
+        }
+    }
+}
+
+/// RGB pixels, row by row
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![Rgb::BLACK; width * height],
+        }
+    }
+
+    /// Fills a rectangle, the parts outside of the image are left out
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, color: Rgb) {
+        for row in y..(y + height).min(self.height) {
+            let start = row * self.width;
+            let end = start + (x + width).min(self.width);
+            if start + x < end {
+                self.pixels[start + x..end].fill(color);
+            }
+        }
+    }
+
+    /// Draws a line `thickness` pixels wide between two pixels
+    fn line(&mut self, from: (usize, usize), to: (usize, usize), thickness: usize, color: Rgb) {
+        let (dx, dy) = (to.0 as f64 - from.0 as f64, to.1 as f64 - from.1 as f64);
+        let steps = dx.abs().max(dy.abs()).max(1.0) as usize;
+        for i in 0..=steps {
+            let t = i as f64 / steps as f64;
+            let x = (from.0 as f64 + dx * t).round() as usize;
+            let y = (from.1 as f64 + dy * t).round() as usize;
+            let offset = thickness / 2;
+            self.fill(
+                x.saturating_sub(offset),
+                y.saturating_sub(offset),
+                thickness,
+                thickness,
+                color,
+            );
+        }
+    }
+
+    fn ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.reserve(self.pixels.len() * 3);
+        for &Rgb(r, g, b) in &self.pixels {
+            out.extend([r, g, b]);
+        }
+        out
+    }
+}
+
+/// Picks at most `frames` of the steps `1..=len` at even distances, the last one is always `len`
+///
+/// Used to animate long processes with a limited number of frames.
+pub fn evenly(len: usize, frames: usize) -> impl Iterator<Item = usize> {
+    let frames = frames.min(len);
+    (1..=frames).map(move |i| i * len / frames)
+}
+
+/// Writes the frames into `dir` as `frame0000.ppm`, `frame0001.ppm` and so on
+///
+/// Returns the paths of the written files.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_frames(dir: &Path, frames: &[Canvas], scale: u32) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut paths = Vec::new();
+    for (i, frame) in frames.iter().enumerate() {
+        let path = dir.join(format!("frame{i:04}.ppm"));
+        std::fs::write(&path, frame.ppm(scale))?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Frames of the visualization of a day's input, the last frame is the final picture
+///
+/// Returns `None` if the day can't be visualized.
+pub fn frames(day: u8, input: &str) -> Option<Vec<Canvas>> {
+    Some(match day {
+        10 => crate::day10::visualize(input),
+        14 => crate::day14::visualize(input),
+        16 => crate::day16::visualize(input),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canvas() -> Canvas {
+        let grid = Grid::parse("#.<\n..#\n", |c| c);
+        let mut canvas = Canvas::from_grid(&grid, |&c| match c {
+            '#' => Cell::new('#').fg(Rgb::GREY),
+            '.' => Cell::new(' '),
+            c => Cell::new(c).fg(Rgb::RED),
+        });
+        canvas.highlight([(1, 0), (1, 1)], Rgb::BLUE);
+        canvas
+    }
+
+    #[test]
+    fn test_canvas() {
+        let mut canvas = canvas();
+        assert_eq!((canvas.rows(), canvas.cols()), (2, 3));
+        assert_eq!(canvas.to_string(), "# <\n  #\n");
+        assert_eq!(canvas.cell((1, 0)).bg, Some(Rgb::BLUE));
+        assert_eq!(canvas.cell((0, 1)).bg, None);
+
+        canvas.set((0, 1), Cell::new('x'));
+        assert_eq!(canvas.to_string(), "#x<\n  #\n");
+    }
+
+    #[test]
+    fn test_ansi() {
+        let mut canvas = canvas();
+        canvas.path([(0, 2), (1, 2)], Rgb::YELLOW);
+        let expected = concat!(
+            "\x1b[38;2;110;110;110m#",
+            "\x1b[38;2;230;230;230m ",
+            "\x1b[38;2;240;200;40m<\x1b[0m\n",
+            "\x1b[38;2;230;230;230m\x1b[48;2;40;110;220m  ",
+            "\x1b[38;2;240;200;40m\x1b[49m#\x1b[0m\n",
+        );
+        assert_eq!(canvas.ansi(), expected);
+    }
+
+    #[test]
+    fn test_svg() {
+        let mut canvas = canvas();
+        canvas.path([(0, 0), (1, 0)], Rgb::YELLOW);
+        let svg = canvas.svg(10);
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="30" height="20""#)
+        );
+        assert!(svg.contains(r##"<rect x="0" y="10" width="10" height="10" fill="#286edc"/>"##));
+        assert!(svg.contains(r##"<text x="25" y="5" fill="#dc322f">&lt;</text>"##));
+        assert!(svg.contains(r#"<polyline points="5,5 5,15""#));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn test_ppm() {
+        let mut canvas = canvas();
+        canvas.path([(1, 0), (1, 1)], Rgb::YELLOW);
+        let ppm = canvas.ppm(4);
+        let header = b"P6\n12 8\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        let pixels = &ppm[header.len()..];
+        assert_eq!(pixels.len(), 12 * 8 * 3);
+
+        let pixel = |x: usize, y: usize| {
+            let i = (y * 12 + x) * 3;
+            Rgb(pixels[i], pixels[i + 1], pixels[i + 2])
+        };
+        // the margin and the block of a character
+        assert_eq!(pixel(0, 0), Rgb::BLACK);
+        assert_eq!(pixel(1, 1), Rgb::GREY);
+        assert_eq!(pixel(2, 2), Rgb::GREY);
+        assert_eq!(pixel(3, 3), Rgb::BLACK);
+        // a highlighted cell with the path through it
+        assert_eq!(pixel(0, 4), Rgb::BLUE);
+        assert_eq!(pixel(2, 6), Rgb::YELLOW);
+        assert_eq!(pixel(5, 6), Rgb::YELLOW);
+        assert_eq!(pixel(7, 6), Rgb::BLUE);
+    }
+
+    #[test]
+    fn test_evenly() {
+        assert_eq!(evenly(10, 4).collect::<Vec<_>>(), [2, 5, 7, 10]);
+        assert_eq!(evenly(3, 10).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(evenly(0, 10).count(), 0);
+    }
+}
@@ -0,0 +1,32 @@
+//! JS bindings for the solutions.
+//!
+//! Build with `wasm-pack build --target web --out-dir www/pkg --no-default-features --features wasm`,
+//! `www/index.html` is a small page that uses them.
+
+use wasm_bindgen::prelude::*;
+
+use crate::days;
+
+/// Solves given part of the day with `input` and returns the answer.
+///
+/// Invalid input that the parser rejects is returned as an error, but the solvers that don't check
+/// their input panic on it, which aborts the wasm instance.
+#[wasm_bindgen]
+pub fn solve(day: u8, part: u8, input: &str) -> Result<String, JsError> {
+    let solver = days::get(day)
+        .ok_or_else(|| JsError::new(&format!("day {day} is not implemented")))?
+        .part(part)
+        .ok_or_else(|| JsError::new(&format!("day {day} has no part {part}")))?;
+
+    solver(input).map_err(|e| JsError::new(&e))
+}
+
+/// Returns the list of solved days
+#[wasm_bindgen]
+pub fn days() -> Vec<u8> {
+    days::DAYS
+        .iter()
+        .filter(|d| d.is_solved())
+        .map(|d| d.day)
+        .collect()
+}